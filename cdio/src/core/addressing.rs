@@ -1,14 +1,31 @@
-use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::{
+    any, fmt,
+    marker::PhantomData,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
 
-use derive_more::{Display, From, Into};
+use derive_more::{From, Into};
+use thiserror::Error;
+
+use crate::core::{
+    constants::{FRAMES_PER_SECOND, PREGAP_OFFSET},
+    msf::Msf,
+};
 
 /// Newtype representing a Logical Block Address (LBA)
 ///
 /// The LBA is the number that a Host uses to reference Logical Blocks on a block storage device.
 #[repr(transparent)]
-#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, PartialOrd, Ord, From, Into)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, From, Into)]
 pub struct Lba(i32);
 
+/// Prints the address as a plain number, honoring width and alignment like an `i32` does.
+impl fmt::Display for Lba {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
 // macro_rules! lba {
 //     ($e:expr) => {
 //         const {
@@ -60,47 +77,208 @@ impl SubAssign<i32> for Lba {
     }
 }
 
-// impl From<Msf> for Lba {
-//     /* The following comes strainght from the MMC-6 spec (Table 677) */
-//     fn from(value: Msf) -> Self {
-//         let offset_lba =
-//             (value.minute() as i32 * 60 + value.second() as i32) * 75 + value.frame() as i32;
+/// Subtracted from the frame count of MSFs 90:00:00 and above, which address the Lead-in.
+const MSF_LEAD_IN_OFFSET: i32 = 450150;
 
-//         // Range from MMC-6: 00:00:00 <= MSF <= 89:59:74
-//         if value <= Msf::new_unchecked(89, 59, 74) {
-//             Self(offset_lba - PREGAP_OFFSET as i32)
-//         }
-//         // Range from MMC-6: 90:00:00 <= MSF <= 99:59:74
-//         else {
-//             Self(offset_lba - 450150)
-//         }
-//     }
-// }
+/// A value that's out of range for the address type `A`, e.g. an LBA past the last MSF.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{value} is out of range for {}", self.target())]
+pub struct AddressError<A> {
+    /// The offending value, as an LBA for conversions between address types.
+    pub value: i64,
+    _target: PhantomData<A>,
+}
 
-// ! THIS NEEDS TO BE MOVED
-// ! In short, this conversion is only technically defined for the WRITE command, and it not
-// ! technically, a top-level rule. This conversion should be moved to a module dedicated to
-// ! handling the WRITE command. Currently I'm going to proceed under the assumption that in the
-// ! typically LBAs and MSFs should not be converted to and from each other.
-// impl From<Lba> for Msf {
-//     /* The following comes strainght from the MMC-6 spec (Table 677) */
-//     fn from(value: Lba) -> Self {
-//         let raw_lba: i32 = value.into();
-
-//         if (-150..=404849).contains(&raw_lba) {
-//             let m = (raw_lba + PREGAP_OFFSET as i32) / (60 * 75);
-//             let s = (raw_lba + PREGAP_OFFSET as i32 - m * 60 * 75) / 75;
-//             let f = raw_lba + PREGAP_OFFSET as i32 - m * 60 * 75 - s * 75;
-
-//             // We should be mathmatecially garunteed safe truncation here
-//             Msf::new_unchecked(m as u8, s as u8, f as u8)
-//         } else {
-//             let m = (raw_lba + 450150) / (60 * 75);
-//             let s = (raw_lba + 450150 - m * 60 * 75) / 75;
-//             let f = raw_lba + 450150 - m * 60 * 75 - s * 75;
-
-//             // We should be mathmatecially garunteed safe truncation here
-//             Msf::new_unchecked(m as u8, s as u8, f as u8)
-//         }
-//     }
-// }
+impl<A> AddressError<A> {
+    pub fn new(value: impl Into<i64>) -> Self {
+        Self {
+            value: value.into(),
+            _target: PhantomData,
+        }
+    }
+
+    /// The name of the address type the value couldn't be converted to, e.g. `"Msf"`.
+    pub fn target(&self) -> &'static str {
+        let name = any::type_name::<A>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+}
+
+/// An address on a disc which can be converted to and from a Logical Block Address.
+pub trait Address: Sized {
+    fn to_lba(&self) -> Lba;
+
+    /// Fails if `lba` isn't representable by this address type.
+    fn from_lba(lba: Lba) -> Result<Self, AddressError<Self>>;
+}
+
+impl TryFrom<i64> for Lba {
+    type Error = AddressError<Lba>;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        i32::try_from(value)
+            .map(Self)
+            .map_err(|_| AddressError::new(value))
+    }
+}
+
+impl Address for Lba {
+    #[inline]
+    fn to_lba(&self) -> Lba {
+        *self
+    }
+
+    #[inline]
+    fn from_lba(lba: Lba) -> Result<Self, AddressError<Self>> {
+        Ok(lba)
+    }
+}
+
+/// Newtype representing a Logical Sector Number (LSN)
+///
+/// As in ECMA-130 and libcdio, the LSN counts sectors from the start of track 1 (00:02:00), after
+/// the 150 frame pregap, so it's numerically the same as the LBA. Note libcdio's own "LBA" is the
+/// one that counts from 00:00:00, and so is 150 higher.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, From, Into)]
+pub struct Lsn(i32);
+
+/// See the [`Display`](fmt::Display) impl of [`Lba`].
+impl fmt::Display for Lsn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Address for Lsn {
+    #[inline]
+    fn to_lba(&self) -> Lba {
+        Lba(self.0)
+    }
+
+    #[inline]
+    fn from_lba(lba: Lba) -> Result<Self, AddressError<Self>> {
+        Ok(Self(lba.0))
+    }
+}
+
+impl TryFrom<Lba> for Lsn {
+    type Error = AddressError<Lsn>;
+
+    fn try_from(lba: Lba) -> Result<Self, Self::Error> {
+        Self::from_lba(lba)
+    }
+}
+
+// The conversions below come straight from MMC-6 Table 677. Strictly speaking the spec only
+// defines them for addressing the WRITE commands, MSFs and LBAs reported by the Drive should be
+// preferred over converting between the two where possible.
+impl Address for Msf {
+    fn to_lba(&self) -> Lba {
+        let offset_lba = (i32::from(u8::from(self.min())) * 60 + i32::from(u8::from(self.sec())))
+            * FRAMES_PER_SECOND as i32
+            + i32::from(u8::from(self.frame()));
+
+        // Range from MMC-6: 00:00:00 <= MSF <= 89:59:74
+        if u8::from(self.min()) <= 89 {
+            Lba(offset_lba - PREGAP_OFFSET as i32)
+        }
+        // Range from MMC-6: 90:00:00 <= MSF <= 99:59:74
+        else {
+            Lba(offset_lba - MSF_LEAD_IN_OFFSET)
+        }
+    }
+
+    fn from_lba(lba: Lba) -> Result<Self, AddressError<Self>> {
+        let offset_lba = match lba.0 {
+            -150..=404849 => lba.0 + PREGAP_OFFSET as i32,
+            -45150..=-151 => lba.0 + MSF_LEAD_IN_OFFSET,
+            _ => return Err(AddressError::new(lba.0)),
+        };
+
+        // Mathematically guaranteed to be in range by the match above
+        Msf::from_frames(offset_lba as u32).ok_or(AddressError::new(lba.0))
+    }
+}
+
+impl TryFrom<Lba> for Msf {
+    type Error = AddressError<Msf>;
+
+    fn try_from(lba: Lba) -> Result<Self, Self::Error> {
+        Self::from_lba(lba)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::msf::{Frame, Minute, Second};
+
+    use super::*;
+
+    fn msf(m: u8, s: u8, f: u8) -> Msf {
+        Msf::new(
+            Minute::try_from(m).unwrap(),
+            Second::try_from(s).unwrap(),
+            Frame::try_from(f).unwrap(),
+        )
+    }
+
+    #[test]
+    fn lba_round_trip() {
+        let lba = Lba::from(1234);
+        assert_eq!(Lba::from_lba(lba.to_lba()), Ok(lba));
+    }
+
+    #[test]
+    fn lsn_round_trip() {
+        assert_eq!(Lsn::from(0).to_lba(), Lba::from(0));
+        assert_eq!(Lsn::from(4503).to_lba(), Lba::from(4503));
+        assert_eq!(Lsn::from_lba(Lba::from(-150)), Ok(Lsn::from(-150)));
+
+        // Both count from the start of track 1, not the start of the pregap
+        assert_eq!(Msf::from_lba(Lsn::from(0).to_lba()), Ok(msf(0, 2, 0)));
+    }
+
+    #[test]
+    fn msf_round_trip() {
+        for (msf, lba) in [
+            (msf(0, 0, 0), -150),
+            (msf(0, 2, 0), 0),
+            (msf(1, 2, 3), 4503),
+            (msf(89, 59, 74), 404849),
+            (msf(90, 0, 0), -45150),
+            (msf(99, 59, 74), -151),
+        ] {
+            assert_eq!(msf.to_lba(), Lba::from(lba));
+            assert_eq!(Msf::from_lba(Lba::from(lba)), Ok(msf));
+        }
+
+        assert_eq!(
+            Msf::from_lba(Lba::from(404850)),
+            Err(AddressError::new(404850))
+        );
+        assert_eq!(
+            Msf::from_lba(Lba::from(-45151)),
+            Err(AddressError::new(-45151))
+        );
+    }
+
+    #[test]
+    fn display_as_number() {
+        assert_eq!(format!("{}", Lba::try_from(12345i64).unwrap()), "12345");
+        assert_eq!(format!("{:6}", Lba::from(-150)), "  -150");
+        assert_eq!(format!("{:<6}|", Lsn::from(150)), "150   |");
+    }
+
+    #[test]
+    fn out_of_range_conversions() {
+        let error = Lba::try_from(i64::from(i32::MAX) + 1).unwrap_err();
+        assert_eq!(error.value, 2_147_483_648);
+        assert_eq!(error.to_string(), "2147483648 is out of range for Lba");
+
+        assert_eq!(Lsn::try_from(Lba::from(i32::MAX)), Ok(Lsn::from(i32::MAX)));
+
+        let error = Msf::try_from(Lba::from(404850)).unwrap_err();
+        assert_eq!(error.to_string(), "404850 is out of range for Msf");
+    }
+}
@@ -18,3 +18,6 @@ pub const MAX_TRACKS: u8 = 99;
 
 /// The track number of the leadout section
 pub const LEADOUT_TRACK_NUM: u8 = 0xAA;
+
+/// The POINT of the Lead-in Q sub-channel frames giving the start of the session's Lead-out
+pub const LEADOUT_START_POINT: u8 = 0xA2;
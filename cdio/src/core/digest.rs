@@ -0,0 +1,72 @@
+use md5::Digest;
+
+/// An incremental hash which data can be fed into a chunk at a time, so that large reads can be
+/// verified without holding all of the data in memory.
+pub trait StreamDigest: Default {
+    type Output;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Self::Output;
+}
+
+/// CRC-32 (IEEE), the checksum used by EAC and most rip logs.
+impl StreamDigest for crc32fast::Hasher {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        crc32fast::Hasher::update(self, data)
+    }
+
+    fn finalize(self) -> Self::Output {
+        crc32fast::Hasher::finalize(self)
+    }
+}
+
+impl StreamDigest for md5::Md5 {
+    type Output = [u8; 16];
+
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(self, data)
+    }
+
+    fn finalize(self) -> Self::Output {
+        Digest::finalize(self).into()
+    }
+}
+
+/// Computes both digests in a single pass, e.g. `(crc32fast::Hasher, md5::Md5)`.
+impl<A: StreamDigest, B: StreamDigest> StreamDigest for (A, B) {
+    type Output = (A::Output, B::Output);
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+        self.1.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        (self.0.finalize(), self.1.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_digests() {
+        let mut digest = <(crc32fast::Hasher, md5::Md5)>::default();
+        digest.update(b"The quick brown fox ");
+        digest.update(b"jumps over the lazy dog");
+
+        let (crc, md5) = digest.finalize();
+        assert_eq!(crc, 0x414F_A339);
+        assert_eq!(
+            md5,
+            [
+                0x9E, 0x10, 0x7D, 0x9D, 0x37, 0x2B, 0xB6, 0x82, 0x6B, 0xD8, 0x1D, 0x35, 0x42, 0xA4,
+                0x19, 0xD6
+            ]
+        );
+    }
+}
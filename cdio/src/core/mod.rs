@@ -1,4 +1,5 @@
 pub mod addressing;
 pub mod constants;
+pub mod digest;
 pub mod msf;
 pub mod util;
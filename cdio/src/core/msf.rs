@@ -1,9 +1,15 @@
 use std::fmt;
 
+use bcd::InvalidBcdDigit;
 use deku::{reader::Reader, DekuError, DekuRead, DekuReader};
 use derive_more::{Display, Into};
 use thiserror::Error;
 
+use crate::core::{
+    constants::{FRAMES_PER_MINUTE, FRAMES_PER_SECOND},
+    util::bcd_to_u8,
+};
+
 #[derive(Debug, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Into, Ord, Hash)]
 pub struct Minute(u8);
 
@@ -106,6 +112,18 @@ impl<'a> DekuReader<'a> for Frame {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum MsfBcdError {
+    #[error(transparent)]
+    InvalidBcdDigit(#[from] InvalidBcdDigit),
+    #[error(transparent)]
+    Minute(#[from] MinuteRangeError),
+    #[error(transparent)]
+    Second(#[from] SecondRangeError),
+    #[error(transparent)]
+    Frame(#[from] FrameRangeError),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, DekuRead)]
 pub struct Msf(Minute, Second, Frame);
 
@@ -114,6 +132,32 @@ impl Msf {
         Self(min, sec, frame)
     }
 
+    /// Decodes an MSF stored as BCD, like the times in the Q sub-channel.
+    pub fn from_bcd_bytes(bytes: [u8; 3]) -> Result<Self, MsfBcdError> {
+        Ok(Self(
+            Minute::try_from(bcd_to_u8(bytes[0])?)?,
+            Second::try_from(bcd_to_u8(bytes[1])?)?,
+            Frame::try_from(bcd_to_u8(bytes[2])?)?,
+        ))
+    }
+
+    /// Splits a number of frames into minutes, seconds, and frames, e.g. to express a length of
+    /// time rather than an address. Returns `None` past 99:59:74.
+    pub fn from_frames(frames: u32) -> Option<Self> {
+        let frames_per_minute = u32::from(FRAMES_PER_MINUTE);
+        let frames_per_second = u32::from(FRAMES_PER_SECOND);
+
+        let m = u8::try_from(frames / frames_per_minute).ok()?;
+        let s = (frames % frames_per_minute) / frames_per_second;
+        let f = frames % frames_per_second;
+
+        Some(Self(
+            Minute::try_from(m).ok()?,
+            Second::try_from(s as u8).ok()?,
+            Frame::try_from(f as u8).ok()?,
+        ))
+    }
+
     pub const fn min(&self) -> Minute {
         self.0
     }
@@ -132,3 +176,41 @@ impl fmt::Display for Msf {
         write!(f, "{:02}:{:02}:{:02}", self.0, self.1, self.2)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msf_from_bcd_bytes() {
+        let msf = Msf::from_bcd_bytes([0x12, 0x34, 0x56]).unwrap();
+        assert_eq!(msf.to_string(), "12:34:56");
+
+        assert!(matches!(
+            Msf::from_bcd_bytes([0x00, 0x60, 0x00]),
+            Err(MsfBcdError::Second(_))
+        ));
+        assert!(matches!(
+            Msf::from_bcd_bytes([0x00, 0x00, 0x1A]),
+            Err(MsfBcdError::InvalidBcdDigit(_))
+        ));
+    }
+
+    #[test]
+    fn msf_from_frames() {
+        assert_eq!(Msf::from_frames(0).unwrap().to_string(), "00:00:00");
+        assert_eq!(
+            Msf::from_frames(12 * 4500 + 34 * 75 + 56)
+                .unwrap()
+                .to_string(),
+            "12:34:56"
+        );
+        assert_eq!(
+            Msf::from_frames(99 * 4500 + 59 * 75 + 74)
+                .unwrap()
+                .to_string(),
+            "99:59:74"
+        );
+        assert_eq!(Msf::from_frames(100 * 4500), None);
+    }
+}
@@ -1,3 +1,5 @@
+use bcd::{Bcd, InvalidBcdDigit};
+
 pub struct BitReader(pub u8);
 
 impl BitReader {
@@ -6,3 +8,223 @@ impl BitReader {
         self.0 & mask != 0
     }
 }
+
+/// Packs bit fields into a single byte, the counterpart to [`BitReader`] for building CDBs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BitWriter(pub u8);
+
+impl BitWriter {
+    #[inline]
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Sets the bits in `mask` if `value` is true, otherwise clears them.
+    #[inline]
+    pub const fn set_bit(self, mask: u8, value: bool) -> Self {
+        if value {
+            Self(self.0 | mask)
+        } else {
+            Self(self.0 & !mask)
+        }
+    }
+
+    /// Writes `value` into the field `mask << shift`, where `mask` is the unshifted field width.
+    /// Bits of `value` outside of `mask` are discarded.
+    #[inline]
+    pub const fn set_field(self, shift: u8, mask: u8, value: u8) -> Self {
+        Self((self.0 & !(mask << shift)) | ((value & mask) << shift))
+    }
+}
+
+impl From<BitWriter> for u8 {
+    #[inline]
+    fn from(value: BitWriter) -> Self {
+        value.0
+    }
+}
+
+/// Decodes a single packed BCD byte, e.g. `0x12` to `12`.
+///
+/// Returns an error if either nibble isn't a decimal digit.
+#[inline]
+pub const fn bcd_to_u8(value: u8) -> Result<u8, InvalidBcdDigit> {
+    match Bcd::<1>::from_bcd_bytes([value]) {
+        Ok(_) => Ok((value >> 4) * 10 + (value & 0x0F)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Encodes `value` as a single packed BCD byte, e.g. `12` to `0x12`.
+///
+/// Returns `None` if `value` is greater than 99.
+#[inline]
+pub const fn u8_to_bcd(value: u8) -> Option<u8> {
+    if value > 99 {
+        None
+    } else {
+        Some(((value / 10) << 4) | (value % 10))
+    }
+}
+
+/// Splits a stream of descriptors into one slice per descriptor, e.g. the Feature Descriptors of
+/// GET CONFIGURATION or the sense data descriptors.
+///
+/// Iteration stops at the first descriptor that's cut off by the end of the data, whatever is
+/// left is available from [`DescriptorCursor::remainder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorCursor<'a> {
+    remaining: &'a [u8],
+    layout: DescriptorLayout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DescriptorLayout {
+    Fixed(usize),
+    LengthPrefixed {
+        length_offset: usize,
+        header_len: usize,
+    },
+}
+
+impl<'a> DescriptorCursor<'a> {
+    /// Descriptors that are all `len` bytes long. A `len` of zero yields nothing.
+    pub fn fixed(data: &'a [u8], len: usize) -> Self {
+        Self {
+            remaining: data,
+            layout: DescriptorLayout::Fixed(len),
+        }
+    }
+
+    /// Descriptors with a `header_len` byte header, whose byte at `length_offset` is the number
+    /// of bytes following the header (the Additional Length). Iteration stops at a descriptor
+    /// that would be zero bytes long, which is only possible with no header.
+    pub fn length_prefixed(data: &'a [u8], length_offset: usize, header_len: usize) -> Self {
+        Self {
+            remaining: data,
+            layout: DescriptorLayout::LengthPrefixed {
+                length_offset,
+                header_len,
+            },
+        }
+    }
+
+    /// The data after the last descriptor yielded so far. Non-empty once iteration has ended
+    /// only if the final descriptor was truncated.
+    pub fn remainder(&self) -> &'a [u8] {
+        self.remaining
+    }
+}
+
+impl<'a> Iterator for DescriptorCursor<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = match self.layout {
+            DescriptorLayout::Fixed(len) => len,
+            // Widened before adding, an Additional Length near 255 would overflow a u8
+            DescriptorLayout::LengthPrefixed {
+                length_offset,
+                header_len,
+            } => header_len + usize::from(*self.remaining.get(length_offset)?),
+        };
+        // An empty descriptor wouldn't advance the cursor, yielding it forever
+        if len == 0 {
+            return None;
+        }
+
+        let descriptor = self.remaining.get(..len)?;
+        self.remaining = &self.remaining[len..];
+        Some(descriptor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_clear_bits() {
+        let writer = BitWriter::new().set_bit(0b1000_0001, true);
+        assert_eq!(writer.0, 0b1000_0001);
+
+        let writer = writer.set_bit(0b0000_0001, false);
+        assert_eq!(writer.0, 0b1000_0000);
+    }
+
+    #[test]
+    fn set_field_masks_value() {
+        let byte: u8 = BitWriter::new().set_field(4, 0b1111, 0xAB).into();
+        assert_eq!(byte, 0xB0);
+    }
+
+    #[test]
+    fn set_field_overwrites_only_its_bits() {
+        let byte: u8 = BitWriter(0xFF).set_field(2, 0b111, 0b010).into();
+        assert_eq!(byte, 0b1110_1011);
+    }
+
+    #[test]
+    fn bcd_round_trip() {
+        for value in 0..=99 {
+            let encoded = u8_to_bcd(value).unwrap();
+            assert_eq!(encoded, (value / 10) * 16 + value % 10);
+            assert_eq!(bcd_to_u8(encoded), Ok(value));
+        }
+
+        assert_eq!(u8_to_bcd(100), None);
+    }
+
+    #[test]
+    fn invalid_bcd_digit_is_error() {
+        assert!(bcd_to_u8(0x1A).is_err());
+        assert!(bcd_to_u8(0xA1).is_err());
+    }
+
+    #[test]
+    fn length_prefixed_descriptors() {
+        #[rustfmt::skip]
+        let data = [
+            0x00, 0x01, 0x00, 0x00,
+            0x00, 0x02, 0x00, 0x02, 0xAA, 0xBB,
+            // Claims 0xFF bytes but the data ends
+            0x00, 0x03, 0x00, 0xFF, 0xCC,
+        ];
+
+        let mut descriptors = DescriptorCursor::length_prefixed(&data, 3, 4);
+
+        assert_eq!(descriptors.next(), Some(&data[..4]));
+        assert_eq!(descriptors.next(), Some(&data[4..10]));
+        assert_eq!(descriptors.next(), None);
+        assert_eq!(descriptors.remainder(), &data[10..]);
+
+        // A header cut off before its length byte
+        let mut descriptors = DescriptorCursor::length_prefixed(&data[..2], 3, 4);
+        assert_eq!(descriptors.next(), None);
+    }
+
+    #[test]
+    fn fixed_size_descriptors() {
+        let data = [1, 2, 3, 4, 5, 6, 7];
+
+        let descriptors: Vec<&[u8]> = DescriptorCursor::fixed(&data, 3).collect();
+        assert_eq!(descriptors, [&[1, 2, 3][..], &[4, 5, 6]]);
+
+        let mut descriptors = DescriptorCursor::fixed(&data, 3);
+        descriptors.by_ref().for_each(drop);
+        assert_eq!(descriptors.remainder(), [7]);
+
+        assert_eq!(DescriptorCursor::fixed(&data, 0).next(), None);
+    }
+
+    #[test]
+    fn zero_length_descriptor_ends_iteration() {
+        // Without a header, an Additional Length of zero is an empty descriptor
+        let data = [2, 0xAA, 0, 1, 0xBB];
+
+        let mut descriptors = DescriptorCursor::length_prefixed(&data, 0, 0);
+        assert_eq!(descriptors.next(), Some(&[2, 0xAA][..]));
+        assert_eq!(descriptors.next(), None);
+        assert_eq!(descriptors.remainder(), [0, 1, 0xBB]);
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::addressing::Lba;
+use crate::device::Drive;
+use crate::scsi::mmc::commands::read_capacity::ReadCapacity;
+use crate::scsi::mmc::commands::seek::Seek10;
+use crate::scsi::mmc::commands::test_unit_ready::TestUnitReady;
+use crate::transport::Transport;
+
+/// The times of the seeks issued by [`benchmark_seek`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SeekTimes {
+    /// Each seek's time, in the order they were issued.
+    pub samples: Vec<Duration>,
+    pub min: Duration,
+    pub avg: Duration,
+    pub max: Duration,
+}
+
+impl SeekTimes {
+    fn new(samples: Vec<Duration>) -> Self {
+        let min = samples.iter().min().copied().unwrap_or_default();
+        let max = samples.iter().max().copied().unwrap_or_default();
+        let total: Duration = samples.iter().sum();
+        let avg = total / samples.len().max(1) as u32;
+
+        Self {
+            samples,
+            min,
+            avg,
+            max,
+        }
+    }
+}
+
+/// Times `samples` SEEKs to random LBAs across the disc, to characterize how quickly the Drive
+/// moves its pickup. Each seek is timed by the [`Transport`], which for
+/// [`SgIo`](crate::transport::sgio::SgIo) is the kernel's measurement in milliseconds.
+///
+/// Fails with MEDIUM NOT PRESENT if there's no disc, see
+/// [`Error::is_medium_not_present`](crate::Error::is_medium_not_present).
+pub fn benchmark_seek<T: Transport>(
+    drive: &Drive<T>,
+    samples: u32,
+) -> Result<SeekTimes, crate::Error> {
+    drive.execute(TestUnitReady::new(0.into()))?;
+    let last_lba = drive.execute(ReadCapacity::new(0.into()))?.lba;
+    let sectors = u64::try_from(i32::from(last_lba)).unwrap_or(0) + 1;
+
+    // Only needs to scatter the seeks, not be unpredictable, so a xorshift will do
+    let mut state = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_nanos() as u64)
+        | 1;
+    let mut random_lba = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        Lba::from((state % sectors) as i32)
+    };
+
+    let times = (0..samples)
+        .map(|_| {
+            drive
+                .execute_detailed(Seek10::new(random_lba(), 0.into()))
+                .map(|((), completion)| completion.duration)
+        })
+        .collect::<Result<Vec<Duration>, crate::Error>>()?;
+
+    Ok(SeekTimes::new(times))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[test]
+    fn seek_times_from_each_command() {
+        let millis = Duration::from_millis;
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x00, [])
+                // Last LBA 359999, 2048 byte blocks
+                .with_response(0x25, [0x00, 0x05, 0x7E, 0x3F, 0x00, 0x00, 0x08, 0x00])
+                .with_response(0x2B, [])
+                // TEST UNIT READY and READ CAPACITY, then the seeks
+                .with_durations([millis(1), millis(1), millis(120), millis(85), millis(40)]),
+        );
+
+        let times = benchmark_seek(&drive, 3).unwrap();
+
+        assert_eq!(times.samples, [millis(120), millis(85), millis(40)]);
+        assert_eq!(times.min, millis(40));
+        assert_eq!(times.avg, Duration::from_nanos(81_666_666));
+        assert_eq!(times.max, millis(120));
+
+        let seeks: Vec<i32> = drive
+            .transport()
+            .submitted_cdbs()
+            .iter()
+            .filter(|cdb| cdb[0] == 0x2B)
+            .map(|cdb| i32::from_be_bytes([cdb[2], cdb[3], cdb[4], cdb[5]]))
+            .collect();
+        assert_eq!(seeks.len(), 3);
+        assert!(seeks.iter().all(|lba| (0..360_000).contains(lba)));
+
+        // Neither TEST UNIT READY nor the seeks have a data phase
+        assert_eq!(drive.transport().buffer_lens(), [0, 8, 0, 0, 0]);
+    }
+
+    #[test]
+    fn seek_needs_a_disc() {
+        let drive =
+            Drive::with_transport(MockTransport::new().with_queued_sense(0x00, 0x02, 0x3A, 0x00));
+
+        assert!(benchmark_seek(&drive, 3)
+            .unwrap_err()
+            .is_medium_not_present());
+        assert_eq!(drive.transport().submitted_cdbs().len(), 1);
+    }
+}
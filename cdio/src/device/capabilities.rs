@@ -0,0 +1,159 @@
+use crate::device::Drive;
+use crate::scsi::mmc::commands::get_configuration::{
+    get_configuration_full, GetConfigurationResponse, RtField,
+};
+use crate::scsi::mmc::commands::mode_sense::{
+    capabilities::CapabilitiesPage, ModeSense, PageControl,
+};
+use crate::scsi::mmc::features::profile_list::{MediaFamily, Profile};
+use crate::scsi::mmc::types::FeatureCode;
+use crate::transport::Transport;
+
+/// What media a Drive can read and write, and how fast.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DriveCapabilities {
+    /// Every Profile the Drive reports in its Profile List.
+    pub profiles: Vec<Profile>,
+    /// In kB/s (1000 bytes per second), from the CD/DVD Capabilities and Mechanical Status page.
+    /// `None` if the Drive doesn't return the page or leaves the field zeroed.
+    pub max_read_speed: Option<u16>,
+    /// See [`DriveCapabilities::max_read_speed`].
+    pub max_write_speed: Option<u16>,
+}
+
+impl DriveCapabilities {
+    /// Issues GET CONFIGURATION for the Profile List, and MODE SENSE for the CD/DVD Capabilities
+    /// and Mechanical Status page. The mode page is obsolete, so failing to read it only leaves
+    /// the speeds unknown.
+    pub fn probe<T: Transport>(drive: &Drive<T>) -> Result<Self, crate::Error> {
+        let configuration = get_configuration_full(
+            drive,
+            RtField::Supported,
+            FeatureCode::ProfileList as u16,
+            0.into(),
+        )?;
+
+        let capabilities_page = drive
+            .execute(ModeSense::<CapabilitiesPage>::new(
+                PageControl::Current,
+                0.into(),
+            ))
+            .ok();
+
+        Ok(Self::from_responses(&configuration, capabilities_page))
+    }
+
+    pub fn from_responses(
+        configuration: &GetConfigurationResponse,
+        capabilities_page: Option<CapabilitiesPage>,
+    ) -> Self {
+        let profiles = configuration.profiles().collect();
+
+        let nonzero = |speed: u16| (speed != 0).then_some(speed);
+
+        Self {
+            profiles,
+            max_read_speed: capabilities_page.and_then(|page| nonzero(page.max_read_speed)),
+            max_write_speed: capabilities_page.and_then(|page| nonzero(page.max_write_speed)),
+        }
+    }
+
+    pub fn can_read(&self, family: MediaFamily) -> bool {
+        self.profiles
+            .iter()
+            .any(|profile| profile.media_family() == family)
+    }
+
+    pub fn can_write(&self, family: MediaFamily) -> bool {
+        self.profiles
+            .iter()
+            .any(|profile| profile.media_family() == family && profile.is_writable())
+    }
+
+    /// The maximum read speed as a multiple of `family`'s 1x speed, e.g. 48 for a 48x CD drive,
+    /// or `None` if the Drive can't read the family or didn't report a speed.
+    ///
+    /// The mode page reports a single speed, which Drives report for CD media regardless of what
+    /// else they support, so this is always `None` for other families.
+    pub fn max_read_multiple(&self, family: MediaFamily) -> Option<u16> {
+        (family == MediaFamily::Cd && self.can_read(family))
+            .then_some(cd_speed_multiple(self.max_read_speed?))
+    }
+
+    /// See [`DriveCapabilities::max_read_multiple`].
+    pub fn max_write_multiple(&self, family: MediaFamily) -> Option<u16> {
+        (family == MediaFamily::Cd && self.can_write(family))
+            .then_some(cd_speed_multiple(self.max_write_speed?))
+    }
+}
+
+/// Rounds `speed` in kB/s to the nearest multiple of CD 1x, 75 sectors of 2352 bytes per second.
+fn cd_speed_multiple(speed: u16) -> u16 {
+    const SINGLE_SPEED: u32 = 176;
+
+    ((u32::from(speed) + SINGLE_SPEED / 2) / SINGLE_SPEED) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[rustfmt::skip]
+    const CONFIGURATION: &[u8] = &[
+        0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, 0x08,
+        // Profile List
+        0x00, 0x00, 0x03, 0x10,
+        0x00, 0x08, 0x01, 0x00,
+        0x00, 0x09, 0x00, 0x00,
+        0x00, 0x0A, 0x00, 0x00,
+        0x00, 0x10, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn capabilities_from_features_and_mode_page() {
+        #[rustfmt::skip]
+        let capabilities_page: &[u8] = &[
+            0x00, 0x1C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x2A, 0x14,
+            0x0B, 0x03, 0x71, 0x73, 0x29, 0x23,
+            // 48x CD
+            0x21, 0x14, 0x01, 0x00,
+            0x08, 0x00, 0x21, 0x14, 0x00, 0x00,
+            // 24x CD
+            0x10, 0x8A, 0x10, 0x8A,
+        ];
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x46, CONFIGURATION)
+                .with_response(0x5A, capabilities_page),
+        );
+
+        let capabilities = DriveCapabilities::probe(&drive).unwrap();
+
+        assert_eq!(
+            capabilities.profiles,
+            [Profile::CdRom, Profile::CdR, Profile::CdRw, Profile::DvdRom]
+        );
+        assert_eq!(capabilities.max_read_multiple(MediaFamily::Cd), Some(48));
+        // The mode page's speeds are for CD media
+        assert!(capabilities.can_read(MediaFamily::Dvd));
+        assert_eq!(capabilities.max_read_multiple(MediaFamily::Dvd), None);
+        assert_eq!(capabilities.max_write_multiple(MediaFamily::Cd), Some(24));
+        assert_eq!(capabilities.max_write_multiple(MediaFamily::Dvd), None);
+        assert_eq!(capabilities.max_read_multiple(MediaFamily::Bd), None);
+    }
+
+    #[test]
+    fn missing_mode_page_leaves_speeds_unknown() {
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x46, CONFIGURATION));
+
+        let capabilities = DriveCapabilities::probe(&drive).unwrap();
+
+        assert!(capabilities.can_write(MediaFamily::Cd));
+        assert!(!capabilities.can_write(MediaFamily::Dvd));
+        assert_eq!(capabilities.max_read_speed, None);
+        assert_eq!(capabilities.max_read_multiple(MediaFamily::Cd), None);
+    }
+}
@@ -1,17 +1,32 @@
-use nix::sys::stat::Mode;
-use std::error::Error;
-use std::os::fd::{AsRawFd, OwnedFd};
 use std::path::Path;
+#[cfg(feature = "async")]
+use std::sync::Arc;
 use std::{fs, io};
 
 use array_concat::*;
 use const_format::concatcp;
-use nix::fcntl::{open, OFlag};
+use deku::DekuError;
 use seq_macro::seq;
 
-use crate::scsi::mmc::commands::{Command, OpCodeDef, Response};
+use crate::scsi::mmc::commands::get_configuration::{get_configuration_full, RtField};
+use crate::scsi::mmc::commands::inquiry::{self, Inquiry, InquiryResponse, PeripheralDeviceType};
+use crate::scsi::mmc::commands::mode_select::ModeSelect;
+use crate::scsi::mmc::commands::mode_sense::{
+    capabilities::CapabilitiesPage, ModeSense, PageControl, RawModePage,
+};
+use crate::scsi::mmc::commands::start_stop_unit::{LoadEjectOperation, StartStopUnit};
+use crate::scsi::mmc::commands::test_unit_ready::TestUnitReady;
+use crate::scsi::mmc::commands::{Command, OpCodeDef, Response, DEFAULT_TIMEOUT};
+use crate::scsi::mmc::types::FeatureCode;
 // use crate::scsi::mmc::commands::{execute, inquiry::Inquiry};
-use crate::transport::sgio::{run_sgio, DxferDirection};
+use crate::transport::error::{MMCError, UnitAttentionCondition};
+use crate::transport::sgio::{DxferDirection, ScsiError, SgIo};
+use crate::transport::{Completion, Transport};
+
+pub mod benchmark;
+pub mod capabilities;
+pub mod mounts;
+pub mod probe;
 
 macro_rules! device_files {
     ($prefix:expr, $($range:tt)+) => {{
@@ -32,10 +47,16 @@ const SR_DEVICES: [&str; 28] = device_files!("/dev/sr", 0u8..=27u8);
 pub const DEVICES: [&str; concat_arrays_size!(NAMED_DEVICES, HD_DEVICES, SCD_DEVICES, SR_DEVICES)] =
     concat_arrays!(NAMED_DEVICES, HD_DEVICES, SCD_DEVICES, SR_DEVICES);
 
+/// The largest data buffer [`Drive::execute`] allocates for a command by default, 16 MiB. Larger
+/// reads should be split up, e.g. with a
+/// [`SectorReader`](crate::scsi::mmc::commands::read_cd::SectorReader).
+pub const DEFAULT_MAX_ALLOCATION_LEN: usize = 16 * 1024 * 1024;
+
 #[allow(dead_code)]
 #[derive(Debug)]
-pub struct Drive {
-    pub devnode: String,
+pub struct Drive<T: Transport = SgIo> {
+    transport: T,
+    max_allocation_len: usize,
     // pub removeable_medium: bool,
     // pub spc_version: spc::Version,
     // pub vendor: String,
@@ -45,28 +66,247 @@ pub struct Drive {
 
 impl Drive {
     pub fn new(devnode: String) -> Self {
-        Self { devnode }
+        Self::with_transport(SgIo::new(devnode))
+    }
+
+    /// Like [`Drive::new`], but first confirms with INQUIRY that `devnode` is a CD/DVD device, so
+    /// that MMC commands are never sent to e.g. a hard disk by mistake.
+    pub fn open_optical(devnode: String) -> Result<Self, crate::Error> {
+        let drive = Self::new(devnode);
+        drive.ensure_optical()?;
+        Ok(drive)
+    }
+
+    pub fn devnode(&self) -> &str {
+        self.transport.devnode()
+    }
+}
+
+impl<T: Transport> Drive<T> {
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            transport,
+            max_allocation_len: DEFAULT_MAX_ALLOCATION_LEN,
+        }
+    }
+
+    /// Sets the largest data buffer [`Drive::execute`] or [`Drive::execute_raw`] allocates for a
+    /// command, by default [`DEFAULT_MAX_ALLOCATION_LEN`]. Commands with a larger allocation length
+    /// fail with [`crate::Error::AllocationTooLarge`] without being submitted.
+    pub fn with_max_allocation_len(mut self, max_allocation_len: usize) -> Self {
+        self.max_allocation_len = max_allocation_len;
+        self
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
     }
 
-    fn get_fd(&self) -> io::Result<OwnedFd> {
-        Ok(open(
-            self.devnode.as_str(),
-            OFlag::O_RDONLY | OFlag::O_NONBLOCK,
-            Mode::empty(),
-        )?)
+    /// Fails with [`inquiry::Error::NotOptical`] unless INQUIRY reports a CD/DVD device.
+    pub fn ensure_optical(&self) -> Result<(), crate::Error> {
+        let inquiry = self.execute(Inquiry::<InquiryResponse>::new(0.into()))?;
+
+        match inquiry.peripheral_device_type {
+            PeripheralDeviceType::CDOrDVDDevice => Ok(()),
+            device_type => Err(inquiry::Error::NotOptical(device_type).into()),
+        }
+    }
+
+    /// Issues TEST UNIT READY, retrying once if it fails with the UNIT ATTENTION a Drive reports
+    /// on the first command after the media was changed, so that later commands don't fail with
+    /// it instead. Returns whether the media may have changed, e.g. to invalidate a cached TOC.
+    ///
+    /// Any other error, including the Drive not being ready (say with no media loaded), is
+    /// returned.
+    pub fn clear_unit_attention(&self) -> Result<bool, crate::Error> {
+        match self.execute(TestUnitReady::new(0.into())) {
+            Ok(()) => Ok(false),
+            Err(crate::Error::Scsi(ScsiError::MMCError(MMCError::UnitAttentionCondition(
+                UnitAttentionCondition::NotReadyToReadyChangeMediumMayHaveChanged,
+            )))) => self.execute(TestUnitReady::new(0.into())).map(|()| true),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Ejects the media, opening the tray. Fails with
+    /// [`ProtocolError::MediumRemovalPrevented`](crate::transport::error::ProtocolError::MediumRemovalPrevented)
+    /// while the Host has removal prevented, e.g. while the disc is mounted.
+    pub fn eject(&self) -> Result<(), crate::Error> {
+        self.start_stop_unit(false, LoadEjectOperation::EjectIfPermitted)
+    }
+
+    /// Loads the media, closing the tray, and waits for the disc to spin up.
+    pub fn load(&self) -> Result<(), crate::Error> {
+        self.start_stop_unit(false, LoadEjectOperation::LoadAndStartDisc)
+    }
+
+    /// Like [`Drive::load`], returning as soon as the Drive accepts the command rather than once
+    /// the disc is ready.
+    pub fn close_tray(&self) -> Result<(), crate::Error> {
+        self.start_stop_unit(true, LoadEjectOperation::LoadAndStartDisc)
+    }
+
+    fn start_stop_unit(
+        &self,
+        immediate: bool,
+        operation: LoadEjectOperation,
+    ) -> Result<(), crate::Error> {
+        self.execute(StartStopUnit::new(immediate, operation, 0.into()))
+    }
+
+    /// Whether the Drive reports any Profile which can write media, e.g. CD-R or BD-RE, whether
+    /// or not such media is currently loaded.
+    pub fn is_writer(&self) -> Result<bool, crate::Error> {
+        let configuration = get_configuration_full(
+            self,
+            RtField::Supported,
+            FeatureCode::ProfileList as u16,
+            0.into(),
+        )?;
+
+        Ok(configuration
+            .profiles()
+            .any(|profile| profile.is_writable()))
+    }
+
+    /// The current read speed in kB/s, from the CD/DVD Capabilities and Mechanical Status page.
+    pub fn read_speed_kbps(&self) -> Result<u16, crate::Error> {
+        let page = self.execute(ModeSense::<CapabilitiesPage>::new(
+            PageControl::Current,
+            0.into(),
+        ))?;
+
+        Ok(page.current_read_speed)
+    }
+
+    /// Sets the read speed in kB/s by writing the CD/DVD Capabilities and Mechanical Status page
+    /// back with MODE SELECT, for Drives which ignore SET CD SPEED. The page is read first so
+    /// only the Current Read Speed is changed.
+    pub fn set_read_speed_kbps(&self, speed: u16) -> Result<(), crate::Error> {
+        const OFFSET: usize = CapabilitiesPage::CURRENT_READ_SPEED_OFFSET;
+
+        let mut page = self.execute(ModeSense::<RawModePage<0x2A>>::new(
+            PageControl::Current,
+            0.into(),
+        ))?;
+
+        let current_read_speed =
+            page.parameters
+                .get_mut(OFFSET..(OFFSET + 2))
+                .ok_or(DekuError::Parse(
+                    "Mode page 2Ah is too short to have a Current Read Speed".into(),
+                ))?;
+        current_read_speed.copy_from_slice(&speed.to_be_bytes());
+
+        self.execute(ModeSelect::new(&page, false, 0.into()))
     }
 
     pub fn execute<O: OpCodeDef, C: Command<O>>(
         &self,
         command: C,
-    ) -> Result<C::Response, Box<dyn Error>> {
-        let bytes = run_sgio(
-            self.get_fd()?.as_raw_fd(),
+    ) -> Result<C::Response, crate::Error> {
+        self.execute_detailed(command).map(|(response, _)| response)
+    }
+
+    /// Like [`Drive::execute`], also returning the [`Completion`], e.g. to log how long the
+    /// command took or to detect the Drive returning less data than was allocated.
+    pub fn execute_detailed<O: OpCodeDef, C: Command<O>>(
+        &self,
+        command: C,
+    ) -> Result<(C::Response, Completion), crate::Error> {
+        #[cfg(feature = "log")]
+        log::debug!("issuing {}", command.describe());
+
+        if let Some(parameter_list) = command.parameter_list() {
+            let completion = self.transport.submit_detailed(
+                command.as_cdb().as_mut(),
+                DxferDirection::ToDev,
+                &mut parameter_list.to_vec(),
+                command.timeout(),
+            )?;
+            return Ok((
+                C::Response::from_bytes(&[]).map_err(Into::into)?,
+                completion,
+            ));
+        }
+
+        let allocation_len = command.allocation_len();
+        if allocation_len > self.max_allocation_len {
+            return Err(crate::Error::AllocationTooLarge {
+                requested: allocation_len,
+                max: self.max_allocation_len,
+            });
+        }
+
+        // Commands that neither send nor return data transfer nothing
+        let dir = if allocation_len == 0 {
+            DxferDirection::None
+        } else {
+            DxferDirection::FromDev
+        };
+
+        let mut data = vec![0u8; allocation_len];
+        let completion = self.transport.submit_detailed(
             command.as_cdb().as_mut(),
-            4096,
-            DxferDirection::FromDev,
+            dir,
+            &mut data,
+            command.timeout(),
         )?;
-        Ok(C::Response::from_bytes(&bytes)?)
+        data.truncate(completion.transferred);
+        Ok((
+            C::Response::from_bytes(&data).map_err(Into::into)?,
+            completion,
+        ))
+    }
+
+    /// Submits an arbitrary CDB, for commands without a [`Command`] implementation. Returns the
+    /// `buf_len` byte data buffer truncated to what the Drive transferred.
+    ///
+    /// Errors are reported the same way as for [`Drive::execute`], with sense data parsed into an
+    /// [`MMCError`](crate::transport::error::MMCError) where possible. A `buf_len` over the
+    /// maximum allocation length fails with [`crate::Error::AllocationTooLarge`] without being
+    /// submitted, see [`Drive::with_max_allocation_len`].
+    pub fn execute_raw(
+        &self,
+        cdb: &[u8],
+        dir: DxferDirection,
+        buf_len: usize,
+    ) -> Result<Vec<u8>, crate::Error> {
+        if buf_len > self.max_allocation_len {
+            return Err(crate::Error::AllocationTooLarge {
+                requested: buf_len,
+                max: self.max_allocation_len,
+            });
+        }
+
+        let mut data = vec![0u8; buf_len];
+        let len = self
+            .transport
+            .submit(cdb, dir, &mut data, DEFAULT_TIMEOUT)?;
+        data.truncate(len);
+        Ok(data)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: Transport + Send + Sync + 'static> Drive<T> {
+    /// Like [`Drive::execute`], but runs the command on Tokio's blocking thread pool so that
+    /// slow commands (e.g. multi-second reads) don't block the async runtime.
+    ///
+    /// The Drive is shared through an [`Arc`] since the command may outlive the caller's borrow.
+    /// A panic while executing the command is resumed in the awaiting task.
+    pub async fn execute_async<O, C>(
+        self: Arc<Self>,
+        command: C,
+    ) -> Result<C::Response, crate::Error>
+    where
+        O: OpCodeDef,
+        C: Command<O> + Send + 'static,
+        C::Response: Send + 'static,
+    {
+        tokio::task::spawn_blocking(move || self.execute(command))
+            .await
+            .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))
     }
 }
 
@@ -119,3 +359,278 @@ pub fn scan_sysfs() -> io::Result<Vec<String>> {
 
 //     devices
 // }
+
+#[cfg(test)]
+mod tests {
+    use crate::scsi::mmc::commands::read_cd::{
+        MainChannelFlags, ReadCd, CDDA_USER_DATA_SIZE, MAX_TRANSFER_LENGTH,
+    };
+    use crate::transport::{error::ProtocolError, mock::MockTransport};
+
+    use super::*;
+
+    #[test]
+    fn read_only_and_writer_profile_lists() {
+        #[rustfmt::skip]
+        let read_only: &[u8] = &[
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x08,
+            0x00, 0x00, 0x03, 0x08,
+            0x00, 0x08, 0x01, 0x00,
+            0x00, 0x10, 0x00, 0x00,
+        ];
+        #[rustfmt::skip]
+        let writer: &[u8] = &[
+            0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x08,
+            0x00, 0x00, 0x03, 0x0C,
+            0x00, 0x08, 0x01, 0x00,
+            0x00, 0x09, 0x00, 0x00,
+            0x00, 0x0A, 0x00, 0x00,
+        ];
+
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x46, read_only));
+        assert!(!drive.is_writer().unwrap());
+
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x46, writer));
+        assert!(drive.is_writer().unwrap());
+    }
+
+    #[test]
+    fn raw_inquiry_matches_typed() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x05, 0x80, 0x05, 0x32, 0x1F, 0x00, 0x00, 0x00,
+            b'A', b'S', b'U', b'S', b' ', b' ', b' ', b' ',
+            b'B', b'W', b'-', b'1', b'6', b'D', b'1', b'H',
+            b'T', b' ', b' ', b' ', b' ', b' ', b' ', b' ',
+            b'3', b'.', b'1', b'0',
+        ];
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x12, response));
+
+        let command = Inquiry::<InquiryResponse>::new(0.into());
+        let raw = drive
+            .execute_raw(&command.as_cdb(), DxferDirection::FromDev, 96)
+            .unwrap();
+        let typed = drive.execute(command).unwrap();
+
+        assert_eq!(raw, response);
+        let submitted = drive.transport().submitted_cdbs();
+        assert_eq!(submitted[0], submitted[1]);
+
+        let parsed = InquiryResponse::from_bytes(&raw).unwrap();
+        assert_eq!(
+            parsed.t10_vendor_identification,
+            typed.t10_vendor_identification
+        );
+        assert_eq!(parsed.product_identification, typed.product_identification);
+        assert_eq!(parsed.product_revision_level, typed.product_revision_level);
+    }
+
+    #[test]
+    fn raw_command_error() {
+        let drive = Drive::with_transport(MockTransport::new());
+
+        assert!(matches!(
+            drive.execute_raw(&[0xFF, 0, 0, 0, 0, 0], DxferDirection::None, 0),
+            Err(crate::Error::Scsi(ScsiError::MMCError(_)))
+        ));
+    }
+
+    #[test]
+    fn raw_command_allocation_cap() {
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x12, [0u8; 96]))
+            .with_max_allocation_len(64);
+
+        assert!(matches!(
+            drive.execute_raw(&[0x12, 0, 0, 0, 96, 0], DxferDirection::FromDev, 96),
+            Err(crate::Error::AllocationTooLarge {
+                requested: 96,
+                max: 64,
+            })
+        ));
+        assert!(drive.transport().submitted_cdbs().is_empty());
+        assert!(drive
+            .execute_raw(&[0x12, 0, 0, 0, 64, 0], DxferDirection::FromDev, 64)
+            .is_ok());
+    }
+
+    #[test]
+    fn set_read_speed_through_mode_page() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xAA, 0x14,
+            0x3B, 0x17, 0x71, 0x73, 0x29, 0x23,
+            0x21, 0x14, 0x01, 0x00, 0x08, 0x00,
+            // Current Read Speed, 24x (CD)
+            0x10, 0x8A,
+            0x00, 0x00, 0x10, 0x8A, 0x10, 0x8A,
+        ];
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x5A, response)
+                .with_response(0x55, []),
+        );
+
+        assert_eq!(drive.read_speed_kbps().unwrap(), 4234);
+        drive.set_read_speed_kbps(1412).unwrap();
+
+        let mut expected = [&[0u8; 8][..], &response[8..]].concat();
+        // The Parameters Savable bit is cleared
+        expected[8] = 0x2A;
+        expected[22..24].copy_from_slice(&1412u16.to_be_bytes());
+        assert_eq!(drive.transport().sent_data(), [expected]);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn execute_async_inquiry() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x05, 0x80, 0x05, 0x32, 0x1F, 0x00, 0x00, 0x00,
+            b'A', b'S', b'U', b'S', b' ', b' ', b' ', b' ',
+            b'B', b'W', b'-', b'1', b'6', b'D', b'1', b'H',
+            b'T', b' ', b' ', b' ', b' ', b' ', b' ', b' ',
+            b'3', b'.', b'1', b'0',
+        ];
+        let drive = Arc::new(Drive::with_transport(
+            MockTransport::new().with_response(0x12, response),
+        ));
+
+        let inquiry = drive
+            .clone()
+            .execute_async(Inquiry::<InquiryResponse>::new(0.into()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            inquiry.product_identification,
+            InquiryResponse::from_bytes(response)
+                .unwrap()
+                .product_identification
+        );
+        assert_eq!(drive.transport().submitted_cdbs().len(), 1);
+    }
+
+    #[test]
+    fn clear_media_changed_unit_attention() {
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_queued_sense(0x00, 0x06, 0x28, 0x00)
+                .with_response(0x00, []),
+        );
+
+        assert!(drive.clear_unit_attention().unwrap());
+        assert!(!drive.clear_unit_attention().unwrap());
+        assert_eq!(drive.transport().submitted_cdbs().len(), 3);
+
+        // MEDIUM NOT PRESENT isn't retried
+        let drive =
+            Drive::with_transport(MockTransport::new().with_queued_sense(0x00, 0x02, 0x3A, 0x00));
+        assert!(drive.clear_unit_attention().is_err());
+        assert_eq!(drive.transport().submitted_cdbs().len(), 1);
+    }
+
+    #[test]
+    fn eject_load_and_close_tray() {
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x1B, []));
+
+        drive.eject().unwrap();
+        drive.load().unwrap();
+        drive.close_tray().unwrap();
+
+        assert_eq!(
+            drive.transport().submitted_cdbs(),
+            [
+                [0x1B, 0x00, 0x00, 0x00, 0x02, 0x00],
+                [0x1B, 0x00, 0x00, 0x00, 0x03, 0x00],
+                [0x1B, 0x01, 0x00, 0x00, 0x03, 0x00],
+            ]
+        );
+
+        let drive =
+            Drive::with_transport(MockTransport::new().with_queued_sense(0x1B, 0x05, 0x53, 0x02));
+        assert!(matches!(
+            drive.eject(),
+            Err(crate::Error::Scsi(ScsiError::MMCError(
+                MMCError::ProtocolError(ProtocolError::MediumRemovalPrevented)
+            )))
+        ));
+    }
+
+    #[test]
+    fn over_cap_allocation_is_rejected() {
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; 27 * CDDA_USER_DATA_SIZE]),
+        );
+
+        let read_cd = |transfer_length| ReadCd {
+            transfer_length,
+            main_channel: MainChannelFlags::USER_DATA,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            drive.execute(read_cd(MAX_TRANSFER_LENGTH)),
+            Err(crate::Error::AllocationTooLarge {
+                requested,
+                max: DEFAULT_MAX_ALLOCATION_LEN,
+            }) if requested == MAX_TRANSFER_LENGTH as usize * CDDA_USER_DATA_SIZE
+        ));
+        assert!(drive.transport().submitted_cdbs().is_empty());
+
+        let drive = drive.with_max_allocation_len(CDDA_USER_DATA_SIZE);
+        assert!(drive.execute(read_cd(1)).is_ok());
+        assert!(matches!(
+            drive.execute(read_cd(2)),
+            Err(crate::Error::AllocationTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn completion_surfaces_duration_and_residual() {
+        // Only one of the two sectors asked for is returned
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0xBE, vec![0u8; CDDA_USER_DATA_SIZE])
+                .with_durations([std::time::Duration::from_millis(75)]),
+        );
+
+        let (data, completion) = drive
+            .execute_detailed(ReadCd {
+                transfer_length: 2,
+                main_channel: MainChannelFlags::USER_DATA,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(data.len(), CDDA_USER_DATA_SIZE);
+        assert_eq!(
+            completion,
+            Completion {
+                transferred: CDDA_USER_DATA_SIZE,
+                residual: CDDA_USER_DATA_SIZE,
+                duration: std::time::Duration::from_millis(75),
+            }
+        );
+    }
+
+    #[test]
+    fn hard_disk_is_not_optical() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x00, 0x05, 0x02, 0x1F, 0x00, 0x00, 0x00,
+            b'A', b'T', b'A', b' ', b' ', b' ', b' ', b' ',
+            b'S', b'a', b'm', b's', b'u', b'n', b'g', b' ',
+            b'S', b'S', b'D', b' ', b'8', b'7', b'0', b' ',
+            b'1', b'B', b'6', b'Q',
+        ];
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x12, response));
+
+        assert!(matches!(
+            drive.ensure_optical(),
+            Err(crate::Error::Inquiry(inquiry::Error::NotOptical(
+                PeripheralDeviceType::DirectAccessBlockDevice
+            )))
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use thiserror::Error;
+
+use crate::device::Drive;
+
+const PROC_MOUNTS: &str = "/proc/mounts";
+
+#[derive(Debug, Error)]
+pub enum InUseError {
+    #[error("{devnode} is mounted at {mount_point}")]
+    Mounted {
+        devnode: String,
+        mount_point: String,
+    },
+    #[error("Failed to read the mount table")]
+    MountTable(#[source] io::Error),
+}
+
+impl Drive {
+    /// Fails with [`InUseError::Mounted`] if the OS has a filesystem on the Drive's media mounted,
+    /// unless `force` is set, so a disc in use isn't wiped out from under it.
+    ///
+    /// [`Drive::execute`] can't tell which commands are destructive, so callers must call this
+    /// themselves before e.g. BLANK, FORMAT UNIT, or writes.
+    pub fn ensure_not_mounted(&self, force: bool) -> Result<(), InUseError> {
+        self.ensure_not_mounted_in(Path::new(PROC_MOUNTS), force)
+    }
+
+    fn ensure_not_mounted_in(&self, mounts: &Path, force: bool) -> Result<(), InUseError> {
+        if force {
+            return Ok(());
+        }
+
+        match mount_point(mounts, self.devnode()) {
+            Ok(None) => Ok(()),
+            Ok(Some(mount_point)) => Err(InUseError::Mounted {
+                devnode: self.devnode().to_owned(),
+                mount_point,
+            }),
+            Err(error) => Err(InUseError::MountTable(error)),
+        }
+    }
+}
+
+/// Looks `devnode` up in a mount table in the format of `/proc/mounts`, returning where it's
+/// mounted. Symlinks like `/dev/cdrom` are resolved, since the table lists the real device node.
+fn mount_point(mounts: &Path, devnode: &str) -> io::Result<Option<String>> {
+    // Sources like `proc` or `tmpfs` aren't paths, so mustn't be resolved against the current
+    // directory
+    let canonical = |path: &str| {
+        let path = PathBuf::from(path);
+        if path.is_absolute() {
+            fs::canonicalize(&path).unwrap_or(path)
+        } else {
+            path
+        }
+    };
+    let devnode = canonical(devnode);
+
+    Ok(fs::read_to_string(mounts)?.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let source = unescape(fields.next()?);
+        let mount_point = unescape(fields.next()?);
+
+        (canonical(&source) == devnode).then_some(mount_point)
+    }))
+}
+
+/// Decodes the `\NNN` octal escapes the mount table uses for whitespace and backslashes in a
+/// field, e.g. `\040` for a space.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get((i + 1)..(i + 4))
+            .filter(|digits| {
+                bytes[i] == b'\\' && digits.iter().all(|digit| matches!(digit, b'0'..=b'7'))
+            })
+            .and_then(|digits| {
+                let digits = std::str::from_utf8(digits).ok()?;
+                u8::from_str_radix(digits, 8).ok()
+            });
+
+        match escaped {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 4;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// Writes `contents` as a mount table, unique to `name` so tests can run in parallel.
+    fn fake_mounts(name: &str, contents: &str) -> PathBuf {
+        let mounts = env::temp_dir().join(format!("cdio-{name}-{}", std::process::id()));
+        fs::write(&mounts, contents).unwrap();
+        mounts
+    }
+
+    #[test]
+    fn find_mounted_device() {
+        let mounts = fake_mounts(
+            "mounts",
+            "proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n\
+             /dev/sda1 / ext4 rw,relatime 0 0\n\
+             /dev/sr0 /run/media/user/AUDIO_CD iso9660 ro,nosuid,nodev,relatime 0 0\n",
+        );
+
+        let sr0 = mount_point(&mounts, "/dev/sr0");
+        let sr1 = mount_point(&mounts, "/dev/sr1");
+        fs::remove_file(&mounts).unwrap();
+
+        assert_eq!(sr0.unwrap().as_deref(), Some("/run/media/user/AUDIO_CD"));
+        assert_eq!(sr1.unwrap(), None);
+    }
+
+    #[test]
+    fn escaped_paths() {
+        let mounts = fake_mounts(
+            "mounts-escaped",
+            "/dev/disk/by-label/MY\\040DISC /run/media/user/MY\\040DISC\\011(1) udf ro 0 0\n",
+        );
+
+        let found = mount_point(&mounts, "/dev/disk/by-label/MY DISC");
+        fs::remove_file(&mounts).unwrap();
+
+        assert_eq!(
+            found.unwrap().as_deref(),
+            Some("/run/media/user/MY DISC\t(1)")
+        );
+        assert_eq!(unescape("C:\\134dir\\04"), "C:\\dir\\04");
+    }
+
+    #[test]
+    fn mounted_unless_forced() {
+        let mounts = fake_mounts(
+            "mounts-guard",
+            "/dev/cdio-test-sr0 /run/media/user/AUDIO_CD iso9660 ro 0 0\n",
+        );
+        let mounted = Drive::new("/dev/cdio-test-sr0".to_owned());
+        let unmounted = Drive::new("/dev/cdio-test-sr1".to_owned());
+
+        let refused = mounted.ensure_not_mounted_in(&mounts, false);
+        let forced = mounted.ensure_not_mounted_in(&mounts, true);
+        let other = unmounted.ensure_not_mounted_in(&mounts, false);
+        fs::remove_file(&mounts).unwrap();
+
+        assert!(matches!(
+            &refused,
+            Err(InUseError::Mounted { devnode, mount_point })
+                if devnode == "/dev/cdio-test-sr0" && mount_point == "/run/media/user/AUDIO_CD"
+        ));
+        assert!(forced.is_ok());
+        assert!(other.is_ok());
+
+        let missing = mounted.ensure_not_mounted_in(Path::new("/nonexistent/mounts"), false);
+        assert!(matches!(missing, Err(InUseError::MountTable(_))));
+        assert!(matches!(
+            crate::Error::from(refused.unwrap_err()),
+            crate::Error::InUse(InUseError::Mounted { .. })
+        ));
+    }
+}
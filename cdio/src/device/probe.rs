@@ -0,0 +1,123 @@
+use std::io;
+
+use crate::device::{capabilities::DriveCapabilities, scan_sysfs, Drive};
+use crate::scsi::mmc::commands::get_configuration::{get_configuration_full, RtField};
+use crate::scsi::mmc::commands::inquiry::{Inquiry, InquiryResponse};
+use crate::scsi::mmc::commands::mode_sense::{
+    capabilities::CapabilitiesPage, ModeSense, PageControl,
+};
+use crate::scsi::mmc::features::profile_list::Profile;
+use crate::scsi::mmc::types::FeatureCode;
+use crate::transport::Transport;
+
+/// A Drive's identity, what it can do, and what's loaded in it, see [`probe_all`].
+#[derive(Debug)]
+pub struct DriveReport {
+    pub inquiry: InquiryResponse,
+    /// `None` if no media is loaded.
+    pub current_profile: Option<Profile>,
+    pub capabilities: DriveCapabilities,
+}
+
+impl DriveReport {
+    /// Issues INQUIRY, GET CONFIGURATION for the Profile List, and MODE SENSE for the CD/DVD
+    /// Capabilities and Mechanical Status page. None of them need media, so an empty Drive is
+    /// reported rather than failing.
+    pub fn probe<T: Transport>(drive: &Drive<T>) -> Result<Self, crate::Error> {
+        let inquiry = drive.execute(Inquiry::<InquiryResponse>::new(0.into()))?;
+
+        let configuration = get_configuration_full(
+            drive,
+            RtField::Supported,
+            FeatureCode::ProfileList as u16,
+            0.into(),
+        )?;
+
+        let capabilities_page = drive
+            .execute(ModeSense::<CapabilitiesPage>::new(
+                PageControl::Current,
+                0.into(),
+            ))
+            .ok();
+
+        Ok(Self {
+            inquiry,
+            // The Drive reports Profile 0000h while it has no current Profile
+            current_profile: (configuration.current_profile != Profile::Reserved(0))
+                .then_some(configuration.current_profile),
+            capabilities: DriveCapabilities::from_responses(&configuration, capabilities_page),
+        })
+    }
+
+    pub fn media_present(&self) -> bool {
+        self.current_profile.is_some()
+    }
+}
+
+/// Probes every optical Drive found by [`scan_sysfs`], e.g. to list the Drives and what's in them
+/// on startup. Each Drive is probed independently, so one that fails (say it's busy, or was
+/// unplugged since the scan) is reported alongside the rest rather than failing them all.
+pub fn probe_all() -> io::Result<Vec<(String, Result<DriveReport, crate::Error>)>> {
+    Ok(scan_sysfs()?
+        .into_iter()
+        .map(|devnode| {
+            let report = DriveReport::probe(&Drive::new(devnode.clone()));
+            (devnode, report)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scsi::mmc::features::profile_list::MediaFamily;
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[rustfmt::skip]
+    const INQUIRY: &[u8] = &[
+        0x05, 0x80, 0x05, 0x32, 0x1F, 0x00, 0x00, 0x00,
+        b'A', b'S', b'U', b'S', b' ', b' ', b' ', b' ',
+        b'B', b'W', b'-', b'1', b'6', b'D', b'1', b'H',
+        b'T', b' ', b' ', b' ', b' ', b' ', b' ', b' ',
+        b'3', b'.', b'1', b'0',
+    ];
+
+    fn configuration(current_profile: u8) -> Vec<u8> {
+        #[rustfmt::skip]
+        let configuration = vec![
+            0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, current_profile,
+            // Profile List
+            0x00, 0x00, 0x03, 0x0C,
+            0x00, 0x08, 0x00, 0x00,
+            0x00, 0x09, 0x00, 0x00,
+            0x00, 0x0A, 0x00, 0x00,
+        ];
+        configuration
+    }
+
+    #[test]
+    fn probe_loaded_and_empty_drives() {
+        let loaded = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x12, INQUIRY)
+                .with_response(0x46, configuration(0x08)),
+        );
+        let empty = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x12, INQUIRY)
+                .with_response(0x46, configuration(0x00)),
+        );
+
+        let loaded = DriveReport::probe(&loaded).unwrap();
+        assert!(loaded.media_present());
+        assert_eq!(loaded.current_profile, Some(Profile::CdRom));
+
+        let empty = DriveReport::probe(&empty).unwrap();
+        assert!(!empty.media_present());
+        assert_eq!(empty.current_profile, None);
+        assert!(empty.capabilities.can_write(MediaFamily::Cd));
+        // No capabilities page, the speed is just unknown
+        assert_eq!(empty.capabilities.max_read_speed, None);
+    }
+}
@@ -0,0 +1,508 @@
+use std::io::{self, Write};
+
+use thiserror::Error;
+
+use crate::core::{addressing::Lba, digest::StreamDigest, msf::Msf};
+use crate::device::Drive;
+use crate::rainbow_books::q_subcode;
+use crate::scsi::mmc::commands::read::Read10;
+use crate::scsi::mmc::commands::read_capacity::ReadCapacity;
+use crate::scsi::mmc::commands::read_cd::{SectorReader, SectorType, CDDA_USER_DATA_SIZE};
+use crate::scsi::mmc::commands::read_toc_pma_atip::formatted_toc::FormattedToc;
+use crate::scsi::mmc::commands::read_toc_pma_atip::multi_session_info::MultiSessionInformation;
+use crate::scsi::mmc::commands::read_toc_pma_atip::ReadTocPmaAtip;
+use crate::transport::Transport;
+
+/// 64 KiB, the largest transfer virtually every Drive and host adapter accepts.
+const ISO_BLOCKS_PER_READ: u16 = 32;
+
+/// The offset of the Mode byte in the header of a raw data sector, following the 12 byte sync
+/// pattern and the 3 byte address.
+const MODE_BYTE_OFFSET: usize = 15;
+
+/// From the end of the audio session of an Enhanced CD to the start of the data session's first
+/// track: the audio session's Lead-out (90 seconds), the data session's Lead-in (60 seconds), and
+/// the first track's pregap (2 seconds). None of it can be read.
+const SESSION_GAP: i32 = (90 + 60 + 2) * 75;
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("The TOC has no tracks or no Lead-out to read up to")]
+    IncompleteToc,
+    #[error("Track {0} is not in the TOC")]
+    TrackNotFound(u8),
+    #[error("Failed to read the capacity of the media")]
+    Capacity(#[source] crate::Error),
+    #[error("Failed to read the session information")]
+    Sessions(#[source] crate::Error),
+    #[error("The media has no recorded blocks")]
+    BlankMedia,
+    #[error("Failed to read sectors starting at LBA {lba}")]
+    Read {
+        lba: Lba,
+        #[source]
+        source: crate::Error,
+    },
+    #[error(transparent)]
+    Write(#[from] io::Error),
+}
+
+/// How a track is described in the CUE sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrackMode {
+    Audio,
+    Mode1,
+    Mode2,
+}
+
+impl TrackMode {
+    fn as_cue(&self) -> &'static str {
+        match self {
+            Self::Audio => "AUDIO",
+            Self::Mode1 => "MODE1/2352",
+            Self::Mode2 => "MODE2/2352",
+        }
+    }
+}
+
+/// Reads every sector from the start of the first track up to the Lead-out into `bin` as raw
+/// 2352 byte sectors, then writes a CUE sheet describing it, referring to the image as
+/// `bin_name`, into `cue`.
+///
+/// Every sector is read the same way regardless of its track, so mixed mode discs (a data track
+/// followed by audio tracks) are imaged as they are on the disc. Data tracks are described as
+/// Mode 1 or Mode 2 from the header of their first sector.
+pub fn write_bin_cue<T: Transport>(
+    drive: &Drive<T>,
+    toc: &FormattedToc<Lba>,
+    bin_name: &str,
+    bin: &mut impl Write,
+    cue: &mut impl Write,
+) -> Result<(), ImageError> {
+    let first_track = toc.tracks().next().ok_or(ImageError::IncompleteToc)?;
+    let leadout = toc.leadout().ok_or(ImageError::IncompleteToc)?;
+
+    let start = first_track.track_start_address;
+    let sectors = u32::try_from(
+        i32::from(leadout.track_start_address) - i32::from(first_track.track_start_address),
+    )
+    .map_err(|_| ImageError::IncompleteToc)?;
+
+    let mut modes: Vec<TrackMode> = toc
+        .tracks()
+        .map(|track| {
+            if track.control.contains(q_subcode::Control::IS_DATA) {
+                // Corrected once the track's first sector is read
+                TrackMode::Mode1
+            } else {
+                TrackMode::Audio
+            }
+        })
+        .collect();
+
+    let mut reader = SectorReader::raw(drive, start, sectors);
+    loop {
+        let chunk_start = reader.position();
+        let Some(chunk) = reader.next() else {
+            break;
+        };
+        let (data, _) = chunk.map_err(|source| ImageError::Read {
+            lba: chunk_start,
+            source,
+        })?;
+
+        for (track, mode) in toc.tracks().zip(modes.iter_mut()) {
+            if *mode == TrackMode::Audio {
+                continue;
+            }
+
+            let sector = i32::from(track.track_start_address) - i32::from(chunk_start);
+            let mode_byte = usize::try_from(sector)
+                .ok()
+                .and_then(|sector| data.get(sector * CDDA_USER_DATA_SIZE + MODE_BYTE_OFFSET));
+            if let Some(0x02) = mode_byte {
+                *mode = TrackMode::Mode2;
+            }
+        }
+
+        bin.write_all(&data)?;
+    }
+
+    writeln!(cue, "FILE \"{bin_name}\" BINARY")?;
+    for (track, mode) in toc.tracks().zip(modes) {
+        writeln!(cue, "  TRACK {:02} {}", track.track_number, mode.as_cue())?;
+
+        if mode == TrackMode::Audio {
+            let flags: Vec<&str> = [
+                (q_subcode::Control::COPY_PERMITTED, "DCP"),
+                (q_subcode::Control::FOUR_CHANNELS, "4CH"),
+                (q_subcode::Control::PREEMPHASIS_OR_INCREMENTAL, "PRE"),
+            ]
+            .into_iter()
+            .filter_map(|(flag, name)| track.control.contains(flag).then_some(name))
+            .collect();
+            if !flags.is_empty() {
+                writeln!(cue, "    FLAGS {}", flags.join(" "))?;
+            }
+        }
+
+        // Relative to the start of the image rather than the disc
+        let offset = i32::from(track.track_start_address) - i32::from(start);
+        let index = u32::try_from(offset)
+            .ok()
+            .and_then(Msf::from_frames)
+            .ok_or(ImageError::IncompleteToc)?;
+        writeln!(cue, "    INDEX 01 {index}")?;
+    }
+
+    Ok(())
+}
+
+/// Reads a single track into `sink`, from its start in the TOC up to the start of the next track
+/// (or the Lead-out), returning the number of bytes written.
+///
+/// An audio track followed by a data track instead ends where the data track's pregap starts, or
+/// at its own session's Lead-out if the data track is in the next session, as on an Enhanced CD.
+/// The session information is read from the Drive to tell these apart.
+///
+/// Audio tracks are read as CD-DA, so `sink` receives 16-bit stereo PCM. Data tracks are read as
+/// raw 2352 byte sectors, like [`write_bin_cue`] does.
+pub fn extract_track<T: Transport>(
+    drive: &Drive<T>,
+    toc: &FormattedToc<Lba>,
+    track_number: u8,
+    sink: &mut impl Write,
+) -> Result<u64, ImageError> {
+    let mut tracks = toc
+        .tracks()
+        .skip_while(|track| track.track_number != track_number);
+    let track = tracks
+        .next()
+        .ok_or(ImageError::TrackNotFound(track_number))?;
+    let next = tracks.next();
+
+    let is_data = |control: q_subcode::Control| control.contains(q_subcode::Control::IS_DATA);
+    let end = match next {
+        Some(next) if !is_data(track.control) && is_data(next.control) => {
+            let session_info = drive
+                .execute(ReadTocPmaAtip::<MultiSessionInformation<Lba>>::new(
+                    12,
+                    0.into(),
+                ))
+                .map_err(ImageError::Sessions)?;
+            let data_session = toc.split_data_session(&session_info).data_session;
+
+            if data_session
+                .first()
+                .is_some_and(|first| first.track_number == next.track_number)
+            {
+                next.track_start_address - SESSION_GAP
+            } else {
+                toc.gaps()
+                    .find(|gap| gap.track_number == next.track_number)
+                    .map_or(next.track_start_address, |gap| gap.start)
+            }
+        }
+        Some(next) => next.track_start_address,
+        None => {
+            toc.leadout()
+                .ok_or(ImageError::IncompleteToc)?
+                .track_start_address
+        }
+    };
+
+    let start = track.track_start_address;
+    let sectors =
+        u32::try_from(i32::from(end) - i32::from(start)).map_err(|_| ImageError::IncompleteToc)?;
+
+    let mut reader = if is_data(track.control) {
+        SectorReader::raw(drive, start, sectors)
+    } else {
+        SectorReader::new(drive, start, sectors).sector_type(SectorType::CdDa)
+    };
+
+    let mut written = 0;
+    loop {
+        let chunk_start = reader.position();
+        let Some(chunk) = reader.next() else {
+            break;
+        };
+        let (data, _) = chunk.map_err(|source| ImageError::Read {
+            lba: chunk_start,
+            source,
+        })?;
+
+        sink.write_all(&data)?;
+        written += data.len() as u64;
+    }
+
+    Ok(written)
+}
+
+/// Reads every 2048 byte block of a data disc, from LBA 0 to the last LBA reported by READ
+/// CAPACITY, into `sink`, returning the number of bytes written. The result is a mountable ISO
+/// image for discs with a single data track, e.g. most DVD-ROMs.
+///
+/// The block length reported by READ CAPACITY is ignored, as some Drives report 2352 for CD
+/// media even though READ (10) transfers 2048 bytes per block.
+pub fn extract_iso<T: Transport>(
+    drive: &Drive<T>,
+    sink: &mut impl Write,
+) -> Result<u64, ImageError> {
+    let capacity = drive
+        .execute(ReadCapacity::new(0.into()))
+        .map_err(ImageError::Capacity)?;
+    let blocks = u32::try_from(i32::from(capacity.lba))
+        .ok()
+        .and_then(|last_lba| last_lba.checked_add(1))
+        .ok_or(ImageError::BlankMedia)?;
+
+    let mut written = 0;
+    let mut lba = Lba::from(0);
+    let mut remaining = blocks;
+    while remaining > 0 {
+        // The final read covers whatever is left of the last window
+        let transfer_length = u16::try_from(remaining)
+            .unwrap_or(u16::MAX)
+            .min(ISO_BLOCKS_PER_READ);
+
+        let data = drive
+            .execute(Read10::new(lba, transfer_length, 0.into()))
+            .map_err(|source| ImageError::Read { lba, source })?;
+        sink.write_all(&data)?;
+
+        written += data.len() as u64;
+        lba += i32::from(transfer_length);
+        remaining -= u32::from(transfer_length);
+    }
+
+    Ok(written)
+}
+
+/// Writes everything `reader` reads into `sink` while hashing it with `D`, e.g.
+/// `crc32fast::Hasher` or `(crc32fast::Hasher, md5::Md5)` for both, returning the digest.
+///
+/// Only one chunk is held in memory at a time, so multi-gigabyte ranges can be imaged and
+/// verified without buffering them like [`read_audio_range`] does.
+///
+/// [`read_audio_range`]: crate::scsi::mmc::commands::read_cd::read_audio_range
+pub fn copy_hashed<T: Transport, D: StreamDigest>(
+    mut reader: SectorReader<'_, T>,
+    sink: &mut impl Write,
+) -> Result<D::Output, ImageError> {
+    let mut digest = D::default();
+
+    loop {
+        let chunk_start = reader.position();
+        let Some(chunk) = reader.next() else {
+            break;
+        };
+        let (data, _) = chunk.map_err(|source| ImageError::Read {
+            lba: chunk_start,
+            source,
+        })?;
+
+        digest.update(&data);
+        sink.write_all(&data)?;
+    }
+
+    Ok(digest.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        scsi::mmc::commands::{
+            read_capacity::MMC_BLOCK_LENGTH, read_cd::read_audio_range, Response,
+        },
+        transport::mock::MockTransport,
+    };
+
+    use super::*;
+
+    #[test]
+    fn mixed_mode_bin_cue() {
+        #[rustfmt::skip]
+        let toc: &[u8] = &[
+            0x00, 0x1A, 0x01, 0x02,
+            0x00, 0x14, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x12, 0x02, 0x00, 0x00, 0x00, 0x00, 0x1E,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x00, 0x32,
+        ];
+        let toc = FormattedToc::<Lba>::from_bytes(toc).unwrap();
+
+        // Every sector looks like a Mode 2 data sector
+        let mut sector = [0u8; CDDA_USER_DATA_SIZE];
+        sector[1..11].fill(0xFF);
+        sector[MODE_BYTE_OFFSET] = 0x02;
+        let drive =
+            Drive::with_transport(MockTransport::new().with_response(0xBE, sector.repeat(27)));
+
+        let mut bin = Vec::new();
+        let mut cue = Vec::new();
+        write_bin_cue(&drive, &toc, "disc.bin", &mut bin, &mut cue).unwrap();
+
+        assert_eq!(bin.len(), 50 * CDDA_USER_DATA_SIZE);
+        assert_eq!(
+            String::from_utf8(cue).unwrap(),
+            "FILE \"disc.bin\" BINARY\n\
+             \x20 TRACK 01 MODE2/2352\n\
+             \x20   INDEX 01 00:00:00\n\
+             \x20 TRACK 02 AUDIO\n\
+             \x20   FLAGS DCP\n\
+             \x20   INDEX 01 00:00:30\n"
+        );
+    }
+
+    #[test]
+    fn streaming_digest_matches_buffered() {
+        let chunk: Vec<u8> = (0..27 * CDDA_USER_DATA_SIZE)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let drive = Drive::with_transport(MockTransport::new().with_response(0xBE, chunk));
+
+        let buffered = read_audio_range(&drive, Lba::from(0), 60).unwrap();
+        let mut expected = <(crc32fast::Hasher, md5::Md5)>::default();
+        expected.update(&buffered);
+
+        let mut sink = Vec::new();
+        let digest = copy_hashed::<_, (crc32fast::Hasher, md5::Md5)>(
+            SectorReader::new(&drive, Lba::from(0), 60),
+            &mut sink,
+        )
+        .unwrap();
+
+        assert_eq!(sink, buffered);
+        assert_eq!(digest, expected.finalize());
+    }
+
+    #[test]
+    fn extract_iso_up_to_capacity() {
+        let blocks: Vec<u8> = (0..ISO_BLOCKS_PER_READ)
+            .flat_map(|block| [block as u8; MMC_BLOCK_LENGTH as usize])
+            .collect();
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                // Last LBA 69, 70 blocks
+                .with_response(0x25, [0x00, 0x00, 0x00, 0x45, 0x00, 0x00, 0x08, 0x00])
+                .with_response(0x28, blocks),
+        );
+
+        let mut iso = Vec::new();
+        let written = extract_iso(&drive, &mut iso).unwrap();
+
+        assert_eq!(written, 70 * u64::from(MMC_BLOCK_LENGTH));
+        assert_eq!(iso.len() as u64, written);
+
+        let reads: Vec<(u32, u16)> = drive.transport().submitted_cdbs()[1..]
+            .iter()
+            .map(|cdb| {
+                (
+                    u32::from_be_bytes([cdb[2], cdb[3], cdb[4], cdb[5]]),
+                    u16::from_be_bytes([cdb[7], cdb[8]]),
+                )
+            })
+            .collect();
+        assert_eq!(reads, [(0, 32), (32, 32), (64, 6)]);
+    }
+
+    #[test]
+    fn extract_second_of_three_tracks() {
+        #[rustfmt::skip]
+        let toc: &[u8] = &[
+            0x00, 0x22, 0x01, 0x03,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x00, 0x00, 0x28,
+            0x00, 0x10, 0x03, 0x00, 0x00, 0x00, 0x00, 0x64,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x00, 0x96,
+        ];
+        let toc = FormattedToc::<Lba>::from_bytes(toc).unwrap();
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; 27 * CDDA_USER_DATA_SIZE]),
+        );
+
+        let mut pcm = Vec::new();
+        let written = extract_track(&drive, &toc, 2, &mut pcm).unwrap();
+
+        // LBA 40 up to track 3 at LBA 100
+        assert_eq!(written, 60 * CDDA_USER_DATA_SIZE as u64);
+        assert_eq!(pcm.len() as u64, written);
+
+        let cdbs = drive.transport().submitted_cdbs();
+        assert_eq!(cdbs[0][1] >> 2, SectorType::CdDa as u8);
+        assert_eq!(&cdbs[0][2..6], [0, 0, 0, 40]);
+
+        assert!(matches!(
+            extract_track(&drive, &toc, 4, &mut pcm),
+            Err(ImageError::TrackNotFound(4))
+        ));
+    }
+
+    #[test]
+    fn extract_audio_track_before_data_track() {
+        #[rustfmt::skip]
+        let toc: &[u8] = &[
+            0x00, 0x22, 0x01, 0x03,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x00, 0x00, 0x28,
+            // The data track at LBA 11,500
+            0x00, 0x14, 0x03, 0x00, 0x00, 0x00, 0x2C, 0xEC,
+            0x00, 0x14, 0xAA, 0x00, 0x00, 0x00, 0x2E, 0xE0,
+        ];
+        let toc = FormattedToc::<Lba>::from_bytes(toc).unwrap();
+        let drive = |last_session: u8, first_track_last_session: u8| {
+            Drive::with_transport(
+                MockTransport::new()
+                    .with_response(0xBE, vec![0u8; 27 * CDDA_USER_DATA_SIZE])
+                    .with_response(
+                        0x43,
+                        [
+                            0x00,
+                            0x0A,
+                            0x01,
+                            last_session,
+                            0x00,
+                            0x14,
+                            first_track_last_session,
+                            0x00,
+                            0x00,
+                            0x00,
+                            0x2C,
+                            0xEC,
+                        ],
+                    ),
+            )
+        };
+
+        // An Enhanced CD, with the data track alone in session 2
+        let enhanced_cd = drive(2, 3);
+        let written = extract_track(&enhanced_cd, &toc, 2, &mut io::sink()).unwrap();
+
+        // LBA 40 up to the audio session's Lead-out at LBA 100
+        assert_eq!(written, 60 * CDDA_USER_DATA_SIZE as u64);
+        let cdbs = enhanced_cd.transport().submitted_cdbs();
+        assert_eq!(cdbs[0][..3], [0x43, 0x00, 0x01]);
+        let read_end = cdbs[1..]
+            .iter()
+            .map(|cdb| {
+                u32::from_be_bytes([cdb[2], cdb[3], cdb[4], cdb[5]])
+                    + u32::from_be_bytes([0, cdb[6], cdb[7], cdb[8]])
+            })
+            .max();
+        assert_eq!(read_end, Some(100));
+
+        // A single session, only the data track's pregap is skipped
+        let mixed_mode = drive(1, 1);
+        let written = extract_track(&mixed_mode, &toc, 2, &mut io::sink()).unwrap();
+        assert_eq!(written, (11_350 - 40) * CDDA_USER_DATA_SIZE as u64);
+
+        // Audio followed by audio needs no session information
+        let audio = drive(2, 3);
+        extract_track(&audio, &toc, 1, &mut io::sink()).unwrap();
+        assert!(audio
+            .transport()
+            .submitted_cdbs()
+            .iter()
+            .all(|cdb| cdb[0] == 0xBE));
+    }
+}
@@ -0,0 +1,243 @@
+use std::ops::Range;
+
+use thiserror::Error;
+
+use crate::core::{addressing::Lba, msf::Msf};
+use crate::device::Drive;
+use crate::scsi::mmc::commands::get_configuration::{
+    GetConfiguration, GetConfigurationResponse, RtField,
+};
+use crate::scsi::mmc::commands::read_disc_information::{
+    DiscInformation, DiscStatus, ReadDiscInformation,
+};
+use crate::scsi::mmc::commands::read_toc_pma_atip::{formatted_toc::FormattedToc, ReadTocPmaAtip};
+use crate::scsi::mmc::features::profile_list::Profile;
+use crate::scsi::mmc::types::FeatureCode;
+use crate::transport::Transport;
+
+pub mod image;
+
+const ALLOCATION_LENGTH: u16 = 4096;
+
+#[derive(Debug, Error)]
+pub enum ProbeError {
+    #[error("Failed to read the current profile (GET CONFIGURATION)")]
+    Configuration(#[source] crate::Error),
+    #[error("Failed to read the TOC (READ TOC/PMA/ATIP) with current profile {profile:?}")]
+    Toc {
+        profile: Profile,
+        #[source]
+        source: crate::Error,
+    },
+    #[error("Failed to read the disc information (READ DISC INFORMATION)")]
+    DiscInformation {
+        profile: Profile,
+        toc: FormattedToc<Msf>,
+        #[source]
+        source: crate::Error,
+    },
+}
+
+/// A summary of the disc currently loaded in a drive, if any.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Disc {
+    /// The drive's current profile, which identifies the type of the loaded media.
+    /// [`Profile::Reserved(0)`](Profile::Reserved) while the drive is empty.
+    pub profile: Profile,
+    /// `None` if the drive is empty.
+    pub media: Option<Media>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Media {
+    pub toc: FormattedToc<Msf>,
+    pub session_count: u16,
+    /// Whether the disc is closed to further recording. Always true for pressed media.
+    pub is_finalized: bool,
+}
+
+impl Disc {
+    /// Issues GET CONFIGURATION, READ TOC/PMA/ATIP, and READ DISC INFORMATION in turn to assemble
+    /// a [`Disc`]. Whatever was successfully read before a failure is carried in the error.
+    ///
+    /// An empty drive isn't an error: the drive has no current profile, or reports MEDIUM NOT
+    /// PRESENT, and a [`Disc`] without [`media`](Disc::media) is returned.
+    pub fn probe<T: Transport>(drive: &Drive<T>) -> Result<Self, ProbeError> {
+        let empty = |profile| {
+            Ok(Self {
+                profile,
+                media: None,
+            })
+        };
+
+        // Only the Feature Header is needed for the current profile, so request the smallest
+        // descriptor the Drive is required to have.
+        let configuration = match drive.execute(GetConfiguration::new(
+            RtField::Supported,
+            FeatureCode::Core as u16,
+            ALLOCATION_LENGTH,
+            0.into(),
+        )) {
+            Ok(configuration) => configuration,
+            Err(source) if source.is_medium_not_present() => return empty(Profile::Reserved(0)),
+            Err(source) => return Err(ProbeError::Configuration(source)),
+        };
+
+        if configuration.current_profile == Profile::Reserved(0) {
+            return empty(configuration.current_profile);
+        }
+
+        let toc = match drive.execute(ReadTocPmaAtip::<FormattedToc<Msf>>::all_tracks(
+            ALLOCATION_LENGTH,
+            0.into(),
+        )) {
+            Ok(toc) => toc,
+            Err(source) if source.is_medium_not_present() => {
+                return empty(configuration.current_profile)
+            }
+            Err(source) => {
+                return Err(ProbeError::Toc {
+                    profile: configuration.current_profile,
+                    source,
+                })
+            }
+        };
+
+        let disc_information =
+            match drive.execute(ReadDiscInformation::new(ALLOCATION_LENGTH, 0.into())) {
+                Ok(disc_information) => disc_information,
+                Err(source) => {
+                    return Err(ProbeError::DiscInformation {
+                        profile: configuration.current_profile,
+                        toc,
+                        source,
+                    });
+                }
+            };
+
+        Ok(Self::from_responses(configuration, toc, disc_information))
+    }
+
+    pub fn from_responses(
+        configuration: GetConfigurationResponse,
+        toc: FormattedToc<Msf>,
+        disc_information: DiscInformation,
+    ) -> Self {
+        Self {
+            profile: configuration.current_profile,
+            media: Some(Media {
+                toc,
+                session_count: disc_information.number_of_sessions,
+                is_finalized: disc_information.disc_status == DiscStatus::Complete,
+            }),
+        }
+    }
+
+    /// See [`FormattedToc::hidden_track_one_audio`]. Always `None` if the drive is empty.
+    pub fn hidden_track_one_audio(&self) -> Option<Range<Lba>> {
+        self.media.as_ref()?.toc.hidden_track_one_audio()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[test]
+    fn probe_from_canned_responses() {
+        #[rustfmt::skip]
+        let configuration: &[u8] = &[
+            0x00, 0x00, 0x00, 0x1C, 0x00, 0x00, 0x00, 0x08,
+            // Profile List
+            0x00, 0x00, 0x03, 0x08,
+            0x00, 0x08, 0x01, 0x00,
+            0x00, 0x09, 0x00, 0x00,
+            // Core
+            0x00, 0x01, 0x0B, 0x08,
+            0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00,
+        ];
+        #[rustfmt::skip]
+        let toc: &[u8] = &[
+            0x00, 0x1A, 0x01, 0x02,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x03, 0x20, 0x10,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x07, 0x01, 0x2C,
+        ];
+        #[rustfmt::skip]
+        let disc_information: &[u8] = &[
+            0x00, 0x20, 0x0E, 0x01, 0x01, 0x01, 0x02, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x46, configuration)
+                .with_response(0x43, toc)
+                .with_response(0x51, disc_information),
+        );
+
+        let disc = Disc::probe(&drive).unwrap();
+
+        assert_eq!(disc.profile, Profile::CdRom);
+        let media = disc.media.unwrap();
+        assert_eq!(media.toc.first_track_number, 1);
+        assert_eq!(media.toc.last_track_number, 2);
+        assert_eq!(media.toc.toc_track_descriptors.len(), 3);
+        assert_eq!(media.session_count, 1);
+        assert!(media.is_finalized);
+
+        assert_eq!(
+            drive.transport().submitted_cdbs(),
+            [
+                // Just the Core Feature
+                vec![0x46, 0x02, 0x00, 0x01, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00],
+                // Formatted TOC in MSF, from the first track
+                vec![0x43, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00],
+                vec![0x51, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00],
+            ]
+        );
+    }
+
+    #[test]
+    fn probe_empty_drive() {
+        // Some Drives keep reporting the last profile, but fail READ TOC
+        #[rustfmt::skip]
+        let configuration: &[u8] = &[
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x08,
+            // Core
+            0x00, 0x01, 0x0B, 0x08,
+            0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00,
+        ];
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x46, configuration)
+                .with_queued_sense(0x43, 0x02, 0x3A, 0x00),
+        );
+
+        let disc = Disc::probe(&drive).unwrap();
+        assert_eq!(disc.profile, Profile::CdRom);
+        assert_eq!(disc.media, None);
+        assert_eq!(disc.hidden_track_one_audio(), None);
+
+        // Any other failure is still an error
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x46, configuration)
+                .with_queued_sense(0x43, 0x03, 0x11, 0x00),
+        );
+        assert!(matches!(Disc::probe(&drive), Err(ProbeError::Toc { .. })));
+
+        // No current profile, so READ TOC isn't even issued
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x46, [0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00]),
+        );
+        assert_eq!(Disc::probe(&drive).unwrap().media, None);
+        assert_eq!(drive.transport().submitted_cdbs().len(), 1);
+    }
+}
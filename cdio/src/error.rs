@@ -0,0 +1,85 @@
+use std::convert::Infallible;
+
+use deku::DekuError;
+use thiserror::Error;
+
+use crate::{
+    device::mounts::InUseError,
+    scsi::mmc::commands::inquiry,
+    transport::{
+        error::{MMCError, ReadinessError},
+        sgio::ScsiError,
+    },
+};
+
+/// Any error from executing a command, whether from the Drive or from parsing its response.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Scsi(#[from] ScsiError),
+    #[error(transparent)]
+    Inquiry(#[from] inquiry::Error),
+    /// The command's allocation length is over the Drive's limit, see
+    /// [`Drive::with_max_allocation_len`](crate::device::Drive::with_max_allocation_len).
+    #[error("Allocation length of {requested} bytes exceeds the maximum of {max}")]
+    AllocationTooLarge { requested: usize, max: usize },
+    /// The Drive transferred less data than a read needs, e.g. fewer sectors than requested.
+    #[error("Expected at least {expected} bytes, but only {received} were read")]
+    ShortRead { expected: usize, received: usize },
+    /// A response that couldn't be parsed, e.g. a truncated TOC.
+    #[error(transparent)]
+    Parse(#[from] DekuError),
+    /// The Drive's media is mounted, see
+    /// [`Drive::ensure_not_mounted`](crate::device::Drive::ensure_not_mounted).
+    #[error(transparent)]
+    InUse(#[from] InUseError),
+}
+
+impl Error {
+    /// Whether the Drive reported MEDIUM NOT PRESENT, with or without the tray's state.
+    pub fn is_medium_not_present(&self) -> bool {
+        matches!(
+            self,
+            Self::Scsi(ScsiError::MMCError(MMCError::ReadinessError(
+                ReadinessError::MediumNotPresent
+                    | ReadinessError::MediumNotPresentTrayClosed
+                    | ReadinessError::MediumNotPresentTrayOpen
+            )))
+        )
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::addressing::Lba,
+        device::Drive,
+        scsi::mmc::commands::read_toc_pma_atip::{formatted_toc::FormattedToc, ReadTocPmaAtip},
+        transport::mock::MockTransport,
+    };
+
+    use super::*;
+
+    #[test]
+    fn toc_parse_error_converts() {
+        // The TOC Data Length claims a descriptor that isn't there
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0x43, [0x00, 0x0A, 0x01, 0x01, 0x00]),
+        );
+
+        let error = drive
+            .execute(ReadTocPmaAtip::<FormattedToc<Lba>>::all_tracks(
+                4096,
+                0.into(),
+            ))
+            .unwrap_err();
+
+        assert!(matches!(error, Error::Parse(_)));
+    }
+}
@@ -1,5 +1,9 @@
 pub mod core;
 pub mod device;
+pub mod disc;
+pub mod error;
 pub mod rainbow_books;
 pub mod scsi;
 pub mod transport;
+
+pub use error::Error;
@@ -0,0 +1,142 @@
+// The identifiers carried in Mode 2 and Mode 3 of the Q sub-channel. Drives with flaky sub-channel
+// reads sometimes return garbage with the valid bit set, so both are validated before use.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+/// A Media Catalog Number, the 13 digit UPC/EAN of a release.
+///
+/// See IEC 60908 §17.5.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mcn([u8; 13]);
+
+#[derive(Debug, Error)]
+#[error("Invalid Media Catalog Number: {0:?}. Expected 13 digits")]
+pub struct McnParseError(String);
+
+impl Mcn {
+    pub fn as_str(&self) -> &str {
+        // Only ever constructed from ASCII
+        std::str::from_utf8(&self.0).unwrap()
+    }
+}
+
+impl FromStr for Mcn {
+    type Err = McnParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.as_bytes()
+            .try_into()
+            .ok()
+            .filter(|bytes: &[u8; 13]| bytes.iter().all(u8::is_ascii_digit))
+            .map(Self)
+            .ok_or_else(|| McnParseError(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Mcn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An International Standard Recording Code, e.g. `USRC17607839`: a 2 letter country code, a 3
+/// character registrant code, the last 2 digits of the year, and a 5 digit designation code.
+///
+/// See IEC 60908 §17.5.3 and ISO 3901.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Isrc([u8; 12]);
+
+#[derive(Debug, Error)]
+#[error("Invalid ISRC: {0:?}. Expected CCXXXYYNNNNN")]
+pub struct IsrcParseError(String);
+
+impl Isrc {
+    pub fn as_str(&self) -> &str {
+        // Only ever constructed from ASCII
+        std::str::from_utf8(&self.0).unwrap()
+    }
+
+    pub fn country_code(&self) -> &str {
+        &self.as_str()[..2]
+    }
+
+    pub fn registrant_code(&self) -> &str {
+        &self.as_str()[2..5]
+    }
+
+    pub fn year(&self) -> &str {
+        &self.as_str()[5..7]
+    }
+
+    pub fn designation_code(&self) -> &str {
+        &self.as_str()[7..]
+    }
+}
+
+impl FromStr for Isrc {
+    type Err = IsrcParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = |bytes: &[u8; 12]| {
+            bytes[..2].iter().all(u8::is_ascii_uppercase)
+                && bytes[2..5]
+                    .iter()
+                    .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+                && bytes[5..].iter().all(u8::is_ascii_digit)
+        };
+
+        s.as_bytes()
+            .try_into()
+            .ok()
+            .filter(valid)
+            .map(Self)
+            .ok_or_else(|| IsrcParseError(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Isrc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_isrc() {
+        let isrc: Isrc = "USRC17607839".parse().unwrap();
+
+        assert_eq!(isrc.to_string(), "USRC17607839");
+        assert_eq!(isrc.country_code(), "US");
+        assert_eq!(isrc.registrant_code(), "RC1");
+        assert_eq!(isrc.year(), "76");
+        assert_eq!(isrc.designation_code(), "07839");
+
+        for invalid in [
+            "",
+            "USRC1760783",
+            "USRC176078390",
+            "usrc17607839",
+            "U5RC17607839",
+            "USRC1760783X",
+            "US-RC1-76-07839",
+            "\0\0\0\0\0\0\0\0\0\0\0\0",
+        ] {
+            assert!(invalid.parse::<Isrc>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn parse_mcn() {
+        let mcn: Mcn = "0724384960650".parse().unwrap();
+        assert_eq!(mcn.to_string(), "0724384960650");
+
+        assert!("072438496065".parse::<Mcn>().is_err());
+        assert!("07243849606X0".parse::<Mcn>().is_err());
+    }
+}
@@ -1,3 +1,5 @@
 pub mod atip;
 pub mod cd_text;
+pub mod identifiers;
 pub mod q_subcode;
+pub mod xa;
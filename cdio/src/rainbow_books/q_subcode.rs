@@ -77,6 +77,79 @@ impl<'a> DekuReader<'a> for Control {
     }
 }
 
+/// The kind of track described by a [`Control`] field, with the bits that mean different things
+/// for audio and data tracks interpreted accordingly.
+///
+/// The Control Field means the same thing in every Q mode, so the ADR doesn't need to be taken
+/// into account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrackType {
+    Audio2Ch {
+        pre_emphasis: bool,
+        copy_permitted: bool,
+    },
+    /// Defined by the Red Book but never used in practice.
+    Audio4Ch {
+        pre_emphasis: bool,
+        copy_permitted: bool,
+    },
+    DataUninterrupted {
+        copy_permitted: bool,
+    },
+    DataIncremental {
+        copy_permitted: bool,
+    },
+}
+
+impl TrackType {
+    pub fn is_audio(&self) -> bool {
+        matches!(self, Self::Audio2Ch { .. } | Self::Audio4Ch { .. })
+    }
+
+    pub fn copy_permitted(&self) -> bool {
+        match *self {
+            Self::Audio2Ch { copy_permitted, .. }
+            | Self::Audio4Ch { copy_permitted, .. }
+            | Self::DataUninterrupted { copy_permitted }
+            | Self::DataIncremental { copy_permitted } => copy_permitted,
+        }
+    }
+
+    /// Always `false` for data tracks.
+    pub fn pre_emphasis(&self) -> bool {
+        match *self {
+            Self::Audio2Ch { pre_emphasis, .. } | Self::Audio4Ch { pre_emphasis, .. } => {
+                pre_emphasis
+            }
+            Self::DataUninterrupted { .. } | Self::DataIncremental { .. } => false,
+        }
+    }
+}
+
+impl From<Control> for TrackType {
+    fn from(control: Control) -> Self {
+        let copy_permitted = control.contains(Control::COPY_PERMITTED);
+        let low_bit = control.contains(Control::PREEMPHASIS_OR_INCREMENTAL);
+
+        // 11xx is reserved, the data bit takes precedence
+        match (
+            control.contains(Control::IS_DATA),
+            control.contains(Control::FOUR_CHANNELS),
+        ) {
+            (true, _) if low_bit => Self::DataIncremental { copy_permitted },
+            (true, _) => Self::DataUninterrupted { copy_permitted },
+            (false, false) => Self::Audio2Ch {
+                pre_emphasis: low_bit,
+                copy_permitted,
+            },
+            (false, true) => Self::Audio4Ch {
+                pre_emphasis: low_bit,
+                copy_permitted,
+            },
+        }
+    }
+}
+
 use bcd::{bcd, Bcd};
 use deku::{ctx::BitSize, reader::Reader, DekuError, DekuReader};
 use thiserror::Error;
@@ -172,5 +245,86 @@ pub struct MediaCatalogNumber {
     pub aframe: Bcd<1>,
 }
 
+/// The CRC recorded after the Control/ADR and DATA-Q bytes of a Q sub-channel frame, computed over
+/// `data`. The polynomial is x^16 + x^12 + x^5 + 1, with the remainder inverted.
+///
+/// See IEC 60908 §17.5.
+pub fn crc(data: &[u8]) -> u16 {
+    let remainder = data.iter().fold(0u16, |crc, &byte| {
+        (0..8).fold(crc ^ (u16::from(byte) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    });
+
+    !remainder
+}
+
 // NOTE: Once you have all the qpayloads modeled out, don't just go an put them all in a big enum.
 // Make enums as needed like for RawToc. This lets you pick and choose which QPayloads are valid.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn q_crc() {
+        // The standard check value of CRC-16/XMODEM, which is the same CRC without the inversion
+        assert_eq!(!crc(b"123456789"), 0x31C3);
+        assert_eq!(
+            crc(&[0x01, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x31, 0x74]),
+            0xC579
+        );
+    }
+
+    #[test]
+    fn track_type_from_control() {
+        for (control, track_type) in [
+            (
+                0b0000,
+                TrackType::Audio2Ch {
+                    pre_emphasis: false,
+                    copy_permitted: false,
+                },
+            ),
+            (
+                0b0011,
+                TrackType::Audio2Ch {
+                    pre_emphasis: true,
+                    copy_permitted: true,
+                },
+            ),
+            (
+                0b1001,
+                TrackType::Audio4Ch {
+                    pre_emphasis: true,
+                    copy_permitted: false,
+                },
+            ),
+            (
+                0b0100,
+                TrackType::DataUninterrupted {
+                    copy_permitted: false,
+                },
+            ),
+            (
+                0b0111,
+                TrackType::DataIncremental {
+                    copy_permitted: true,
+                },
+            ),
+        ] {
+            assert_eq!(
+                TrackType::from(Control::from_bits_retain(control)),
+                track_type
+            );
+        }
+
+        assert!(
+            !TrackType::from(Control::IS_DATA | Control::PREEMPHASIS_OR_INCREMENTAL).pre_emphasis()
+        );
+    }
+}
@@ -0,0 +1,135 @@
+// CD-ROM XA (Green Book / White Book) Mode 2 sectors, which may mix Form 1 and Form 2 within a
+// single track, e.g. the video and audio streams of a Video CD. Every sector carries an 8 byte
+// subheader (two copies of the same 4 bytes) after its header, and the Submode byte says which
+// Form the sector is, and so how much user data it has.
+
+use bitflags::bitflags;
+
+use crate::core::util::DescriptorCursor;
+use crate::scsi::mmc::commands::read_cd::{
+    CDDA_USER_DATA_SIZE, MODE2_FORM1_USER_DATA_SIZE, MODE2_FORM2_USER_DATA_SIZE,
+};
+
+/// Sync pattern, then the 3 byte address.
+const MODE_BYTE_OFFSET: usize = 15;
+const SUBHEADER_OFFSET: usize = 16;
+const USER_DATA_OFFSET: usize = 24;
+
+bitflags! {
+    /// The Submode byte of the subheader.
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Submode: u8 {
+        const END_OF_FILE = 1 << 7;
+        const REAL_TIME = 1 << 6;
+        /// If set the sector is Form 2, otherwise Form 1.
+        const FORM_2 = 1 << 5;
+        const TRIGGER = 1 << 4;
+        const DATA = 1 << 3;
+        const AUDIO = 1 << 2;
+        const VIDEO = 1 << 1;
+        const END_OF_RECORD = 1 << 0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Form {
+    /// 2048 bytes of user data, protected by EDC and ECC.
+    Form1,
+    /// 2324 bytes of user data, with only an (optional) EDC.
+    Form2,
+}
+
+/// A raw 2352 byte Mode 2 sector, split according to its Form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XaSector<'a> {
+    pub file_number: u8,
+    pub channel_number: u8,
+    pub submode: Submode,
+    pub coding_information: u8,
+    pub user_data: &'a [u8],
+}
+
+impl<'a> XaSector<'a> {
+    /// Returns `None` unless `raw` is a whole sector whose header has Mode 2.
+    pub fn parse(raw: &'a [u8]) -> Option<Self> {
+        if raw.len() != CDDA_USER_DATA_SIZE || raw[MODE_BYTE_OFFSET] != 0x02 {
+            return None;
+        }
+
+        let subheader = &raw[SUBHEADER_OFFSET..SUBHEADER_OFFSET + 4];
+        let submode = Submode::from_bits_retain(subheader[2]);
+
+        let user_data_size = match Self::form_of(submode) {
+            Form::Form1 => MODE2_FORM1_USER_DATA_SIZE,
+            Form::Form2 => MODE2_FORM2_USER_DATA_SIZE,
+        };
+
+        Some(Self {
+            file_number: subheader[0],
+            channel_number: subheader[1],
+            submode,
+            coding_information: subheader[3],
+            user_data: &raw[USER_DATA_OFFSET..USER_DATA_OFFSET + user_data_size],
+        })
+    }
+
+    pub fn form(&self) -> Form {
+        Self::form_of(self.submode)
+    }
+
+    fn form_of(submode: Submode) -> Form {
+        if submode.contains(Submode::FORM_2) {
+            Form::Form2
+        } else {
+            Form::Form1
+        }
+    }
+}
+
+/// Splits consecutive raw sectors, e.g. from [`SectorReader::raw`], into XA sectors. `None` is
+/// yielded for any sector that isn't Mode 2, and a trailing partial sector is ignored.
+///
+/// [`SectorReader::raw`]: crate::scsi::mmc::commands::read_cd::SectorReader::raw
+pub fn xa_sectors(raw: &[u8]) -> impl Iterator<Item = Option<XaSector<'_>>> {
+    DescriptorCursor::fixed(raw, CDDA_USER_DATA_SIZE).map(XaSector::parse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sector(submode: u8, fill: u8) -> Vec<u8> {
+        let mut sector = vec![fill; CDDA_USER_DATA_SIZE];
+        sector[..12].copy_from_slice(&[
+            0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0,
+        ]);
+        sector[12..16].copy_from_slice(&[0x00, 0x02, 0x00, 0x02]);
+        sector[16..24].copy_from_slice(&[1, 0, submode, 0, 1, 0, submode, 0]);
+        sector
+    }
+
+    #[test]
+    fn mixed_form_sectors() {
+        // A data sector, then a real-time video sector
+        let raw = [sector(0x08, 0xAA), sector(0x62, 0xBB)].concat();
+
+        let sectors: Vec<XaSector> = xa_sectors(&raw).map(Option::unwrap).collect();
+
+        assert_eq!(sectors[0].form(), Form::Form1);
+        assert_eq!(sectors[0].file_number, 1);
+        assert_eq!(sectors[0].user_data.len(), MODE2_FORM1_USER_DATA_SIZE);
+
+        assert_eq!(sectors[1].form(), Form::Form2);
+        assert!(sectors[1]
+            .submode
+            .contains(Submode::VIDEO | Submode::REAL_TIME));
+        assert_eq!(sectors[1].user_data.len(), MODE2_FORM2_USER_DATA_SIZE);
+        assert!(sectors[1].user_data.iter().all(|&byte| byte == 0xBB));
+
+        // Mode 1
+        let mut mode_1 = sector(0x08, 0xAA);
+        mode_1[MODE_BYTE_OFFSET] = 0x01;
+        assert_eq!(XaSector::parse(&mode_1), None);
+    }
+}
@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use crate::core::util::BitWriter;
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// The Format Types commonly used with CD and DVD media.
+///
+/// See MMC-6 §6.5.4.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum FormatType {
+    FullFormat = 0x00,
+    SpareAreaExpansion = 0x01,
+    ZoneReformat = 0x04,
+    ZoneFormat = 0x05,
+    /// CD-RW/DVD-RW Full Format
+    RwFullFormat = 0x10,
+    /// CD-RW/DVD-RW Grow Session
+    RwGrowSession = 0x11,
+    /// CD-RW/DVD-RW Add Session
+    RwAddSession = 0x12,
+    DvdRwQuickGrowLastSession = 0x13,
+    DvdRwQuickAddSession = 0x14,
+    DvdRwQuick = 0x15,
+    DvdPlusRwBasicFormat = 0x26,
+}
+
+/// The Format Descriptor of the FORMAT UNIT parameter list, whose fields are interpreted
+/// according to the [`FormatType`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormatDescriptor {
+    pub number_of_blocks: u32,
+    pub format_type: FormatType,
+    pub format_sub_type: u8,
+    /// Only the lower 24 bits are sent to the Drive.
+    pub type_dependent_parameter: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormatUnit {
+    parameter_list: [u8; 12],
+    immed: bool,
+    fmt_data: bool,
+    cmp_list: bool,
+    control: Control,
+}
+
+impl FormatUnit {
+    /// Formatting can take well over an hour when the Drive isn't returning immediately.
+    pub const TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+
+    /// Formats the media per `format_descriptor`, which is sent as the parameter list (FMTDATA).
+    /// Defects found while formatting are added to the existing defect list, rather than replacing
+    /// it (CMPLST clear). When `immed` is set the Drive returns once the parameter list is
+    /// validated, and progress can be followed with REQUEST SENSE.
+    pub fn new(format_descriptor: FormatDescriptor, immed: bool, control: Control) -> Self {
+        let number_of_blocks = format_descriptor.number_of_blocks.to_be_bytes();
+        let type_dependent_parameter = format_descriptor.type_dependent_parameter.to_be_bytes();
+
+        let parameter_list = [
+            // Format List Header
+            0,
+            BitWriter::new()
+                .set_bit(0b1000_0000, immed) // FOV, required for IMMED to be valid
+                .set_bit(0b0000_0010, immed)
+                .into(),
+            0x00,
+            0x08, // Format Descriptor Length
+            // Format Descriptor
+            number_of_blocks[0],
+            number_of_blocks[1],
+            number_of_blocks[2],
+            number_of_blocks[3],
+            BitWriter::new()
+                .set_field(2, 0b11_1111, format_descriptor.format_type as u8)
+                .set_field(0, 0b11, format_descriptor.format_sub_type)
+                .into(),
+            type_dependent_parameter[1],
+            type_dependent_parameter[2],
+            type_dependent_parameter[3],
+        ];
+
+        Self {
+            parameter_list,
+            immed,
+            fmt_data: true,
+            cmp_list: false,
+            control,
+        }
+    }
+
+    /// Sets FMTDATA. Without it no parameter list is sent, and the Drive formats the media its
+    /// own default way, ignoring the format descriptor and `immed`.
+    pub fn with_fmt_data(mut self, fmt_data: bool) -> Self {
+        self.fmt_data = fmt_data;
+        self
+    }
+
+    /// Sets CMPLST, so the defects found while formatting replace the existing defect list.
+    pub fn with_cmp_list(mut self, cmp_list: bool) -> Self {
+        self.cmp_list = cmp_list;
+        self
+    }
+}
+
+type FormatUnitOpCode = OpCode<0x04>;
+
+impl Command<FormatUnitOpCode> for FormatUnit {
+    type Response = ();
+
+    fn as_cdb(&self) -> <FormatUnitOpCode as OpCodeDef>::Cdb {
+        [
+            FormatUnitOpCode::OP_CODE,
+            BitWriter::new()
+                .set_bit(0b0001_0000, self.fmt_data)
+                .set_bit(0b0000_1000, self.cmp_list)
+                .set_field(0, 0b111, 0b001) // Format Code
+                .into(),
+            0,
+            0,
+            0,
+            self.control.into(),
+        ]
+    }
+
+    /// Nothing is returned, and without FMTDATA nothing is sent either.
+    fn allocation_len(&self) -> usize {
+        0
+    }
+
+    fn parameter_list(&self) -> Option<&[u8]> {
+        self.fmt_data.then_some(&self.parameter_list[..])
+    }
+
+    fn timeout(&self) -> Duration {
+        if self.fmt_data && self.immed {
+            super::DEFAULT_TIMEOUT
+        } else {
+            Self::TIMEOUT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{device::Drive, transport::mock::MockTransport};
+
+    use super::*;
+
+    #[test]
+    fn quick_format_parameter_list() {
+        let format_unit = FormatUnit::new(
+            FormatDescriptor {
+                number_of_blocks: 0,
+                format_type: FormatType::DvdRwQuick,
+                format_sub_type: 0,
+                type_dependent_parameter: 0x0000_0010,
+            },
+            true,
+            0.into(),
+        );
+
+        assert_eq!(format_unit.as_cdb(), [0x04, 0x11, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(
+            format_unit.parameter_list().unwrap(),
+            [0x00, 0x82, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x54, 0x00, 0x00, 0x10]
+        );
+        assert_eq!(format_unit.timeout(), super::super::DEFAULT_TIMEOUT);
+    }
+
+    #[test]
+    fn fmt_data_and_cmp_list() {
+        let format_unit = FormatUnit::new(
+            FormatDescriptor {
+                number_of_blocks: 0,
+                format_type: FormatType::FullFormat,
+                format_sub_type: 0,
+                type_dependent_parameter: 0,
+            },
+            true,
+            0.into(),
+        );
+
+        let replacing_defects = format_unit.clone().with_cmp_list(true);
+        assert_eq!(replacing_defects.as_cdb()[1], 0x19);
+        assert!(replacing_defects.parameter_list().is_some());
+
+        let drive_default = format_unit.with_fmt_data(false);
+        assert_eq!(drive_default.as_cdb()[1], 0x01);
+        assert_eq!(drive_default.parameter_list(), None);
+        assert_eq!(drive_default.timeout(), FormatUnit::TIMEOUT);
+    }
+
+    #[test]
+    fn drive_default_format_transfers_no_data() {
+        // Anything the Drive returned would land in a data buffer, if one were allocated
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x04, [0xAA; 8]));
+        let format_unit = FormatUnit::new(
+            FormatDescriptor {
+                number_of_blocks: 0,
+                format_type: FormatType::FullFormat,
+                format_sub_type: 0,
+                type_dependent_parameter: 0,
+            },
+            false,
+            0.into(),
+        )
+        .with_fmt_data(false);
+
+        let (_, completion) = drive.execute_detailed(format_unit).unwrap();
+
+        assert_eq!(
+            drive.transport().submitted_cdbs(),
+            [[0x04, 0x01, 0x00, 0x00, 0x00, 0x00]]
+        );
+        assert!(drive.transport().sent_data().is_empty());
+        assert_eq!(completion.transferred, 0);
+    }
+}
@@ -1,6 +1,14 @@
-use crate::scsi::mmc::features::{Feature, FeatureCodeDef, FeatureData};
+use std::io::Cursor;
 
-use super::{Command, Control, OpCode};
+use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+use crate::core::util::DescriptorCursor;
+use crate::device::Drive;
+use crate::scsi::mmc::features::{profile_list::Profile, Feature, FeatureData};
+use crate::scsi::mmc::types::FeatureCode;
+use crate::transport::Transport;
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
@@ -17,6 +25,7 @@ pub enum RtField {
     Supported = 0b10,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GetConfiguration {
     rt: RtField,
     starting_feature_number: u16,
@@ -24,13 +33,367 @@ pub struct GetConfiguration {
     control: Control,
 }
 
+impl GetConfiguration {
+    pub fn new(
+        rt: RtField,
+        starting_feature_number: u16,
+        allocation_length: u16,
+        control: Control,
+    ) -> Self {
+        Self {
+            rt,
+            starting_feature_number,
+            allocation_length,
+            control,
+        }
+    }
+}
+
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GetConfigurationResponse {
-    current_profile: u16,
-    // feature_descriptors: Vec<Feature<dyn FeatureCodeDef<FeatureData = >>>,
+    #[deku(temp, endian = "big")]
+    _data_length: u32,
+
+    #[deku(pad_bytes_before = "2")]
+    pub current_profile: Profile,
+
+    #[deku(reader = "read_features(deku::reader, *_data_length)")]
+    pub features: Vec<Feature>,
+}
+
+/// Reads the Feature Descriptors covered by `data_length`, stopping at the end of the response
+/// if it's shorter. A final descriptor that was cut off by too small of an allocation length is
+/// dropped rather than failing the whole response, as is any zero padding some Drives count in
+/// `data_length` after the last descriptor.
+fn read_features<R: deku::no_std_io::Read + deku::no_std_io::Seek>(
+    reader: &mut Reader<R>,
+    data_length: u32,
+) -> Result<Vec<Feature>, DekuError> {
+    // The Data Length includes the Reserved and Current Profile fields already read
+    let expected = (data_length as usize).saturating_sub(4);
+
+    let mut data = Vec::with_capacity(expected);
+    while data.len() < expected && !reader.end() {
+        data.push(u8::from_reader_with_ctx(reader, ())?);
+    }
+
+    DescriptorCursor::length_prefixed(&data, 3, 4)
+        // All zeros would be a Profile List, but that's always persistent and current
+        .take_while(|descriptor| descriptor.iter().any(|&byte| byte != 0))
+        .map(|descriptor| {
+            Feature::from_reader_with_ctx(&mut Reader::new(Cursor::new(descriptor)), ())
+        })
+        .collect()
+}
+
+impl GetConfigurationResponse {
+    /// Features which are currently active, e.g. those only available with the loaded media.
+    pub fn current_features(&self) -> impl Iterator<Item = &Feature> {
+        self.features.iter().filter(|feature| feature.current)
+    }
+
+    /// Features which are always current, regardless of the loaded media.
+    pub fn persistent_features(&self) -> impl Iterator<Item = &Feature> {
+        self.features.iter().filter(|feature| feature.persistent)
+    }
+
+    pub fn feature(&self, code: FeatureCode) -> Option<&Feature> {
+        self.features
+            .iter()
+            .find(|feature| feature.feature_code == code as u16)
+    }
+
+    /// The Profile Numbers from the Profile List, if it was returned.
+    pub fn profiles(&self) -> impl Iterator<Item = Profile> + '_ {
+        self.features
+            .iter()
+            .filter_map(|feature| match &feature.feature_data {
+                FeatureData::ProfileList(descriptors) => Some(descriptors),
+                _ => None,
+            })
+            .flatten()
+            .map(|descriptor| descriptor.profile_number)
+    }
+
+    /// Whether `code` is reported and currently active.
+    pub fn supports(&self, code: FeatureCode) -> bool {
+        self.feature(code).is_some_and(|feature| feature.current)
+    }
+}
+
+/// Just the Feature Header, the first 8 bytes of every GET CONFIGURATION response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, DekuRead)]
+pub struct FeatureHeader {
+    /// The number of bytes of response data following this field, regardless of the allocation
+    /// length.
+    #[deku(endian = "big")]
+    pub data_length: u32,
+    #[deku(pad_bytes_before = "2")]
+    pub current_profile: Profile,
+}
+
+impl FeatureHeader {
+    const LENGTH: u16 = 8;
+}
+
+impl Response for FeatureHeader {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+impl Response for GetConfigurationResponse {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
 }
 
 type GetConfigurationOpCode = OpCode<0x46>;
 
-// impl Command<GetConfigurationOpCode> for GetConfiguration {
-//     fn as_cdb(&self) -> <GetConfigurationOpCode as super::OpCodeDef>::Cdb {}
-// }
+impl Command<GetConfigurationOpCode> for GetConfiguration {
+    type Response = GetConfigurationResponse;
+
+    fn as_cdb(&self) -> <GetConfigurationOpCode as OpCodeDef>::Cdb {
+        [
+            GetConfigurationOpCode::OP_CODE,
+            (self.rt as u8) & 0b11,
+            (self.starting_feature_number >> 8) as u8,
+            self.starting_feature_number as u8,
+            0,
+            0,
+            0,
+            (self.allocation_length >> 8) as u8,
+            self.allocation_length as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        self.allocation_length.into()
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "GET CONFIGURATION RT {:?} from Feature {:04X}h",
+            self.rt, self.starting_feature_number
+        )
+    }
+}
+
+/// Requests only the Feature Header, to learn how long the complete response is.
+struct GetFeatureHeader(GetConfiguration);
+
+impl Command<GetConfigurationOpCode> for GetFeatureHeader {
+    type Response = FeatureHeader;
+
+    fn as_cdb(&self) -> <GetConfigurationOpCode as OpCodeDef>::Cdb {
+        self.0.as_cdb()
+    }
+
+    fn allocation_len(&self) -> usize {
+        self.0.allocation_len()
+    }
+}
+
+/// Issues GET CONFIGURATION twice, first for just the Feature Header and then with an
+/// allocation length covering all of the data it reports. The Drive truncates the response to
+/// the allocation length, so guessing too small of one would cut off the last descriptors.
+///
+/// Responses longer than the 16-bit allocation length allows are still truncated.
+pub fn get_configuration_full<T: Transport>(
+    drive: &Drive<T>,
+    rt: RtField,
+    starting_feature_number: u16,
+    control: Control,
+) -> Result<GetConfigurationResponse, crate::Error> {
+    let header = drive.execute(GetFeatureHeader(GetConfiguration::new(
+        rt,
+        starting_feature_number,
+        FeatureHeader::LENGTH,
+        control,
+    )))?;
+
+    // The Data Length doesn't include itself
+    let allocation_length = u16::try_from(header.data_length.saturating_add(4)).unwrap_or(u16::MAX);
+
+    drive.execute(GetConfiguration::new(
+        rt,
+        starting_feature_number,
+        allocation_length,
+        control,
+    ))
+}
+
+/// Requests just the Feature Descriptor for `code` with [`RtField::Supported`], rather than
+/// fetching and searching the whole list. Returns `None` if the Drive doesn't support it.
+pub fn get_feature_descriptor<T: Transport>(
+    drive: &Drive<T>,
+    code: FeatureCode,
+    control: Control,
+) -> Result<Option<Feature>, crate::Error> {
+    let configuration = get_configuration_full(drive, RtField::Supported, code as u16, control)?;
+
+    Ok(configuration
+        .features
+        .into_iter()
+        .find(|feature| feature.feature_code == code as u16))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[test]
+    fn filter_features_by_currency() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x00, 0x00, 0x30, 0x00, 0x00, 0x00, 0x10,
+            // Profile List
+            0x00, 0x00, 0x03, 0x08,
+            0x00, 0x10, 0x01, 0x00,
+            0x00, 0x08, 0x00, 0x00,
+            // Core
+            0x00, 0x01, 0x0B, 0x08,
+            0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00,
+            // Random Readable
+            0x00, 0x10, 0x01, 0x08,
+            0x00, 0x00, 0x08, 0x00, 0x00, 0x10, 0x01, 0x00,
+            // CD Read, not current with the DVD-ROM loaded
+            0x00, 0x1E, 0x08, 0x04,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let configuration = GetConfigurationResponse::from_bytes(response).unwrap();
+
+        assert_eq!(configuration.current_profile, Profile::DvdRom);
+        assert_eq!(configuration.features.len(), 4);
+        assert_eq!(configuration.current_features().count(), 3);
+        assert_eq!(configuration.persistent_features().count(), 2);
+
+        assert!(configuration.supports(FeatureCode::Core));
+        assert!(configuration.supports(FeatureCode::RandomReadable));
+        assert!(configuration.feature(FeatureCode::CDRead).is_some());
+        assert!(!configuration.supports(FeatureCode::CDRead));
+        assert!(!configuration.supports(FeatureCode::DVDRead));
+    }
+
+    #[test]
+    fn full_configuration_after_truncated_header() {
+        #[rustfmt::skip]
+        let response = [
+            &[0x00, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x08][..],
+            // Core
+            &[0x00, 0x01, 0x0B, 0x08, 0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00],
+            // Vendor specific
+            &[0xFF, 0x00, 0x01, 0xF0],
+            &[0xAA; 0xF0],
+        ]
+        .concat();
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x46, response));
+
+        // The vendor specific descriptor is cut off by the allocation length
+        let truncated = drive
+            .execute(GetConfiguration::new(RtField::All, 0, 64, 0.into()))
+            .unwrap();
+        assert_eq!(truncated.features.len(), 1);
+
+        let configuration = get_configuration_full(&drive, RtField::All, 0, 0.into()).unwrap();
+
+        assert_eq!(configuration.current_profile, Profile::CdRom);
+        assert_eq!(configuration.features.len(), 2);
+        assert_eq!(configuration.features[1].feature_code, 0xFF00);
+    }
+
+    #[test]
+    fn single_feature_descriptor() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x08,
+            // CD Read with C2 Error Pointers and CD-Text
+            0x00, 0x1E, 0x0B, 0x04,
+            0x83, 0x00, 0x00, 0x00,
+        ];
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x46, response));
+
+        let cd_read = get_feature_descriptor(&drive, FeatureCode::CDRead, 0.into())
+            .unwrap()
+            .unwrap();
+
+        assert!(cd_read.current);
+        let FeatureData::CdRead(descriptor) = cd_read.feature_data else {
+            panic!("expected CD Read, got {:?}", cd_read.feature_data);
+        };
+        assert!(descriptor.dap && descriptor.c2_flags && descriptor.cd_text);
+
+        let cdb = &drive.transport().submitted_cdbs()[1];
+        assert_eq!(cdb[1], RtField::Supported as u8);
+        assert_eq!(&cdb[2..4], [0x00, 0x1E]);
+        assert_eq!(&cdb[7..9], [0x00, 0x14]);
+
+        // Only the Feature Header when the Feature isn't supported
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0x46, [0, 0, 0, 4, 0, 0, 0, 0x08]),
+        );
+        assert!(
+            get_feature_descriptor(&drive, FeatureCode::CDRead, 0.into())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn additional_length_0xff() {
+        #[rustfmt::skip]
+        let response = [
+            &[0x00, 0x00, 0x01, 0x1B, 0x00, 0x00, 0x00, 0x08][..],
+            &[0xFF, 0x00, 0x01, 0xFF],
+            &[0xAA; 0xFF],
+            // Core
+            &[0x00, 0x01, 0x0B, 0x08, 0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00],
+            // Only the header of a descriptor, cut off at the end of the response
+            &[0xFF, 0x01, 0x01, 0xFF],
+        ]
+        .concat();
+
+        let configuration = GetConfigurationResponse::from_bytes(&response).unwrap();
+
+        assert_eq!(configuration.features.len(), 2);
+        assert_eq!(
+            configuration.features[0].feature_data,
+            FeatureData::Unknown(vec![0xAA; 0xFF])
+        );
+        assert!(configuration.supports(FeatureCode::Core));
+    }
+
+    #[test]
+    fn trailing_zero_padding() {
+        #[rustfmt::skip]
+        let core = [0x00, 0x01, 0x0B, 0x08, 0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00];
+
+        for padding in [2, 4, 6] {
+            let data_length = 4 + core.len() + padding;
+            let response = [
+                &[0x00, 0x00, 0x00, data_length as u8, 0x00, 0x00, 0x00, 0x08][..],
+                &core,
+                &vec![0x00; padding],
+            ]
+            .concat();
+
+            let configuration = GetConfigurationResponse::from_bytes(&response).unwrap();
+
+            assert_eq!(
+                configuration.features.len(),
+                1,
+                "{padding} bytes of padding"
+            );
+            assert!(configuration.supports(FeatureCode::Core));
+        }
+    }
+}
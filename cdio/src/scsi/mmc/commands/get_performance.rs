@@ -0,0 +1,298 @@
+use std::io::Cursor;
+
+use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+use crate::{
+    core::{addressing::Lba, util::BitWriter},
+    device::Drive,
+    transport::Transport,
+};
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+/// Which Performance Descriptors the Drive returns.
+///
+/// See MMC-6 §6.8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Except {
+    /// The nominal performance of the media from `starting_lba` to the end.
+    Nominal = 0b00,
+    /// The entire performance list, including exceptions.
+    EntireList = 0b01,
+    /// Only the exceptions to the nominal performance, e.g. slow areas around defects.
+    ExceptionsOnly = 0b10,
+}
+
+/// Requests Performance data (Type 00h). The other Types, e.g. Write Speed descriptors, aren't
+/// supported for now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetPerformance {
+    write: bool,
+    except: Except,
+    starting_lba: Lba,
+    maximum_number_of_descriptors: u16,
+    control: Control,
+}
+
+impl GetPerformance {
+    /// Requests the read (or write, if `write` is set) performance of the media, with a 10%
+    /// tolerance for the nominal performance.
+    pub fn new(
+        write: bool,
+        except: Except,
+        starting_lba: Lba,
+        maximum_number_of_descriptors: u16,
+        control: Control,
+    ) -> Self {
+        Self {
+            write,
+            except,
+            starting_lba,
+            maximum_number_of_descriptors,
+            control,
+        }
+    }
+}
+
+/// The performance over the extent `start_lba..=end_lba`, in kilobytes (1000 bytes) per second.
+///
+/// See MMC-6 §6.8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, DekuRead)]
+#[deku(endian = "big")]
+pub struct PerformanceDescriptor {
+    #[deku(map = "|lba: i32| -> Result<_, DekuError> { Ok(Lba::from(lba)) }")]
+    pub start_lba: Lba,
+    pub start_performance: u32,
+    #[deku(map = "|lba: i32| -> Result<_, DekuError> { Ok(Lba::from(lba)) }")]
+    pub end_lba: Lba,
+    pub end_performance: u32,
+}
+
+impl PerformanceDescriptor {
+    const LENGTH: usize = 16;
+}
+
+/// See MMC-6 §6.8.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Performance {
+    #[deku(temp, endian = "big")]
+    _performance_data_length: u32,
+
+    /// Whether the descriptors describe write, rather than read, performance.
+    #[deku(pad_bits_before = "6", bits = 1)]
+    pub write: bool,
+    /// Whether the descriptors are exceptions to the nominal performance.
+    #[deku(bits = 1, pad_bytes_after = "3")]
+    pub except: bool,
+
+    #[deku(
+        count = "(*_performance_data_length as usize).saturating_sub(4) / PerformanceDescriptor::LENGTH"
+    )]
+    pub performance_descriptors: Vec<PerformanceDescriptor>,
+}
+
+impl Response for Performance {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+/// The nominal performance across the disc, e.g. to anticipate a CAV Drive speeding up toward the
+/// edge of the disc rather than relying on the single maximum speed from the capabilities page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeedProfile {
+    descriptors: Vec<PerformanceDescriptor>,
+}
+
+impl SpeedProfile {
+    pub fn new(performance: Performance) -> Self {
+        Self {
+            descriptors: performance.performance_descriptors,
+        }
+    }
+
+    /// The performance at `lba` in kilobytes per second, interpolated linearly between the start
+    /// and end of the extent containing it. `None` if no extent contains `lba`.
+    pub fn speed_at(&self, lba: Lba) -> Option<u32> {
+        let descriptor = self
+            .descriptors
+            .iter()
+            .find(|descriptor| (descriptor.start_lba..=descriptor.end_lba).contains(&lba))?;
+
+        let start_lba = i64::from(i32::from(descriptor.start_lba));
+        let extent = i64::from(i32::from(descriptor.end_lba)) - start_lba;
+        if extent == 0 {
+            return Some(descriptor.start_performance);
+        }
+
+        let offset = i64::from(i32::from(lba)) - start_lba;
+        let start = i64::from(descriptor.start_performance);
+        let end = i64::from(descriptor.end_performance);
+
+        // Always between the start and end performance, so it fits
+        Some((start + (end - start) * offset / extent) as u32)
+    }
+
+    pub fn descriptors(&self) -> &[PerformanceDescriptor] {
+        &self.descriptors
+    }
+}
+
+/// The most extents [`speed_profile`] requests. Drives usually report a single nominal extent, or
+/// one per zone of a zoned CLV disc.
+const SPEED_PROFILE_MAX_DESCRIPTORS: u16 = 32;
+
+/// Issues GET PERFORMANCE for the nominal read (or write, if `write` is set) performance of the
+/// whole disc.
+pub fn speed_profile<T: Transport>(
+    drive: &Drive<T>,
+    write: bool,
+) -> Result<SpeedProfile, crate::Error> {
+    drive
+        .execute(GetPerformance::new(
+            write,
+            Except::Nominal,
+            Lba::from(0),
+            SPEED_PROFILE_MAX_DESCRIPTORS,
+            0.into(),
+        ))
+        .map(SpeedProfile::new)
+}
+
+type GetPerformanceOpCode = OpCode<0xAC>;
+
+impl Command<GetPerformanceOpCode> for GetPerformance {
+    type Response = Performance;
+
+    fn as_cdb(&self) -> <GetPerformanceOpCode as OpCodeDef>::Cdb {
+        let starting_lba = i32::from(self.starting_lba).to_be_bytes();
+
+        [
+            GetPerformanceOpCode::OP_CODE,
+            BitWriter::new()
+                .set_field(3, 0b11, 0b10) // Tolerance: 10%
+                .set_bit(0b0000_0100, self.write)
+                .set_field(0, 0b11, self.except as u8)
+                .into(),
+            starting_lba[0],
+            starting_lba[1],
+            starting_lba[2],
+            starting_lba[3],
+            0,
+            0,
+            (self.maximum_number_of_descriptors >> 8) as u8,
+            self.maximum_number_of_descriptors as u8,
+            0x00, // Type: Performance
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        8 + usize::from(self.maximum_number_of_descriptors) * PerformanceDescriptor::LENGTH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[test]
+    fn get_performance_cdb() {
+        assert_eq!(
+            GetPerformance::new(true, Except::Nominal, Lba::from(0), 2, 0.into()).as_cdb(),
+            [0xAC, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00]
+        );
+
+        let read_exceptions =
+            GetPerformance::new(false, Except::ExceptionsOnly, Lba::from(0), 2, 0.into());
+        assert_eq!(read_exceptions.as_cdb()[1], 0x12);
+    }
+
+    #[test]
+    fn parse_two_descriptors() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1B, 0x90,
+            0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x2B, 0x11,
+            0x00, 0x10, 0x00, 0x01, 0x00, 0x00, 0x2B, 0x11,
+            0x00, 0x22, 0x1F, 0xFF, 0x00, 0x00, 0x45, 0x60,
+        ];
+
+        let performance = Performance::from_bytes(response).unwrap();
+
+        assert!(!performance.write);
+        assert!(!performance.except);
+        assert_eq!(
+            performance.performance_descriptors,
+            [
+                PerformanceDescriptor {
+                    start_lba: Lba::from(0),
+                    start_performance: 7056,
+                    end_lba: Lba::from(0x100000),
+                    end_performance: 11025,
+                },
+                PerformanceDescriptor {
+                    start_lba: Lba::from(0x100001),
+                    start_performance: 11025,
+                    end_lba: Lba::from(0x221FFF),
+                    end_performance: 17760,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn speed_across_two_regions() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x00,
+            // 4x to 8x over the first 100000 sectors, then 8x to 24x
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xC1,
+            0x00, 0x01, 0x86, 0xA0, 0x00, 0x00, 0x05, 0x82,
+            0x00, 0x01, 0x86, 0xA1, 0x00, 0x00, 0x05, 0x82,
+            0x00, 0x04, 0x93, 0xDF, 0x00, 0x00, 0x10, 0x86,
+        ];
+        let drive = Drive::with_transport(MockTransport::new().with_response(0xAC, response));
+
+        let profile = speed_profile(&drive, false).unwrap();
+
+        assert_eq!(drive.transport().submitted_cdbs()[0][1], 0x10);
+        assert_eq!(profile.descriptors().len(), 2);
+        assert_eq!(profile.speed_at(Lba::from(0)), Some(705));
+        assert_eq!(profile.speed_at(Lba::from(50_000)), Some(1057));
+        assert_eq!(profile.speed_at(Lba::from(100_000)), Some(1410));
+        assert_eq!(profile.speed_at(Lba::from(100_001)), Some(1410));
+        assert_eq!(profile.speed_at(Lba::from(299_999)), Some(4230));
+        assert_eq!(profile.speed_at(Lba::from(300_000)), None);
+        assert_eq!(profile.speed_at(Lba::from(-150)), None);
+
+        let drive = Drive::with_transport(MockTransport::new().with_response(0xAC, response));
+        speed_profile(&drive, true).unwrap();
+        assert_eq!(drive.transport().submitted_cdbs()[0][1], 0x14);
+    }
+
+    #[test]
+    fn speed_across_the_whole_lba_range() {
+        let profile = SpeedProfile::new(Performance {
+            write: false,
+            except: false,
+            performance_descriptors: vec![PerformanceDescriptor {
+                start_lba: Lba::from(i32::MIN),
+                start_performance: 0,
+                end_lba: Lba::from(i32::MAX),
+                end_performance: 1000,
+            }],
+        });
+
+        assert_eq!(profile.speed_at(Lba::from(i32::MIN)), Some(0));
+        assert_eq!(profile.speed_at(Lba::from(0)), Some(500));
+        assert_eq!(profile.speed_at(Lba::from(i32::MAX)), Some(1000));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::scsi::mmc::commands::Response;
+
+use super::{vpd_page_data, Error, PeripheralDeviceType, PeripheralQualifier};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum CodeSet {
+    Binary = 0x1,
+    Ascii = 0x2,
+    Utf8 = 0x3,
+    Reserved(u8),
+}
+
+impl From<u8> for CodeSet {
+    fn from(value: u8) -> Self {
+        match value & 0x0F {
+            0x1 => Self::Binary,
+            0x2 => Self::Ascii,
+            0x3 => Self::Utf8,
+            v => Self::Reserved(v),
+        }
+    }
+}
+
+/// What the designator is associated with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Association {
+    LogicalUnit = 0b00,
+    TargetPort = 0b01,
+    TargetDevice = 0b10,
+    Reserved = 0b11,
+}
+
+impl From<u8> for Association {
+    fn from(value: u8) -> Self {
+        match value & 0b11 {
+            0b00 => Self::LogicalUnit,
+            0b01 => Self::TargetPort,
+            0b10 => Self::TargetDevice,
+            0b11 => Self::Reserved,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum DesignatorType {
+    VendorSpecific = 0x0,
+    /// T10 Vendor ID followed by vendor specific data.
+    T10VendorId = 0x1,
+    Eui64 = 0x2,
+    /// Network Address Authority
+    Naa = 0x3,
+    RelativeTargetPort = 0x4,
+    TargetPortGroup = 0x5,
+    LogicalUnitGroup = 0x6,
+    Md5LogicalUnitIdentifier = 0x7,
+    ScsiNameString = 0x8,
+    ProtocolSpecificPortIdentifier = 0x9,
+    Reserved(u8),
+}
+
+impl From<u8> for DesignatorType {
+    fn from(value: u8) -> Self {
+        match value & 0x0F {
+            0x0 => Self::VendorSpecific,
+            0x1 => Self::T10VendorId,
+            0x2 => Self::Eui64,
+            0x3 => Self::Naa,
+            0x4 => Self::RelativeTargetPort,
+            0x5 => Self::TargetPortGroup,
+            0x6 => Self::LogicalUnitGroup,
+            0x7 => Self::Md5LogicalUnitIdentifier,
+            0x8 => Self::ScsiNameString,
+            0x9 => Self::ProtocolSpecificPortIdentifier,
+            v => Self::Reserved(v),
+        }
+    }
+}
+
+/// A single Designation Descriptor of the Device Identification VPD page.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceIdentifier {
+    /// Only meaningful when `piv` is set and the association is a target port or target device.
+    pub protocol_identifier: u8,
+    pub code_set: CodeSet,
+    /// Protocol Identifier Valid
+    pub piv: bool,
+    pub association: Association,
+    pub designator_type: DesignatorType,
+    /// The raw designator, to be interpreted according to the code set and designator type.
+    pub designator: Vec<u8>,
+}
+
+/// The Device Identification Vital Product Data page (83h), see [SPC-4].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceIdentification {
+    pub peripheral_qualifier: PeripheralQualifier,
+    pub peripheral_device_type: PeripheralDeviceType,
+    pub identifiers: Vec<DeviceIdentifier>,
+}
+
+impl Response for DeviceIdentification {
+    type Error = Error;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let page_data = vpd_page_data(bytes, 0x83)?;
+
+        let peripheral_qualifier = PeripheralQualifier::from((bytes[0] & 0b11100000) >> 5);
+        let peripheral_device_type = PeripheralDeviceType::from(bytes[0] & 0b00011111);
+
+        let mut identifiers = Vec::new();
+        let mut offset = 0;
+        while offset < page_data.len() {
+            let header = page_data
+                .get(offset..(offset + 4))
+                .ok_or(Error::IncompletePage {
+                    expected: 4 + offset + 4,
+                    received: 4 + page_data.len(),
+                })?;
+
+            let designator_length = usize::from(header[3]);
+            let designator_start = offset + 4;
+            let designator_end = designator_start + designator_length;
+
+            let designator =
+                page_data
+                    .get(designator_start..designator_end)
+                    .ok_or(Error::IncompletePage {
+                        expected: 4 + designator_end,
+                        received: 4 + page_data.len(),
+                    })?;
+
+            identifiers.push(DeviceIdentifier {
+                protocol_identifier: header[0] >> 4,
+                code_set: CodeSet::from(header[0]),
+                piv: (header[1] & 0b10000000) != 0,
+                association: Association::from((header[1] & 0b00110000) >> 4),
+                designator_type: DesignatorType::from(header[1]),
+                designator: designator.to_vec(),
+            });
+
+            offset = designator_end;
+        }
+
+        Ok(Self {
+            peripheral_qualifier,
+            peripheral_device_type,
+            identifiers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_device_identification() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x05, 0x83, 0x00, 0x24,
+            // T10 Vendor ID, ASCII
+            0x02, 0x01, 0x00, 0x14,
+            b'H', b'L', b'-', b'D', b'T', b'-', b'S', b'T',
+            b'K', b'Z', b'L', b'H', b'9', b'4', b'7', b'1',
+            b'2', b'3', b' ', b' ',
+            // NAA, binary
+            0x01, 0x03, 0x00, 0x08,
+            0x50, 0x00, 0x0C, 0x50, 0x01, 0x23, 0x45, 0x67,
+        ];
+
+        let device_identification = DeviceIdentification::from_bytes(response).unwrap();
+        let identifiers = &device_identification.identifiers;
+
+        assert_eq!(identifiers.len(), 2);
+
+        assert_eq!(identifiers[0].code_set, CodeSet::Ascii);
+        assert_eq!(identifiers[0].association, Association::LogicalUnit);
+        assert_eq!(identifiers[0].designator_type, DesignatorType::T10VendorId);
+        assert_eq!(identifiers[0].designator, b"HL-DT-STKZLH947123  ");
+
+        assert_eq!(identifiers[1].code_set, CodeSet::Binary);
+        assert_eq!(identifiers[1].designator_type, DesignatorType::Naa);
+        assert_eq!(
+            identifiers[1].designator,
+            [0x50, 0x00, 0x0C, 0x50, 0x01, 0x23, 0x45, 0x67]
+        );
+    }
+
+    #[test]
+    fn truncated_descriptor_is_error() {
+        let response = [0x05, 0x83, 0x00, 0x06, 0x01, 0x0E, 0x00, 0x08, 0x50, 0x00];
+
+        let device_identification = DeviceIdentification::from_bytes(&response);
+
+        assert!(matches!(
+            device_identification,
+            Err(Error::IncompletePage {
+                expected: 16,
+                received: 10
+            })
+        ));
+    }
+}
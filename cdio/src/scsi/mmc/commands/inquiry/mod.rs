@@ -0,0 +1,447 @@
+use std::{marker::PhantomData, str::Utf8Error};
+
+use thiserror::Error;
+
+use crate::{device::Drive, scsi::mmc::types::spc, transport::Transport};
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+pub mod device_identification;
+pub mod unit_serial_number;
+
+const MIN_RESPONSE_LENGTH: usize = 36;
+/// Where the Version Descriptors are in the standard INQUIRY data, 8 of 2 bytes each.
+const VERSION_DESCRIPTORS: std::ops::Range<usize> = 58..74;
+
+mod private {
+    pub trait InquiryPage {
+        const EVPD: bool;
+        const PAGE_CODE: u8;
+        const ALLOCATION_LENGTH: u16;
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("INQUIRY Response must be at least {size} bytes long, received {0}", size = MIN_RESPONSE_LENGTH)]
+    IncompleteResponse(usize),
+    #[error("Vital Product Data page {expected:02X}h was requested, received {received:02X}h")]
+    UnexpectedPageCode { expected: u8, received: u8 },
+    #[error("Vital Product Data page specified {expected} bytes, received {received}")]
+    IncompletePage { expected: usize, received: usize },
+    #[error(transparent)]
+    InvalidASCIISequence(#[from] Utf8Error),
+    #[error("Device is a {0:?}, not a CD/DVD device")]
+    NotOptical(PeripheralDeviceType),
+}
+
+/// Requests either the standard INQUIRY data, or a Vital Product Data page when the response is
+/// one of the VPD page types.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Inquiry<R: InquiryData> {
+    _response_marker: PhantomData<R>,
+    allocation_length: u16,
+    control: Control,
+}
+
+pub trait InquiryData: private::InquiryPage + Response {}
+
+// Standard INQUIRY data
+impl private::InquiryPage for InquiryResponse {
+    const EVPD: bool = false;
+    const PAGE_CODE: u8 = 0x00;
+    const ALLOCATION_LENGTH: u16 = MIN_RESPONSE_LENGTH as u16;
+}
+impl InquiryData for InquiryResponse {}
+
+// Unit Serial Number VPD page
+impl private::InquiryPage for unit_serial_number::UnitSerialNumber {
+    const EVPD: bool = true;
+    const PAGE_CODE: u8 = 0x80;
+    const ALLOCATION_LENGTH: u16 = 0xFF;
+}
+impl InquiryData for unit_serial_number::UnitSerialNumber {}
+
+// Device Identification VPD page
+impl private::InquiryPage for device_identification::DeviceIdentification {
+    const EVPD: bool = true;
+    const PAGE_CODE: u8 = 0x83;
+    const ALLOCATION_LENGTH: u16 = 4096;
+}
+impl InquiryData for device_identification::DeviceIdentification {}
+
+impl<R: InquiryData> Inquiry<R> {
+    pub fn new(control: Control) -> Self {
+        Self {
+            _response_marker: PhantomData,
+            allocation_length: R::ALLOCATION_LENGTH,
+            control,
+        }
+    }
+}
+
+impl Inquiry<InquiryResponse> {
+    /// Requests more than the 36 bytes of standard INQUIRY data, see [`inquiry_full`].
+    pub fn with_allocation_length(mut self, allocation_length: u16) -> Self {
+        self.allocation_length = allocation_length;
+        self
+    }
+}
+
+/// Issues INQUIRY twice, first for the 36 bytes every Drive returns and then for all of the
+/// standard INQUIRY data its Additional Length reports, e.g. for the
+/// [`version_descriptors`](InquiryResponse::version_descriptors). A single request suffices if
+/// the Drive has nothing past the 36 bytes.
+pub fn inquiry_full<T: Transport>(
+    drive: &Drive<T>,
+    control: Control,
+) -> Result<InquiryResponse, crate::Error> {
+    let inquiry = drive.execute(Inquiry::<InquiryResponse>::new(control))?;
+
+    // The Additional Length doesn't include the first 5 bytes
+    let length = u16::from(inquiry.additional_length) + 5;
+    if usize::from(length) <= MIN_RESPONSE_LENGTH {
+        return Ok(inquiry);
+    }
+
+    drive.execute(Inquiry::<InquiryResponse>::new(control).with_allocation_length(length))
+}
+
+/// Checks the header shared by all Vital Product Data pages, returning the page's contents
+/// following the header, bounded to its PAGE LENGTH.
+fn vpd_page_data(bytes: &[u8], page_code: u8) -> Result<&[u8], Error> {
+    if bytes.len() < 4 {
+        return Err(Error::IncompletePage {
+            expected: 4,
+            received: bytes.len(),
+        });
+    }
+
+    if bytes[1] != page_code {
+        return Err(Error::UnexpectedPageCode {
+            expected: page_code,
+            received: bytes[1],
+        });
+    }
+
+    let page_length = usize::from(u16::from_be_bytes([bytes[2], bytes[3]]));
+    bytes
+        .get(4..(4 + page_length))
+        .ok_or(Error::IncompletePage {
+            expected: 4 + page_length,
+            received: bytes.len(),
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PeripheralQualifier {
+    ConnectedOrUnknown = 0b000,
+    NotConnectedButSupported = 0b001,
+    Reserved = 0b010,
+    NotSupported = 0b011,
+    VendorSpecific(u8),
+}
+
+impl From<u8> for PeripheralQualifier {
+    fn from(value: u8) -> Self {
+        match value & 0b111 {
+            0b000 => Self::ConnectedOrUnknown,
+            0b001 => Self::NotConnectedButSupported,
+            0b010 => Self::Reserved,
+            0b011 => Self::NotSupported,
+            v @ 0b100..=0b111 => Self::VendorSpecific(v),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PeripheralDeviceType {
+    DirectAccessBlockDevice = 0x00, // e.g. Magnetic disk
+    SequentialAccessDevice = 0x01,  // e.g. Magnetic tape
+    PrinterDevice = 0x02,
+    ProcessorDevice = 0x03,
+    WriteOnceDevice = 0x04, // e.g. Some optical disks
+    CDOrDVDDevice = 0x05,
+    ScannerDevice = 0x06,        // obsolete
+    OpticalMemoryDevice = 0x07,  // e.g. Some optical disks
+    MediumChangerDevice = 0x08,  // e.g. Jukeboxes
+    CommunicationsDevice = 0x09, // obsolete
+    Obsolete(u8),
+    StorageArrayControllerDevice = 0x0C, // e.g. RAID
+    EnclosureServicesDevice = 0x0D,
+    SimplifiedDirectAccessDevice = 0x0E, // e.g. Magnetic disk
+    OpticalCardReaderWriterDevice = 0x0F,
+    BridgeControllerCommands = 0x10,
+    ObjectBasedStorageDevice = 0x11,
+    AutomationDriveInterface = 0x12,
+    Reserved(u8),
+    WellKnownLogicalUnit = 0x1E,
+    UnknownOrNoDeviceType = 0x1F,
+}
+
+impl From<u8> for PeripheralDeviceType {
+    fn from(value: u8) -> Self {
+        match value & 0x1F {
+            0x00 => Self::DirectAccessBlockDevice,
+            0x01 => Self::SequentialAccessDevice,
+            0x02 => Self::PrinterDevice,
+            0x03 => Self::ProcessorDevice,
+            0x04 => Self::WriteOnceDevice,
+            0x05 => Self::CDOrDVDDevice,
+            0x06 => Self::ScannerDevice,
+            0x07 => Self::OpticalMemoryDevice,
+            0x08 => Self::MediumChangerDevice,
+            0x09 => Self::CommunicationsDevice,
+            v @ 0x0A..=0x0B => Self::Obsolete(v),
+            0x0C => Self::StorageArrayControllerDevice,
+            0x0D => Self::EnclosureServicesDevice,
+            0x0E => Self::SimplifiedDirectAccessDevice,
+            0x0F => Self::OpticalCardReaderWriterDevice,
+            0x10 => Self::BridgeControllerCommands,
+            0x11 => Self::ObjectBasedStorageDevice,
+            0x12 => Self::AutomationDriveInterface,
+            v @ 0x13..=0x1D => Self::Reserved(v),
+            0x1E => Self::WellKnownLogicalUnit,
+            0x1F => Self::UnknownOrNoDeviceType,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TargetPortGroupSupport {
+    NotSupported = 0b00,
+    ImplicitOnly = 0b01,
+    ExplicitOnly = 0b10,
+    ImplicitAndExplicit = 0b11,
+}
+
+impl From<u8> for TargetPortGroupSupport {
+    fn from(value: u8) -> Self {
+        match value & 0b11 {
+            0b00 => Self::NotSupported,
+            0b01 => Self::ImplicitOnly,
+            0b10 => Self::ExplicitOnly,
+            0b11 => Self::ImplicitAndExplicit,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InquiryResponse {
+    pub peripheral_qualifier: PeripheralQualifier,
+    pub peripheral_device_type: PeripheralDeviceType,
+    pub removable_media: bool,
+    pub version: spc::Version,
+    pub normal_aca: bool,
+    pub hierarchical_support: bool,
+    pub response_data_format: u8,
+    pub scc_supported: bool,
+    pub access_controls_coordinator: bool,
+    pub target_port_group_support: TargetPortGroupSupport,
+    pub third_party_copy: bool,
+    pub protect: bool,
+    pub enclosure_services: bool,
+    pub multi_port: bool,
+    pub command_queuing: bool,
+    /// The 8 ASCII character Vendor ID registered with T10.
+    ///
+    /// Valid ASCII characters are codes 0x21 through 0x7E. Vendor ID's are left aligned and may be
+    /// padded on the end with spaces (0x20) if the VendorID is shorter than 8 characters. This
+    /// padding is trimmed during parsing.
+    ///
+    /// Vendor ID assignments available at [T10 Vendor ID Assignments](https://www.t10.org/lists/vid-alph.htm)
+    pub t10_vendor_identification: String,
+    /// The 16 ASCII characters of left-aligned Product Indentification defined by the vendor.
+    /// Trailing whitespace is trimmed.
+    pub product_identification: String,
+    /// The 4 ASCII characters of left-aligned Product Revision data defined by the vendor.
+    /// Trailing whitespace is trimmed.
+    pub product_revision_level: String,
+    // These fields bring us to byte 36 of the response, the minimum size the INQUIRY command
+    // should recieve. Most of what follows is vendor specific and not particularly useful.
+    /// The length of the standard INQUIRY data following this field, which may be longer than
+    /// what was requested.
+    pub additional_length: u8,
+    /// The standards the Drive claims conformance to, e.g. `0x05C0` for MMC-5. Empty unless the
+    /// response was long enough to include them, see [`inquiry_full`]. Unused descriptors (0000h)
+    /// are left out.
+    ///
+    /// See [SPC-4] §6.6.2, Table 147.
+    pub version_descriptors: Vec<u16>,
+}
+
+impl Response for InquiryResponse {
+    type Error = Error;
+
+    fn from_bytes(value: &[u8]) -> Result<Self, Self::Error> {
+        let res_len = value.len();
+        if res_len < MIN_RESPONSE_LENGTH {
+            return Err(Error::IncompleteResponse(res_len));
+        }
+
+        let peripheral_qualifier = PeripheralQualifier::from((value[0] & 0b11100000) >> 5);
+        let peripheral_device_type = PeripheralDeviceType::from(value[0] & 0b00011111);
+        let removable_media = (value[1] & 0b10000000) != 0;
+        let version = spc::Version::from(value[2]);
+        let normal_aca = (value[3] & 0b00100000) != 0;
+        let hierarchical_support = (value[3] & 0b00010000) != 0;
+        let response_data_format = value[3] & 0b00001111;
+        let additional_length = value[4];
+        let scc_supported = (value[5] & 0b10000000) != 0;
+        let access_controls_coordinator = (value[5] & 0b01000000) != 0;
+        let target_port_group_support = TargetPortGroupSupport::from((value[5] & 0b00110000) >> 4);
+        let third_party_copy = (value[5] & 0b00001000) != 0;
+        let protect = (value[5] & 0b00000001) != 0;
+        let enclosure_services = (value[6] & 0b01000000) != 0;
+        let multi_port = (value[6] & 0b00010000) != 0;
+        let command_queuing = (value[7] & 0b00000010) != 0;
+        let t10_vendor_identification = str::from_utf8(&value[8..=15])?.trim_end().to_string();
+        let product_identification = str::from_utf8(&value[16..=31])?.trim_end().to_string();
+        let product_revision_level = str::from_utf8(&value[32..=35])?.trim_end().to_string();
+        let version_descriptors = value
+            .get(VERSION_DESCRIPTORS.start..value.len().min(VERSION_DESCRIPTORS.end))
+            .unwrap_or_default()
+            .chunks_exact(2)
+            .map(|descriptor| u16::from_be_bytes([descriptor[0], descriptor[1]]))
+            .filter(|&descriptor| descriptor != 0)
+            .collect();
+
+        Ok(Self {
+            peripheral_qualifier,
+            peripheral_device_type,
+            removable_media,
+            version,
+            normal_aca,
+            hierarchical_support,
+            response_data_format,
+            scc_supported,
+            access_controls_coordinator,
+            target_port_group_support,
+            third_party_copy,
+            protect,
+            enclosure_services,
+            multi_port,
+            command_queuing,
+            t10_vendor_identification,
+            product_identification,
+            product_revision_level,
+            additional_length,
+            version_descriptors,
+        })
+    }
+}
+
+type InquiryOpCode = OpCode<0x12>;
+
+impl<R: InquiryData> Command<InquiryOpCode> for Inquiry<R> {
+    type Response = R;
+
+    fn as_cdb(&self) -> <InquiryOpCode as OpCodeDef>::Cdb {
+        [
+            InquiryOpCode::OP_CODE,
+            u8::from(R::EVPD),
+            R::PAGE_CODE,
+            (self.allocation_length >> 8) as u8,
+            self.allocation_length as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        self.allocation_length.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device::Drive;
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[test]
+    fn inquiry_through_mock_transport() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x05, 0x80, 0x05, 0x32, 0x1F, 0x00, 0x00, 0x00,
+            b'H', b'L', b'-', b'D', b'T', b'-', b'S', b'T',
+            b'D', b'V', b'D', b'R', b'A', b'M', b' ', b'G',
+            b'H', b'2', b'4', b'N', b'S', b'D', b'0', b' ',
+            b'L', b'H', b'0', b'0',
+        ];
+
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x12, response));
+        let inquiry = drive
+            .execute(Inquiry::<InquiryResponse>::new(0.into()))
+            .unwrap();
+
+        assert_eq!(
+            inquiry.peripheral_qualifier,
+            PeripheralQualifier::ConnectedOrUnknown
+        );
+        assert_eq!(
+            inquiry.peripheral_device_type,
+            PeripheralDeviceType::CDOrDVDDevice
+        );
+        assert!(inquiry.removable_media);
+        assert_eq!(inquiry.version, spc::Version::Spc3);
+        assert_eq!(inquiry.response_data_format, 0x2);
+        assert_eq!(inquiry.t10_vendor_identification, "HL-DT-ST");
+        assert_eq!(inquiry.product_identification, "DVDRAM GH24NSD0");
+        assert_eq!(inquiry.product_revision_level, "LH00");
+    }
+
+    #[test]
+    fn full_inquiry_with_version_descriptors() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x05, 0x80, 0x05, 0x32, 0x45, 0x00, 0x00, 0x00,
+            b'H', b'L', b'-', b'D', b'T', b'-', b'S', b'T',
+            b'D', b'V', b'D', b'R', b'A', b'M', b' ', b'G',
+            b'H', b'2', b'4', b'N', b'S', b'D', b'0', b' ',
+            b'L', b'H', b'0', b'0',
+            // Vendor specific
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // MMC-5, SPC-3, ATA/ATAPI-7, then unused
+            0x05, 0xC0, 0x03, 0x00, 0x1D, 0xE0, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x12, response));
+
+        // The quick path stops at 36 bytes
+        let inquiry = drive
+            .execute(Inquiry::<InquiryResponse>::new(0.into()))
+            .unwrap();
+        assert_eq!(inquiry.additional_length, 0x45);
+        assert!(inquiry.version_descriptors.is_empty());
+
+        let inquiry = inquiry_full(&drive, 0.into()).unwrap();
+        assert_eq!(inquiry.product_revision_level, "LH00");
+        assert_eq!(inquiry.version_descriptors, [0x05C0, 0x0300, 0x1DE0]);
+
+        let allocation_lengths: Vec<u8> = drive
+            .transport()
+            .submitted_cdbs()
+            .iter()
+            .map(|cdb| cdb[4])
+            .collect();
+        assert_eq!(allocation_lengths, [36, 36, 74]);
+    }
+
+    #[test]
+    fn inquiry_response_too_short() {
+        assert!(matches!(
+            InquiryResponse::from_bytes(&[0x05, 0x80, 0x05, 0x32]),
+            Err(Error::IncompleteResponse(4))
+        ));
+    }
+}
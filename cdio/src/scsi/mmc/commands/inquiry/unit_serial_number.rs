@@ -0,0 +1,67 @@
+use crate::scsi::mmc::commands::Response;
+
+use super::{vpd_page_data, Error, PeripheralDeviceType, PeripheralQualifier};
+
+/// The Unit Serial Number Vital Product Data page (80h), see [SPC-4].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitSerialNumber {
+    pub peripheral_qualifier: PeripheralQualifier,
+    pub peripheral_device_type: PeripheralDeviceType,
+    /// The vendor assigned ASCII serial number of the Drive.
+    ///
+    /// The serial number is right-aligned and may be padded with spaces (0x20), this padding is
+    /// trimmed during parsing, along with any trailing spaces some Drives pad the page with.
+    pub product_serial_number: String,
+}
+
+impl Response for UnitSerialNumber {
+    type Error = Error;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let page_data = vpd_page_data(bytes, 0x80)?;
+
+        let peripheral_qualifier = PeripheralQualifier::from((bytes[0] & 0b11100000) >> 5);
+        let peripheral_device_type = PeripheralDeviceType::from(bytes[0] & 0b00011111);
+        let product_serial_number = str::from_utf8(page_data)?.trim().to_string();
+
+        Ok(Self {
+            peripheral_qualifier,
+            peripheral_device_type,
+            product_serial_number,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_unit_serial_number() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x05, 0x80, 0x00, 0x10,
+            b' ', b' ', b' ', b' ', b'K', b'Z', b'L', b'H',
+            b'9', b'4', b'7', b'1', b'2', b'3', b' ', b' ',
+        ];
+
+        let unit_serial_number = UnitSerialNumber::from_bytes(response).unwrap();
+
+        assert_eq!(
+            unit_serial_number.peripheral_device_type,
+            PeripheralDeviceType::CDOrDVDDevice
+        );
+        assert_eq!(unit_serial_number.product_serial_number, "KZLH947123");
+    }
+
+    #[test]
+    fn wrong_page_code_is_error() {
+        assert!(matches!(
+            UnitSerialNumber::from_bytes(&[0x05, 0x83, 0x00, 0x00]),
+            Err(Error::UnexpectedPageCode {
+                expected: 0x80,
+                received: 0x83
+            })
+        ));
+    }
+}
@@ -1,9 +1,36 @@
-use std::error::Error;
+use std::{convert::Infallible, error::Error, time::Duration};
 
 use derive_more::{From, Into};
 
+use crate::core::util::BitWriter;
+
+pub mod format_unit;
 pub mod get_configuration;
+pub mod get_performance;
+pub mod inquiry;
+pub mod mode_select;
+pub mod mode_sense;
+pub mod play_audio_msf;
+pub mod read;
+pub mod read_capacity;
+pub mod read_cd;
+pub mod read_disc_information;
+pub mod read_disc_structure;
+pub mod read_sub_channel;
 pub mod read_toc_pma_atip;
+pub mod read_track_information;
+pub mod report_key;
+pub mod reserve_track;
+pub mod scan;
+pub mod seek;
+pub mod send_cue_sheet;
+pub mod send_key;
+pub mod set_read_ahead;
+pub mod set_streaming;
+pub mod start_stop_unit;
+pub mod stop_play_scan;
+pub mod test_unit_ready;
+pub mod verify;
 
 mod private {
     pub trait Sealed {}
@@ -77,19 +104,101 @@ impl_op_code_def!(
     ]
 );
 
+/// The name of the command with OPERATION CODE `op_code` for logging, e.g. "READ TOC/PMA/ATIP"
+/// for 0x43, or "UNKNOWN" for one this crate doesn't implement.
+pub const fn opcode_name(op_code: u8) -> &'static str {
+    match op_code {
+        0x00 => "TEST UNIT READY",
+        0x04 => "FORMAT UNIT",
+        0x12 => "INQUIRY",
+        0x1A => "MODE SENSE (6)",
+        0x1B => "START STOP UNIT",
+        0x25 => "READ CAPACITY",
+        0x28 => "READ (10)",
+        0x2B => "SEEK (10)",
+        0x2F => "VERIFY (10)",
+        0x42 => "READ SUB-CHANNEL",
+        0x43 => "READ TOC/PMA/ATIP",
+        0x46 => "GET CONFIGURATION",
+        0x47 => "PLAY AUDIO MSF",
+        0x4E => "STOP PLAY/SCAN",
+        0x51 => "READ DISC INFORMATION",
+        0x52 => "READ TRACK INFORMATION",
+        0x53 => "RESERVE TRACK",
+        0x55 => "MODE SELECT (10)",
+        0x5A => "MODE SENSE (10)",
+        0x5D => "SEND CUE SHEET",
+        0x9E => "SERVICE ACTION IN (16)",
+        0xA3 => "SEND KEY",
+        0xA4 => "REPORT KEY",
+        0xA7 => "SET READ AHEAD",
+        0xA8 => "READ (12)",
+        0xAC => "GET PERFORMANCE",
+        0xAD => "READ DISC STRUCTURE",
+        0xB6 => "SET STREAMING",
+        0xBA => "SCAN",
+        0xBE => "READ CD",
+        _ => "UNKNOWN",
+    }
+}
+
+/// How long a command may take before it's aborted, unless it overrides [`Command::timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub trait Command<O: OpCodeDef> {
     type Response: Response;
 
     fn as_cdb(&self) -> O::Cdb;
+
+    /// The size of the buffer to receive the response into.
+    fn allocation_len(&self) -> usize {
+        4096
+    }
+
+    /// Data sent to the Drive along with the CDB. Commands with a parameter list transfer data to
+    /// the Drive instead of from it, so their [`Response`] is typically `()`.
+    fn parameter_list(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// How long the Drive is given to complete the command, long running commands (e.g. FORMAT
+    /// UNIT) should extend this.
+    fn timeout(&self) -> Duration {
+        DEFAULT_TIMEOUT
+    }
+
+    /// A human readable summary of the command for logging, e.g. `"READ CD sectors 100..127 as
+    /// CdDa, USER_DATA"`. By default only the command's name, see [`opcode_name`].
+    fn describe(&self) -> String {
+        opcode_name(O::OP_CODE).to_owned()
+    }
 }
 
 // Might change the associated types / functions later
 pub trait Response: Sized {
-    type Error: Error + 'static;
+    type Error: Error + Into<crate::Error> + 'static;
 
     fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>;
 }
 
+/// The raw response bytes, for commands whose data isn't parsed.
+impl Response for Vec<u8> {
+    type Error = Infallible;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(bytes.to_vec())
+    }
+}
+
+/// For commands that don't return any data.
+impl Response for () {
+    type Error = Infallible;
+
+    fn from_bytes(_: &[u8]) -> Result<Self, Self::Error> {
+        Ok(())
+    }
+}
+
 /// CONTROL byte newtype
 /// ```text
 ///   7   6   5   4   3   2   1   0
@@ -104,5 +213,133 @@ pub trait Response: Sized {
 ///
 /// See: [SAM-6]
 #[repr(transparent)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, From, Into)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, From, Into)]
 pub struct Control(u8);
+
+impl Control {
+    const VENDOR_SPECIFIC_SHIFT: u8 = 6;
+    const VENDOR_SPECIFIC_MASK: u8 = 0b11;
+    const NACA: u8 = 0b0000_0100;
+    const LINK: u8 = 0b0000_0001;
+
+    /// A CONTROL byte with every field cleared, to set fields on.
+    pub const fn builder() -> Self {
+        Self(0)
+    }
+
+    pub const fn naca(self, value: bool) -> Self {
+        Self(BitWriter(self.0).set_bit(Self::NACA, value).0)
+    }
+
+    pub const fn link(self, value: bool) -> Self {
+        Self(BitWriter(self.0).set_bit(Self::LINK, value).0)
+    }
+
+    /// Only the lower two bits of `value` are used.
+    pub const fn vendor_specific(self, value: u8) -> Self {
+        Self(
+            BitWriter(self.0)
+                .set_field(
+                    Self::VENDOR_SPECIFIC_SHIFT,
+                    Self::VENDOR_SPECIFIC_MASK,
+                    value,
+                )
+                .0,
+        )
+    }
+
+    pub const fn is_naca(&self) -> bool {
+        self.0 & Self::NACA != 0
+    }
+
+    pub const fn is_link(&self) -> bool {
+        self.0 & Self::LINK != 0
+    }
+
+    pub const fn vendor_specific_bits(&self) -> u8 {
+        (self.0 >> Self::VENDOR_SPECIFIC_SHIFT) & Self::VENDOR_SPECIFIC_MASK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcode_names() {
+        assert_eq!(opcode_name(0x12), "INQUIRY");
+        assert_eq!(opcode_name(0x43), "READ TOC/PMA/ATIP");
+        assert_eq!(opcode_name(0x46), "GET CONFIGURATION");
+        assert_eq!(opcode_name(0xBE), "READ CD");
+        assert_eq!(opcode_name(0xFF), "UNKNOWN");
+    }
+
+    #[test]
+    fn describe_commands() {
+        use crate::core::addressing::Lba;
+
+        use super::get_configuration::{GetConfiguration, RtField};
+        use super::read_cd::{MainChannelFlags, ReadCd, SectorType, SubChannelSelection};
+        use super::read_toc_pma_atip::{raw_toc::RawToc, ReadTocPmaAtip};
+        use super::test_unit_ready::TestUnitReady;
+
+        let read_cd = ReadCd {
+            sector_type: SectorType::CdDa,
+            starting_lba: Lba::from(100),
+            transfer_length: 27,
+            main_channel: MainChannelFlags::USER_DATA,
+            ..Default::default()
+        };
+        assert_eq!(
+            read_cd.describe(),
+            "READ CD sectors 100..127 as CdDa, USER_DATA"
+        );
+        assert_eq!(
+            ReadCd {
+                sub_channel: SubChannelSelection::QSubChannel,
+                ..read_cd
+            }
+            .describe(),
+            "READ CD sectors 100..127 as CdDa, USER_DATA, QSubChannel"
+        );
+
+        assert_eq!(
+            ReadTocPmaAtip::<RawToc>::new(1, 4096, 0.into()).describe(),
+            "READ TOC/PMA/ATIP format 0010b, track/session 1, MSF"
+        );
+        assert_eq!(
+            GetConfiguration::new(RtField::Supported, 0x001E, 4096, 0.into()).describe(),
+            "GET CONFIGURATION RT Supported from Feature 001Eh"
+        );
+        assert_eq!(TestUnitReady::new(0.into()).describe(), "TEST UNIT READY");
+    }
+
+    #[test]
+    fn control_naca() {
+        let control = Control::builder().naca(true);
+        assert_eq!(u8::from(control), 0b0000_0100);
+        assert!(control.is_naca());
+        assert!(!control.naca(false).is_naca());
+    }
+
+    #[test]
+    fn control_link() {
+        let control = Control::builder().link(true);
+        assert_eq!(u8::from(control), 0b0000_0001);
+        assert!(control.is_link());
+        assert!(!control.link(false).is_link());
+    }
+
+    #[test]
+    fn control_vendor_specific() {
+        let control = Control::builder().vendor_specific(0b10).naca(true);
+        assert_eq!(u8::from(control), 0b1000_0100);
+        assert_eq!(control.vendor_specific_bits(), 0b10);
+        assert_eq!(
+            Control::from(0xFF)
+                .vendor_specific(0)
+                .vendor_specific_bits(),
+            0
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::core::util::BitWriter;
+
+use super::{mode_sense::RawModePage, Command, Control, OpCode, OpCodeDef};
+
+/// The length of the Mode Parameter Header for the 10-byte MODE SELECT.
+const MODE_PARAMETER_HEADER_LENGTH: usize = 8;
+
+/// MODE SELECT (10), sending a single mode page without any block descriptors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModeSelect {
+    save_pages: bool,
+    parameter_list: Vec<u8>,
+    control: Control,
+}
+
+impl ModeSelect {
+    /// With `save_pages` set, the Drive also saves the page to non-volatile memory so it
+    /// persists across resets, if the page is savable.
+    pub fn new<const PAGE_CODE: u8>(
+        page: &RawModePage<PAGE_CODE>,
+        save_pages: bool,
+        control: Control,
+    ) -> Self {
+        // The Mode Data Length is reserved for MODE SELECT, and there are no block descriptors
+        let parameter_list = [&[0; MODE_PARAMETER_HEADER_LENGTH][..], &page.to_bytes()].concat();
+
+        Self {
+            save_pages,
+            parameter_list,
+            control,
+        }
+    }
+}
+
+type ModeSelectOpCode = OpCode<0x55>;
+
+impl Command<ModeSelectOpCode> for ModeSelect {
+    type Response = ();
+
+    fn as_cdb(&self) -> <ModeSelectOpCode as OpCodeDef>::Cdb {
+        let parameter_list_length = self.parameter_list.len() as u16;
+
+        [
+            ModeSelectOpCode::OP_CODE,
+            BitWriter::new()
+                .set_bit(0b0001_0000, true) // PF: the page format follows the standard
+                .set_bit(0b0000_0001, self.save_pages)
+                .into(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            (parameter_list_length >> 8) as u8,
+            parameter_list_length as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn parameter_list(&self) -> Option<&[u8]> {
+        Some(&self.parameter_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_select_cdb() {
+        let page = RawModePage::<0x2A> {
+            parameters: vec![0x01, 0x02],
+        };
+        let mode_select = ModeSelect::new(&page, false, 0.into());
+
+        assert_eq!(
+            mode_select.as_cdb(),
+            [0x55, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x00]
+        );
+        assert_eq!(
+            mode_select.parameter_list().unwrap(),
+            [0, 0, 0, 0, 0, 0, 0, 0, 0x2A, 0x02, 0x01, 0x02]
+        );
+    }
+}
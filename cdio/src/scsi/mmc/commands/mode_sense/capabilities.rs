@@ -0,0 +1,116 @@
+use std::io::Cursor;
+
+use deku::{reader::Reader, DekuError, DekuRead, DekuReader};
+
+use crate::scsi::mmc::commands::Response;
+
+use super::{mode_page_data, private::ModePageCode, ModePage};
+
+/// The CD/DVD Capabilities and Mechanical Status mode page (2Ah).
+///
+/// The page was removed from MMC after MMC-3 in favor of Features and GET PERFORMANCE, but most
+/// Drives still return it, and it's often the only place they report their maximum speeds. Only
+/// the fields present since MMC-1 are modeled.
+///
+/// See MMC-3 §6.3.11, Table 361.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+pub struct CapabilitiesPage {
+    #[deku(pad_bits_before = "2", bits = 1)]
+    pub dvd_ram_read: bool,
+    #[deku(bits = 1)]
+    pub dvd_r_read: bool,
+    #[deku(bits = 1)]
+    pub dvd_rom_read: bool,
+    #[deku(bits = 1)]
+    pub method_2: bool,
+    #[deku(bits = 1)]
+    pub cd_rw_read: bool,
+    #[deku(bits = 1)]
+    pub cd_r_read: bool,
+
+    #[deku(pad_bits_before = "2", bits = 1)]
+    pub dvd_ram_write: bool,
+    #[deku(bits = 1, pad_bits_after = "1")]
+    pub dvd_r_write: bool,
+    #[deku(bits = 1)]
+    pub test_write: bool,
+    #[deku(bits = 1)]
+    pub cd_rw_write: bool,
+    #[deku(bits = 1)]
+    pub cd_r_write: bool,
+
+    #[deku(pad_bytes_before = "1", pad_bits_before = "3", bits = 1)]
+    pub c2_pointers_supported: bool,
+    #[deku(pad_bits_before = "2", bits = 1)]
+    pub cd_da_stream_accurate: bool,
+    #[deku(bits = 1, pad_bytes_after = "2")]
+    pub cd_da_commands_supported: bool,
+
+    /// In kB/s (1000 bytes per second). Obsolete as of MMC-3, and commonly that of CD media
+    /// regardless of what's loaded.
+    #[deku(endian = "big", pad_bytes_after = "2")]
+    pub max_read_speed: u16,
+    /// In KBytes (1024 bytes)
+    #[deku(endian = "big")]
+    pub buffer_size: u16,
+    /// In kB/s, obsolete as of MMC-3.
+    #[deku(endian = "big", pad_bytes_after = "2")]
+    pub current_read_speed: u16,
+    /// In kB/s, obsolete as of MMC-3.
+    #[deku(endian = "big")]
+    pub max_write_speed: u16,
+    /// In kB/s, obsolete as of MMC-3.
+    #[deku(endian = "big")]
+    pub current_write_speed: u16,
+}
+
+impl CapabilitiesPage {
+    /// Where the Current Read Speed is within the page's parameters, for modifying it in a
+    /// [`RawModePage`](super::RawModePage).
+    pub const CURRENT_READ_SPEED_OFFSET: usize = 12;
+}
+
+impl ModePage for CapabilitiesPage {
+    fn from_page_data(data: &[u8]) -> Result<Self, DekuError> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(data)), ())
+    }
+}
+
+impl Response for CapabilitiesPage {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_page_data(mode_page_data(bytes, Self::PAGE_CODE)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_capabilities_page() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x2A, 0x14,
+            0x3B, 0x17, 0x71, 0x73, 0x29, 0x23,
+            // 48x (CD)
+            0x21, 0x14, 0x01, 0x00,
+            0x08, 0x00, 0x21, 0x14, 0x00, 0x00,
+            // 24x (CD)
+            0x10, 0x8A, 0x10, 0x8A,
+        ];
+
+        let page = CapabilitiesPage::from_bytes(response).unwrap();
+
+        assert!(page.dvd_ram_read && page.dvd_rom_read && page.cd_r_read && !page.method_2);
+        assert!(page.dvd_r_write && page.cd_rw_write && !page.dvd_ram_write);
+        assert!(page.c2_pointers_supported);
+        assert!(page.cd_da_stream_accurate);
+        assert!(page.cd_da_commands_supported);
+        assert_eq!(page.max_read_speed, 8468);
+        assert_eq!(page.buffer_size, 2048);
+        assert_eq!(page.max_write_speed, 4234);
+    }
+}
@@ -0,0 +1,321 @@
+use std::marker::PhantomData;
+
+use deku::DekuError;
+
+use crate::core::util::BitWriter;
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+pub mod capabilities;
+pub mod write_parameters;
+
+/// The length of the Mode Parameter Header for the 10-byte MODE SENSE.
+const MODE_PARAMETER_HEADER_LENGTH: usize = 8;
+/// The length of the Mode Parameter Header for the 6-byte MODE SENSE.
+const MODE_PARAMETER_HEADER_6_LENGTH: usize = 4;
+
+mod private {
+    pub trait ModePageCode {
+        const PAGE_CODE: u8;
+        const ALLOCATION_LENGTH: u16;
+    }
+}
+
+/// Which values of the mode page the Drive returns.
+///
+/// See [SPC-4] §6.11.2, Table 98.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum PageControl {
+    Current = 0b00,
+    Changeable = 0b01,
+    Default = 0b10,
+    Saved = 0b11,
+}
+
+/// MODE SENSE (10), requesting a single mode page without any block descriptors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModeSense<P: ModePage> {
+    _response_marker: PhantomData<P>,
+    page_control: PageControl,
+    control: Control,
+}
+
+/// A mode page, which parses as the [`Response`] to MODE SENSE (10), and inside of a
+/// [`ModeSense6Response`] for MODE SENSE (6).
+pub trait ModePage: private::ModePageCode + Response {
+    /// Parses the page's parameters, those following the PAGE LENGTH.
+    fn from_page_data(data: &[u8]) -> Result<Self, DekuError>;
+}
+
+// CD/DVD Capabilities and Mechanical Status page
+impl private::ModePageCode for capabilities::CapabilitiesPage {
+    const PAGE_CODE: u8 = 0x2A;
+    const ALLOCATION_LENGTH: u16 = 0xFF;
+}
+
+// Write Parameters page
+impl private::ModePageCode for write_parameters::WriteParametersPage {
+    const PAGE_CODE: u8 = 0x05;
+    const ALLOCATION_LENGTH: u16 = 0xFF;
+}
+
+/// Any mode page as its raw parameters, e.g. to modify and send back with MODE SELECT without
+/// clobbering the fields that aren't modeled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawModePage<const PAGE_CODE: u8> {
+    /// The bytes following the PAGE LENGTH.
+    pub parameters: Vec<u8>,
+}
+
+impl<const PAGE_CODE: u8> private::ModePageCode for RawModePage<PAGE_CODE> {
+    const PAGE_CODE: u8 = PAGE_CODE;
+    const ALLOCATION_LENGTH: u16 = 0xFF;
+}
+
+impl<const PAGE_CODE: u8> ModePage for RawModePage<PAGE_CODE> {
+    fn from_page_data(data: &[u8]) -> Result<Self, DekuError> {
+        Ok(Self {
+            parameters: data.to_vec(),
+        })
+    }
+}
+
+impl<const PAGE_CODE: u8> Response for RawModePage<PAGE_CODE> {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_page_data(mode_page_data(bytes, PAGE_CODE)?)
+    }
+}
+
+impl<const PAGE_CODE: u8> RawModePage<PAGE_CODE> {
+    /// The complete page as sent with MODE SELECT, with the Parameters Savable bit cleared as
+    /// MODE SELECT requires. Parameters past the 255 bytes a PAGE LENGTH can describe are
+    /// dropped.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let parameters = &self.parameters[..self.parameters.len().min(0xFF)];
+
+        [
+            &[PAGE_CODE & 0b0011_1111, parameters.len() as u8][..],
+            parameters,
+        ]
+        .concat()
+    }
+}
+
+impl<P: ModePage> ModeSense<P> {
+    pub fn new(page_control: PageControl, control: Control) -> Self {
+        Self {
+            _response_marker: PhantomData,
+            page_control,
+            control,
+        }
+    }
+}
+
+/// Skips the 10-byte MODE SENSE's Mode Parameter Header and any block descriptors, returning the
+/// mode page bounded to its PAGE LENGTH, starting from the byte following it.
+fn mode_page_data(bytes: &[u8], page_code: u8) -> Result<&[u8], DekuError> {
+    let header = mode_parameter_header(bytes, MODE_PARAMETER_HEADER_LENGTH)?;
+    let block_descriptor_length = usize::from(u16::from_be_bytes([header[6], header[7]]));
+
+    page_data(
+        &bytes[(MODE_PARAMETER_HEADER_LENGTH + block_descriptor_length).min(bytes.len())..],
+        page_code,
+    )
+}
+
+/// Like [`mode_page_data`], for the 6-byte MODE SENSE's Mode Parameter Header.
+fn mode_page_data_6(bytes: &[u8], page_code: u8) -> Result<&[u8], DekuError> {
+    let header = mode_parameter_header(bytes, MODE_PARAMETER_HEADER_6_LENGTH)?;
+    let block_descriptor_length = usize::from(header[3]);
+
+    page_data(
+        &bytes[(MODE_PARAMETER_HEADER_6_LENGTH + block_descriptor_length).min(bytes.len())..],
+        page_code,
+    )
+}
+
+fn mode_parameter_header(bytes: &[u8], length: usize) -> Result<&[u8], DekuError> {
+    bytes.get(..length).ok_or(DekuError::Parse(
+        format!(
+            "Mode Parameter Header is {length} bytes, received {}",
+            bytes.len()
+        )
+        .into(),
+    ))
+}
+
+/// Checks the page code of the mode page at the start of `page`, returning its parameters.
+fn page_data(page: &[u8], page_code: u8) -> Result<&[u8], DekuError> {
+    let (Some(&page_code_byte), Some(&page_length)) = (page.first(), page.get(1)) else {
+        return Err(DekuError::Parse("Mode page is missing".into()));
+    };
+
+    if page_code_byte & 0b0011_1111 != page_code {
+        return Err(DekuError::Parse(
+            format!(
+                "Mode page {page_code:02X}h was requested, received {:02X}h",
+                page_code_byte & 0b0011_1111
+            )
+            .into(),
+        ));
+    }
+
+    page.get(2..(2 + usize::from(page_length)))
+        .ok_or(DekuError::Parse(
+            format!(
+                "Mode page {page_code:02X}h specified {page_length} bytes, received {}",
+                page.len() - 2
+            )
+            .into(),
+        ))
+}
+
+/// MODE SENSE (6), for older Drives which don't support the 10-byte command. Requests a single
+/// mode page without any block descriptors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModeSense6<P: ModePage> {
+    _response_marker: PhantomData<P>,
+    page_control: PageControl,
+    control: Control,
+}
+
+impl<P: ModePage> ModeSense6<P> {
+    pub fn new(page_control: PageControl, control: Control) -> Self {
+        Self {
+            _response_marker: PhantomData,
+            page_control,
+            control,
+        }
+    }
+}
+
+/// The 4-byte Mode Parameter Header of MODE SENSE (6), followed by the requested page.
+///
+/// See [SPC-4] §7.5.5, Table 447.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModeSense6Response<P: ModePage> {
+    pub medium_type: u8,
+    pub device_specific_parameter: u8,
+    pub page: P,
+}
+
+impl<P: ModePage> Response for ModeSense6Response<P> {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let page = P::from_page_data(mode_page_data_6(bytes, P::PAGE_CODE)?)?;
+
+        Ok(Self {
+            medium_type: bytes[1],
+            device_specific_parameter: bytes[2],
+            page,
+        })
+    }
+}
+
+type ModeSense6OpCode = OpCode<0x1A>;
+
+impl<P: ModePage> Command<ModeSense6OpCode> for ModeSense6<P> {
+    type Response = ModeSense6Response<P>;
+
+    fn as_cdb(&self) -> <ModeSense6OpCode as OpCodeDef>::Cdb {
+        [
+            ModeSense6OpCode::OP_CODE,
+            BitWriter::new().set_bit(0b0000_1000, true).into(), // DBD
+            BitWriter::new()
+                .set_field(6, 0b11, self.page_control as u8)
+                .set_field(0, 0b11_1111, P::PAGE_CODE)
+                .into(),
+            0, // Subpage Code
+            self.allocation_len() as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        // Only a single byte in the 6-byte CDB
+        usize::from(P::ALLOCATION_LENGTH).min(0xFF)
+    }
+}
+
+type ModeSenseOpCode = OpCode<0x5A>;
+
+impl<P: ModePage> Command<ModeSenseOpCode> for ModeSense<P> {
+    type Response = P;
+
+    fn as_cdb(&self) -> <ModeSenseOpCode as OpCodeDef>::Cdb {
+        [
+            ModeSenseOpCode::OP_CODE,
+            BitWriter::new().set_bit(0b0000_1000, true).into(), // DBD
+            BitWriter::new()
+                .set_field(6, 0b11, self.page_control as u8)
+                .set_field(0, 0b11_1111, P::PAGE_CODE)
+                .into(),
+            0, // Subpage Code
+            0,
+            0,
+            0,
+            (P::ALLOCATION_LENGTH >> 8) as u8,
+            P::ALLOCATION_LENGTH as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        P::ALLOCATION_LENGTH.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capabilities::CapabilitiesPage, *};
+
+    #[test]
+    fn mode_sense_capabilities_cdb() {
+        assert_eq!(
+            ModeSense::<CapabilitiesPage>::new(PageControl::Current, 0.into()).as_cdb(),
+            [0x5A, 0x08, 0x2A, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0x00]
+        );
+    }
+
+    #[test]
+    fn page_after_block_descriptor() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04,
+            0xAA, 0xAA, 0xAA, 0xAA,
+            0x2A, 0x02, 0x01, 0x02,
+        ];
+
+        assert_eq!(mode_page_data(response, 0x2A).unwrap(), [0x01, 0x02]);
+        assert!(mode_page_data(response, 0x01).is_err());
+        assert!(mode_page_data(&response[..15], 0x2A).is_err());
+    }
+
+    #[test]
+    fn mode_sense_6_capabilities() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x1B, 0x70, 0x00, 0x00,
+            0x2A, 0x14,
+            0x3B, 0x17, 0x71, 0x73, 0x29, 0x23,
+            0x21, 0x14, 0x01, 0x00,
+            0x08, 0x00, 0x21, 0x14, 0x00, 0x00,
+            0x10, 0x8A, 0x10, 0x8A,
+        ];
+
+        let response = ModeSense6Response::<CapabilitiesPage>::from_bytes(response).unwrap();
+
+        assert_eq!(response.medium_type, 0x70);
+        assert_eq!(response.page.max_read_speed, 8468);
+        assert_eq!(response.page.max_write_speed, 4234);
+
+        assert_eq!(
+            ModeSense6::<CapabilitiesPage>::new(PageControl::Current, 0.into()).as_cdb(),
+            [0x1A, 0x08, 0x2A, 0x00, 0xFF, 0x00]
+        );
+    }
+}
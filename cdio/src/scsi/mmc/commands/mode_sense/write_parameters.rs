@@ -0,0 +1,268 @@
+use deku::DekuError;
+
+use crate::{
+    core::util::BitWriter, rainbow_books::q_subcode::Control, scsi::mmc::commands::Response,
+    scsi::mmc::features::incremental_streaming_writable::DataBlockTypes,
+};
+
+use super::{mode_page_data, private::ModePageCode, ModePage, RawModePage};
+
+/// The Write Parameters page has to be at least this long to hold the modeled fields, up to and
+/// including the Audio Pause Length.
+const MIN_PARAMETERS_LENGTH: usize = 14;
+
+/// How the Drive records, see MMC-6 §4.3.5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum WriteType {
+    Packet = 0x0,
+    TrackAtOnce = 0x1,
+    SessionAtOnce = 0x2,
+    Raw = 0x3,
+    LayerJumpRecording = 0x4,
+}
+
+impl TryFrom<u8> for WriteType {
+    type Error = DekuError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x0 => Self::Packet,
+            0x1 => Self::TrackAtOnce,
+            0x2 => Self::SessionAtOnce,
+            0x3 => Self::Raw,
+            0x4 => Self::LayerJumpRecording,
+            _ => return Err(reserved("Write Type", value)),
+        })
+    }
+}
+
+/// The layout of each block written, see MMC-6 §7.5.4.13, Table 648.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum DataBlockType {
+    /// 2352 bytes of raw data.
+    Raw = 0,
+    /// Raw data followed by 16 bytes of P and Q sub-channel.
+    RawPq = 1,
+    /// Raw data followed by 96 bytes of packed P-W sub-channel.
+    RawPwPack = 2,
+    /// Raw data followed by 96 bytes of raw P-W sub-channel.
+    RawPwRaw = 3,
+    Mode1 = 8,
+    Mode2 = 9,
+    Mode2XaForm1 = 10,
+    /// Form 1 with its 8 byte subheader.
+    Mode2XaForm1Subheader = 11,
+    Mode2XaForm2 = 12,
+    /// Form 1 or Form 2, as given by each block's subheader.
+    Mode2XaMixed = 13,
+}
+
+impl DataBlockType {
+    /// The bit for this Data Block Type in the supported [`DataBlockTypes`] of a Feature, to
+    /// check the Drive supports it before selecting it.
+    pub fn as_flag(self) -> DataBlockTypes {
+        DataBlockTypes::from_bits_retain(1 << self as u8)
+    }
+}
+
+impl TryFrom<u8> for DataBlockType {
+    type Error = DekuError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Raw,
+            1 => Self::RawPq,
+            2 => Self::RawPwPack,
+            3 => Self::RawPwRaw,
+            8 => Self::Mode1,
+            9 => Self::Mode2,
+            10 => Self::Mode2XaForm1,
+            11 => Self::Mode2XaForm1Subheader,
+            12 => Self::Mode2XaForm2,
+            13 => Self::Mode2XaMixed,
+            _ => return Err(reserved("Data Block Type", value)),
+        })
+    }
+}
+
+/// The disc type written to the Lead-in, see MMC-6 §7.5.4.14, Table 649.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SessionFormat {
+    CdDaOrCdRom = 0x00,
+    CdI = 0x10,
+    CdRomXa = 0x20,
+}
+
+impl TryFrom<u8> for SessionFormat {
+    type Error = DekuError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x00 => Self::CdDaOrCdRom,
+            0x10 => Self::CdI,
+            0x20 => Self::CdRomXa,
+            _ => return Err(reserved("Session Format", value)),
+        })
+    }
+}
+
+fn reserved(field: &str, value: u8) -> DekuError {
+    DekuError::Parse(format!("Reserved {field} {value:02X}h in mode page 05h").into())
+}
+
+/// The Write Parameters mode page (05h), to configure before writing.
+///
+/// The fields that aren't modeled, e.g. the Media Catalog Number and ISRC, are kept as read so
+/// they're sent back unchanged by [`WriteParametersPage::to_raw_page`].
+///
+/// See MMC-6 §7.5.4, Table 643.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WriteParametersPage {
+    /// Buffer Underrun Free recording enabled.
+    pub bufe: bool,
+    /// Only simulate writing, the laser stays at read power.
+    pub test_write: bool,
+    pub write_type: WriteType,
+    /// Whether packets are of a fixed [`packet_size`](Self::packet_size).
+    pub fixed_packet: bool,
+    /// The Control nibble of the Q sub-channel of the track written.
+    pub track_mode: Control,
+    pub data_block_type: DataBlockType,
+    /// In blocks, only for [`WriteType::Packet`] with a linking size other than 7.
+    pub link_size: u8,
+    pub session_format: SessionFormat,
+    /// In blocks, only for [`WriteType::Packet`] with [`fixed_packet`](Self::fixed_packet) set.
+    pub packet_size: u32,
+    /// In blocks, the pause before each audio track. 150 (2 seconds) by default.
+    pub audio_pause_length: u16,
+    parameters: Vec<u8>,
+}
+
+impl WriteParametersPage {
+    /// The complete page, with the modeled fields written over the parameters as read, to send
+    /// with [`ModeSelect`](crate::scsi::mmc::commands::mode_select::ModeSelect).
+    pub fn to_raw_page(&self) -> RawModePage<{ Self::PAGE_CODE }> {
+        let mut parameters = self.parameters.clone();
+
+        parameters[0] = BitWriter(parameters[0])
+            .set_bit(0b0100_0000, self.bufe)
+            .set_bit(0b0001_0000, self.test_write)
+            .set_field(0, 0b1111, self.write_type as u8)
+            .into();
+        parameters[1] = BitWriter(parameters[1])
+            .set_bit(0b0010_0000, self.fixed_packet)
+            .set_field(0, 0b1111, self.track_mode.bits())
+            .into();
+        parameters[2] = BitWriter(parameters[2])
+            .set_field(0, 0b1111, self.data_block_type as u8)
+            .into();
+        parameters[3] = self.link_size;
+        parameters[6] = self.session_format as u8;
+        parameters[8..12].copy_from_slice(&self.packet_size.to_be_bytes());
+        parameters[12..14].copy_from_slice(&self.audio_pause_length.to_be_bytes());
+
+        RawModePage { parameters }
+    }
+}
+
+impl ModePage for WriteParametersPage {
+    fn from_page_data(data: &[u8]) -> Result<Self, DekuError> {
+        if data.len() < MIN_PARAMETERS_LENGTH {
+            return Err(DekuError::Parse(
+                format!(
+                    "Mode page 05h is at least {MIN_PARAMETERS_LENGTH} bytes, received {}",
+                    data.len()
+                )
+                .into(),
+            ));
+        }
+
+        Ok(Self {
+            bufe: data[0] & 0b0100_0000 != 0,
+            test_write: data[0] & 0b0001_0000 != 0,
+            write_type: WriteType::try_from(data[0] & 0b1111)?,
+            fixed_packet: data[1] & 0b0010_0000 != 0,
+            track_mode: Control::from_bits_retain(data[1] & 0b1111),
+            data_block_type: DataBlockType::try_from(data[2] & 0b1111)?,
+            link_size: data[3],
+            session_format: SessionFormat::try_from(data[6])?,
+            packet_size: u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+            audio_pause_length: u16::from_be_bytes([data[12], data[13]]),
+            parameters: data.to_vec(),
+        })
+    }
+}
+
+impl Response for WriteParametersPage {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_page_data(mode_page_data(bytes, Self::PAGE_CODE)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    const RESPONSE: &[u8] = &[
+        0x00, 0x3A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x05, 0x32,
+        // BUFE, TAO, Copy, data track, Mode 1, CD-ROM
+        0x41, 0x14, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x96,
+        // Media Catalog Number, left as is
+        0x80, b'0', b'7', b'2', b'4', b'3', b'8', b'4',
+        b'9', b'6', b'0', b'6', b'5', b'0', 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn round_trip_write_parameters() {
+        let mut page = WriteParametersPage::from_bytes(RESPONSE).unwrap();
+
+        assert!(page.bufe && !page.test_write);
+        assert_eq!(page.write_type, WriteType::TrackAtOnce);
+        assert_eq!(page.track_mode, Control::IS_DATA);
+        assert_eq!(page.data_block_type, DataBlockType::Mode1);
+        assert_eq!(page.session_format, SessionFormat::CdDaOrCdRom);
+        assert_eq!(page.audio_pause_length, 150);
+
+        page.write_type = WriteType::SessionAtOnce;
+        let raw = page.to_raw_page();
+
+        // Only the Write Type changed, including the Copy bit that isn't modeled
+        let mut expected = RESPONSE[10..].to_vec();
+        expected[0] = 0x42;
+        assert_eq!(raw.parameters, expected);
+        assert_eq!(&raw.to_bytes()[..2], [0x05, 0x32]);
+
+        let reparsed = WriteParametersPage::from_page_data(&raw.parameters).unwrap();
+        assert_eq!(reparsed.write_type, WriteType::SessionAtOnce);
+        assert_eq!(reparsed.to_raw_page(), raw);
+        assert!(DataBlockTypes::from_bits_retain(0x0100).contains(page.data_block_type.as_flag()));
+    }
+
+    #[test]
+    fn reject_reserved_values() {
+        let mut parameters = RESPONSE[10..].to_vec();
+        parameters[0] = 0x47;
+        assert!(WriteParametersPage::from_page_data(&parameters).is_err());
+
+        let mut parameters = RESPONSE[10..].to_vec();
+        parameters[2] = 0x05;
+        assert!(WriteParametersPage::from_page_data(&parameters).is_err());
+
+        let mut parameters = RESPONSE[10..].to_vec();
+        parameters[6] = 0x30;
+        assert!(WriteParametersPage::from_page_data(&parameters).is_err());
+
+        assert!(WriteParametersPage::from_page_data(&RESPONSE[10..20]).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::core::msf::Msf;
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// Plays audio from `starting_msf` up to, but not including, `ending_msf`, addressed by the same
+/// MSFs reported in the TOC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayAudioMsf {
+    starting_msf: Msf,
+    ending_msf: Msf,
+    control: Control,
+}
+
+impl PlayAudioMsf {
+    /// Returns `None` if `ending_msf` is before `starting_msf`, which the Drive would reject
+    /// with CHECK CONDITION. Equal MSFs are valid, and play nothing.
+    pub fn new(starting_msf: Msf, ending_msf: Msf, control: Control) -> Option<Self> {
+        if ending_msf < starting_msf {
+            return None;
+        }
+
+        Some(Self {
+            starting_msf,
+            ending_msf,
+            control,
+        })
+    }
+}
+
+type PlayAudioMsfOpCode = OpCode<0x47>;
+
+impl Command<PlayAudioMsfOpCode> for PlayAudioMsf {
+    type Response = ();
+
+    fn as_cdb(&self) -> <PlayAudioMsfOpCode as OpCodeDef>::Cdb {
+        [
+            PlayAudioMsfOpCode::OP_CODE,
+            0,
+            0,
+            Msf::min(&self.starting_msf).into(),
+            self.starting_msf.sec().into(),
+            self.starting_msf.frame().into(),
+            Msf::min(&self.ending_msf).into(),
+            self.ending_msf.sec().into(),
+            self.ending_msf.frame().into(),
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::msf::{Frame, Minute, Second};
+
+    use super::*;
+
+    fn msf(m: u8, s: u8, f: u8) -> Msf {
+        Msf::new(
+            Minute::try_from(m).unwrap(),
+            Second::try_from(s).unwrap(),
+            Frame::try_from(f).unwrap(),
+        )
+    }
+
+    #[test]
+    fn play_audio_msf_cdb() {
+        let play = PlayAudioMsf::new(msf(0, 2, 0), msf(4, 31, 74), 0.into()).unwrap();
+
+        assert_eq!(
+            play.as_cdb(),
+            [0x47, 0x00, 0x00, 0x00, 0x02, 0x00, 0x04, 0x1F, 0x4A, 0x00]
+        );
+        assert_eq!(play.allocation_len(), 0);
+        assert!(PlayAudioMsf::new(msf(4, 31, 74), msf(0, 2, 0), 0.into()).is_none());
+    }
+}
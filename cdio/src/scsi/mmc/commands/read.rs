@@ -0,0 +1,188 @@
+use crate::core::{addressing::Lba, util::BitWriter};
+
+use super::{read_cd::MODE1_USER_DATA_SIZE, Command, Control, OpCode, OpCodeDef};
+
+/// The caching flags shared by READ (10) and READ (12).
+///
+/// See MMC-6 §6.15.2, Table 411.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ReadFlags {
+    /// Disable Page Out: the data read is unlikely to be requested again soon, so the Drive
+    /// shouldn't favor keeping it in its cache.
+    pub dpo: bool,
+    /// Force Unit Access: the data must be read from the media rather than the Drive's cache.
+    pub fua: bool,
+}
+
+impl From<ReadFlags> for u8 {
+    fn from(flags: ReadFlags) -> Self {
+        BitWriter::new()
+            .set_bit(0b0001_0000, flags.dpo)
+            .set_bit(0b0000_1000, flags.fua)
+            .into()
+    }
+}
+
+/// Reads user data (2048 bytes per block) from the media. The most widely supported read, but
+/// the TRANSFER LENGTH is limited to 65535 blocks, see [`Read12`] for larger transfers.
+///
+/// See MMC-6 §6.15.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Read10 {
+    pub flags: ReadFlags,
+    pub starting_lba: Lba,
+    pub transfer_length: u16,
+    pub control: Control,
+}
+
+impl Read10 {
+    pub fn new(starting_lba: Lba, transfer_length: u16, control: Control) -> Self {
+        Self {
+            flags: ReadFlags::default(),
+            starting_lba,
+            transfer_length,
+            control,
+        }
+    }
+}
+
+type Read10OpCode = OpCode<0x28>;
+
+impl Command<Read10OpCode> for Read10 {
+    type Response = Vec<u8>;
+
+    fn as_cdb(&self) -> <Read10OpCode as OpCodeDef>::Cdb {
+        let starting_lba = i32::from(self.starting_lba).to_be_bytes();
+        let transfer_length = self.transfer_length.to_be_bytes();
+
+        [
+            Read10OpCode::OP_CODE,
+            self.flags.into(),
+            starting_lba[0],
+            starting_lba[1],
+            starting_lba[2],
+            starting_lba[3],
+            0,
+            transfer_length[0],
+            transfer_length[1],
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        usize::from(self.transfer_length) * MODE1_USER_DATA_SIZE
+    }
+}
+
+/// Like [`Read10`], with a 32-bit TRANSFER LENGTH and the Streaming bit.
+///
+/// See MMC-6 §6.16.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Read12 {
+    pub flags: ReadFlags,
+    pub starting_lba: Lba,
+    pub transfer_length: u32,
+    /// Read with the stream performance set by SET STREAMING, rather than stopping to recover
+    /// errors.
+    pub streaming: bool,
+    pub control: Control,
+}
+
+impl Read12 {
+    pub fn new(starting_lba: Lba, transfer_length: u32, control: Control) -> Self {
+        Self {
+            flags: ReadFlags::default(),
+            starting_lba,
+            transfer_length,
+            streaming: false,
+            control,
+        }
+    }
+}
+
+type Read12OpCode = OpCode<0xA8>;
+
+impl Command<Read12OpCode> for Read12 {
+    type Response = Vec<u8>;
+
+    fn as_cdb(&self) -> <Read12OpCode as OpCodeDef>::Cdb {
+        let starting_lba = i32::from(self.starting_lba).to_be_bytes();
+        let transfer_length = self.transfer_length.to_be_bytes();
+
+        [
+            Read12OpCode::OP_CODE,
+            self.flags.into(),
+            starting_lba[0],
+            starting_lba[1],
+            starting_lba[2],
+            starting_lba[3],
+            transfer_length[0],
+            transfer_length[1],
+            transfer_length[2],
+            transfer_length[3],
+            BitWriter::new().set_bit(0b1000_0000, self.streaming).into(),
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        self.transfer_length as usize * MODE1_USER_DATA_SIZE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{device::Drive, transport::mock::MockTransport};
+
+    use super::*;
+
+    #[test]
+    fn read_10_cdb() {
+        let read = Read10 {
+            flags: ReadFlags {
+                dpo: false,
+                fua: true,
+            },
+            ..Read10::new(Lba::from(0x012345), 0x0102, 0.into())
+        };
+
+        assert_eq!(
+            read.as_cdb(),
+            [0x28, 0x08, 0x00, 0x01, 0x23, 0x45, 0x00, 0x01, 0x02, 0x00]
+        );
+        assert_eq!(read.allocation_len(), 0x0102 * MODE1_USER_DATA_SIZE);
+    }
+
+    #[test]
+    fn read_12_cdb() {
+        let read = Read12 {
+            flags: ReadFlags {
+                dpo: true,
+                fua: false,
+            },
+            streaming: true,
+            ..Read12::new(Lba::from(16), 0x0001_0000, 0.into())
+        };
+
+        assert_eq!(
+            read.as_cdb(),
+            [0xA8, 0x10, 0x00, 0x00, 0x00, 0x10, 0x00, 0x01, 0x00, 0x00, 0x80, 0x00]
+        );
+    }
+
+    #[test]
+    fn read_10_multiple_blocks() {
+        let blocks: Vec<u8> = (0..3u8)
+            .flat_map(|block| [block; MODE1_USER_DATA_SIZE])
+            .collect();
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x28, blocks));
+
+        let data = drive
+            .execute(Read10::new(Lba::from(16), 2, 0.into()))
+            .unwrap();
+
+        assert_eq!(data.len(), 2 * MODE1_USER_DATA_SIZE);
+        assert!(data[..MODE1_USER_DATA_SIZE].iter().all(|&byte| byte == 0));
+        assert!(data[MODE1_USER_DATA_SIZE..].iter().all(|&byte| byte == 1));
+    }
+}
@@ -0,0 +1,245 @@
+use std::io::Cursor;
+
+use deku::{reader::Reader, DekuError, DekuRead, DekuReader};
+
+use crate::core::addressing::Lba;
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+/// The Logical Block size of MMC media, e.g. the user data of a Mode 1 CD sector.
+pub const MMC_BLOCK_LENGTH: u32 = 2048;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadCapacity {
+    control: Control,
+}
+
+impl ReadCapacity {
+    pub fn new(control: Control) -> Self {
+        Self { control }
+    }
+}
+
+/// See MMC-6 §6.23.3, Table 498.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, DekuRead)]
+#[deku(endian = "big")]
+pub struct ReadCapacityResponse {
+    /// The last recorded LBA, not the number of blocks.
+    #[deku(map = "|lba: i32| -> Result<_, DekuError> { Ok(Lba::from(lba)) }")]
+    pub lba: Lba,
+    pub block_length_bytes: u32,
+}
+
+impl ReadCapacityResponse {
+    const LENGTH: usize = 8;
+
+    pub fn block_size(&self) -> u32 {
+        self.block_length_bytes
+    }
+
+    /// Whether the block length is the 2048 bytes MMC requires. Some Drives report the size of
+    /// the entire sector (e.g. 2352) for CD media instead, or a block size from another command
+    /// set entirely.
+    pub fn is_mmc_block_size(&self) -> bool {
+        self.block_length_bytes == MMC_BLOCK_LENGTH
+    }
+
+    /// The capacity of the media in bytes, i.e. every block up to and including the last LBA.
+    /// Returns `None` for blank media, which reports a negative last LBA.
+    pub fn total_bytes(&self) -> Option<u64> {
+        let blocks = u64::try_from(i64::from(i32::from(self.lba)) + 1).ok()?;
+        blocks.checked_mul(u64::from(self.block_length_bytes))
+    }
+}
+
+impl Response for ReadCapacityResponse {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+type ReadCapacityOpCode = OpCode<0x25>;
+
+impl Command<ReadCapacityOpCode> for ReadCapacity {
+    type Response = ReadCapacityResponse;
+
+    fn as_cdb(&self) -> <ReadCapacityOpCode as OpCodeDef>::Cdb {
+        [
+            ReadCapacityOpCode::OP_CODE,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        ReadCapacityResponse::LENGTH
+    }
+}
+
+/// READ CAPACITY (16), for media with more blocks than READ CAPACITY (10) can report.
+///
+/// See [SBC-3] §5.16.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadCapacity16 {
+    control: Control,
+}
+
+impl ReadCapacity16 {
+    const SERVICE_ACTION: u8 = 0x10;
+
+    pub fn new(control: Control) -> Self {
+        Self { control }
+    }
+}
+
+/// See [SBC-3] §5.16.2, Table 66.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(endian = "big")]
+pub struct ReadCapacity16Response {
+    /// The last LBA, not the number of blocks.
+    pub lba: u64,
+    pub block_length_bytes: u32,
+    #[deku(pad_bits_before = "4", bits = 3)]
+    pub p_type: u8,
+    #[deku(bits = 1)]
+    pub prot_en: bool,
+    #[deku(bits = 4)]
+    pub p_i_exponent: u8,
+    /// The number of logical blocks per physical block is 2 to the power of this exponent.
+    #[deku(bits = 4)]
+    pub logical_blocks_per_physical_block_exponent: u8,
+    #[deku(bits = 1)]
+    pub lbpme: bool,
+    #[deku(bits = 1)]
+    pub lbprz: bool,
+    #[deku(bits = 14, pad_bytes_after = "16")]
+    pub lowest_aligned_lba: u16,
+}
+
+impl ReadCapacity16Response {
+    const LENGTH: u32 = 32;
+
+    pub fn logical_blocks_per_physical_block(&self) -> u32 {
+        1 << self.logical_blocks_per_physical_block_exponent
+    }
+
+    /// The capacity of the media in bytes, i.e. every block up to and including the last LBA.
+    pub fn total_bytes(&self) -> Option<u64> {
+        self.lba
+            .checked_add(1)?
+            .checked_mul(u64::from(self.block_length_bytes))
+    }
+}
+
+impl Response for ReadCapacity16Response {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+type ServiceActionIn16OpCode = OpCode<0x9E>;
+
+impl Command<ServiceActionIn16OpCode> for ReadCapacity16 {
+    type Response = ReadCapacity16Response;
+
+    fn as_cdb(&self) -> <ServiceActionIn16OpCode as OpCodeDef>::Cdb {
+        let allocation_length = ReadCapacity16Response::LENGTH.to_be_bytes();
+
+        [
+            ServiceActionIn16OpCode::OP_CODE,
+            Self::SERVICE_ACTION,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            allocation_length[0],
+            allocation_length[1],
+            allocation_length[2],
+            allocation_length[3],
+            0,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        ReadCapacity16Response::LENGTH as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cd_capacity() {
+        // 80 minutes of Mode 1 sectors
+        let response =
+            ReadCapacityResponse::from_bytes(&[0x00, 0x05, 0x7E, 0x3F, 0x00, 0x00, 0x08, 0x00])
+                .unwrap();
+
+        assert_eq!(response.lba, Lba::from(359_999));
+        assert!(response.is_mmc_block_size());
+        assert_eq!(response.total_bytes(), Some(737_280_000));
+    }
+
+    #[test]
+    fn non_mmc_block_size() {
+        let response =
+            ReadCapacityResponse::from_bytes(&[0xFF, 0xFF, 0xFF, 0xFE, 0x00, 0x00, 0x02, 0x00])
+                .unwrap();
+
+        assert_eq!(response.block_size(), 512);
+        assert!(!response.is_mmc_block_size());
+        // Blank media
+        assert_eq!(response.total_bytes(), None);
+
+        let response =
+            ReadCapacityResponse::from_bytes(&[0x7F, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x02, 0x00])
+                .unwrap();
+        assert_eq!(response.total_bytes(), Some(0x8000_0000 * 512));
+    }
+
+    #[test]
+    fn read_capacity_16_large_lba() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x08, 0x00,
+            0x05, 0x12, 0x80, 0x01,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let response = ReadCapacity16Response::from_bytes(response).unwrap();
+
+        assert_eq!(response.lba, 1 << 40);
+        assert_eq!(response.block_length_bytes, 2048);
+        assert_eq!(response.p_type, 0b010);
+        assert!(response.prot_en);
+        assert_eq!(response.p_i_exponent, 1);
+        assert_eq!(response.logical_blocks_per_physical_block(), 4);
+        assert!(response.lbpme);
+        assert_eq!(response.lowest_aligned_lba, 1);
+        assert_eq!(response.total_bytes(), Some(((1 << 40) + 1) * 2048));
+
+        assert_eq!(
+            ReadCapacity16::new(0.into()).as_cdb(),
+            [0x9E, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x20, 0, 0]
+        );
+    }
+}
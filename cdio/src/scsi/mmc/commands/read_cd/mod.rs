@@ -0,0 +1,926 @@
+use std::cmp;
+
+use bitflags::bitflags;
+
+use crate::{
+    core::{
+        addressing::{Address, Lba, Lsn},
+        util::{BitWriter, DescriptorCursor},
+    },
+    device::Drive,
+    rainbow_books::q_subcode,
+    transport::{
+        error::{CDBOrParameterValidationError, MMCError},
+        sgio::ScsiError,
+        Transport,
+    },
+};
+
+use super::{
+    mode_sense::{capabilities::CapabilitiesPage, ModeSense, PageControl},
+    read_track_information::{DataMode, TrackInformation},
+    Command, Control, OpCode, OpCodeDef,
+};
+
+pub mod paranoia;
+pub mod sub_channel;
+
+pub const CDDA_USER_DATA_SIZE: usize = 2352;
+pub const MODE1_USER_DATA_SIZE: usize = 2048;
+pub const MODE2_FORMLESS_USER_DATA_SIZE: usize = 2336;
+pub const MODE2_FORM1_USER_DATA_SIZE: usize = 2048;
+pub const MODE2_FORM2_USER_DATA_SIZE: usize = 2324;
+
+/// The largest TRANSFER LENGTH the 3-byte field can hold.
+pub const MAX_TRANSFER_LENGTH: u32 = 0x00FF_FFFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SectorType {
+    AllTypes = 0b000,
+    CdDa = 0b001,
+    Mode1 = 0b010,
+    Mode2Formless = 0b011,
+    Mode2Form1 = 0b100,
+    Mode2Form2 = 0b101,
+}
+
+impl SectorType {
+    /// The bytes of main channel returned for each sector with `main_channel` selected.
+    ///
+    /// See MMC-6 §6.19.2.9, Table 463. [`SectorType::AllTypes`] could return any of the others,
+    /// so it's the largest of them.
+    pub fn main_channel_size(self, main_channel: MainChannelFlags) -> usize {
+        // SYNC, HEADER, SUBHEADER, USER DATA, and EDC/ECC field sizes
+        let (sync, header, subheader, user_data, edc_ecc) = match self {
+            Self::AllTypes => {
+                return [
+                    Self::CdDa,
+                    Self::Mode1,
+                    Self::Mode2Formless,
+                    Self::Mode2Form1,
+                    Self::Mode2Form2,
+                ]
+                .into_iter()
+                .map(|sector_type| sector_type.main_channel_size(main_channel))
+                .max()
+                .unwrap_or_default();
+            }
+            // Audio has none of the fields, any selection returns the whole sector
+            Self::CdDa if main_channel.is_empty() => return 0,
+            Self::CdDa => return CDDA_USER_DATA_SIZE,
+            Self::Mode1 => (12, 4, 0, MODE1_USER_DATA_SIZE, 288),
+            Self::Mode2Formless => (12, 4, 0, MODE2_FORMLESS_USER_DATA_SIZE, 0),
+            Self::Mode2Form1 => (12, 4, 8, MODE2_FORM1_USER_DATA_SIZE, 280),
+            Self::Mode2Form2 => (12, 4, 8, MODE2_FORM2_USER_DATA_SIZE, 4),
+        };
+
+        [
+            (MainChannelFlags::SYNC, sync),
+            (MainChannelFlags::HEADER, header),
+            (MainChannelFlags::SUBHEADER, subheader),
+            (MainChannelFlags::USER_DATA, user_data),
+            (MainChannelFlags::EDC_ECC, edc_ecc),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| main_channel.contains(*flag))
+        .map(|(_, size)| size)
+        .sum()
+    }
+
+    /// The Sector Type to read a track with, from its control flags (e.g. from the TOC) and the
+    /// Data Mode reported by READ TRACK INFORMATION.
+    ///
+    /// Mode 2 tracks may mix Form 1 and Form 2 sectors, Form 1 is assumed as it's what data
+    /// tracks are typically written with. Falls back to [`SectorType::AllTypes`] when the Drive
+    /// doesn't know the Data Mode.
+    pub fn for_track(track_information: &TrackInformation, control: q_subcode::Control) -> Self {
+        if !control.contains(q_subcode::Control::IS_DATA) {
+            return Self::CdDa;
+        }
+
+        match track_information.data_mode {
+            DataMode::Mode1 => Self::Mode1,
+            DataMode::Mode2 => Self::Mode2Form1,
+            DataMode::Unknown => Self::AllTypes,
+        }
+    }
+}
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct MainChannelFlags: u8 {
+        const SYNC = 1 << 7;
+        const SUBHEADER = 1 << 6;
+        const HEADER = 1 << 5;
+        const USER_DATA = 1 << 4;
+        const EDC_ECC = 1 << 3;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum C2ErrorCode {
+    None = 0b00,
+    /// A bit is associated with each of the 2 352 bytes of main channel where: 0 = No C2 error
+    /// and 1 = C2 error. This results in 294 bytes of C2 error bits. Return the 294 bytes of C2
+    /// error bits in the data stream.
+    ErrorBits = 0b01,
+    /// The Block Error Byte = Logical OR of all of the 294 bytes of C2 error bits. First return
+    /// Block Error Byte, then a pad byte of zero and finally the 294 bytes of C2 error bits.
+    BlockErrorByte = 0b10,
+}
+
+impl C2ErrorCode {
+    /// The bytes of C2 error information appended to each sector.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::ErrorBits => 294,
+            Self::BlockErrorByte => 296,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SubChannelSelection {
+    None = 0b000,
+    QSubChannel = 0b010,
+    RWSubChannel = 0b100,
+}
+
+impl SubChannelSelection {
+    /// The bytes of sub-channel data appended to each sector.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::QSubChannel => 16,
+            Self::RWSubChannel => 96,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadCd {
+    pub sector_type: SectorType,
+    pub dap: bool,
+    pub starting_lba: Lba,
+    /// The number of contiguous blocks to read, only the lower 24 bits are sent to the Drive. See
+    /// [`MAX_TRANSFER_LENGTH`].
+    pub transfer_length: u32,
+    pub main_channel: MainChannelFlags,
+    pub c2_error_info: C2ErrorCode,
+    pub sub_channel: SubChannelSelection,
+    pub control: Control,
+}
+
+impl ReadCd {
+    /// The bytes transferred for each sector: the selected main channel fields, followed by the
+    /// C2 error information and then the sub-channel data.
+    pub fn sector_size(&self) -> usize {
+        self.sector_type.main_channel_size(self.main_channel)
+            + self.c2_error_info.size()
+            + self.sub_channel.size()
+    }
+
+    /// Splits the data returned for this command into its sectors, and each of those into its
+    /// main channel, C2 error information, and sub-channel. A trailing partial sector is ignored.
+    pub fn split_sectors<'a>(&self, data: &'a [u8]) -> impl Iterator<Item = SectorParts<'a>> {
+        let main_channel_size = self.sector_type.main_channel_size(self.main_channel);
+        let c2_size = self.c2_error_info.size();
+
+        DescriptorCursor::fixed(data, self.sector_size()).map(move |sector| {
+            let (main_channel, rest) = sector.split_at(main_channel_size);
+            let (c2, sub_channel) = rest.split_at(c2_size);
+
+            SectorParts {
+                main_channel,
+                c2,
+                sub_channel,
+            }
+        })
+    }
+}
+
+/// A single sector returned by READ CD, see [`ReadCd::split_sectors`]. Fields that weren't
+/// requested are empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SectorParts<'a> {
+    pub main_channel: &'a [u8],
+    /// The C2 error bits, or the Block Error Byte followed by them, see [`C2ErrorCode`].
+    pub c2: &'a [u8],
+    pub sub_channel: &'a [u8],
+}
+
+impl SectorParts<'_> {
+    /// Whether the Drive flagged any byte of the sector as a C2 error. Always `false` if C2 error
+    /// information wasn't requested.
+    pub fn has_c2_errors(&self) -> bool {
+        // The Block Error Byte is the OR of the error bits, and its pad byte is zero
+        self.c2.iter().any(|&byte| byte != 0)
+    }
+}
+
+impl Default for ReadCd {
+    fn default() -> Self {
+        Self {
+            sector_type: SectorType::AllTypes,
+            dap: false,
+            starting_lba: Lba::from(0),
+            transfer_length: 0,
+            main_channel: MainChannelFlags::empty(),
+            c2_error_info: C2ErrorCode::None,
+            sub_channel: SubChannelSelection::None,
+            control: 0.into(),
+        }
+    }
+}
+
+type ReadCdOpCode = OpCode<0xBE>;
+
+impl Command<ReadCdOpCode> for ReadCd {
+    type Response = Vec<u8>;
+
+    fn as_cdb(&self) -> <ReadCdOpCode as OpCodeDef>::Cdb {
+        let starting_lba = i32::from(self.starting_lba).to_be_bytes();
+        let transfer_length = self.transfer_length.to_be_bytes();
+
+        [
+            ReadCdOpCode::OP_CODE,
+            BitWriter::new()
+                .set_field(2, 0b111, self.sector_type as u8)
+                .set_bit(0b0000_0010, self.dap)
+                .into(),
+            starting_lba[0],
+            starting_lba[1],
+            starting_lba[2],
+            starting_lba[3],
+            transfer_length[1],
+            transfer_length[2],
+            transfer_length[3],
+            BitWriter(self.main_channel.bits())
+                .set_field(1, 0b11, self.c2_error_info as u8)
+                .into(),
+            BitWriter::new()
+                .set_field(0, 0b111, self.sub_channel as u8)
+                .into(),
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        let sectors = (self.transfer_length & MAX_TRANSFER_LENGTH) as usize;
+
+        sectors * self.sector_size()
+    }
+
+    fn describe(&self) -> String {
+        let end = self.starting_lba + (self.transfer_length & MAX_TRANSFER_LENGTH) as i32;
+        let main_channel: Vec<&str> = self
+            .main_channel
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect();
+        let mut description = format!(
+            "READ CD sectors {}..{} as {:?}, {}",
+            self.starting_lba,
+            end,
+            self.sector_type,
+            main_channel.join(" | ")
+        );
+
+        if self.c2_error_info != C2ErrorCode::None {
+            description += &format!(", C2 {:?}", self.c2_error_info);
+        }
+        if self.sub_channel != SubChannelSelection::None {
+            description += &format!(", {:?}", self.sub_channel);
+        }
+
+        description
+    }
+}
+
+/// 2352 * 27 = 63504 ~ 64 KBs common CD firmware limit
+pub const MAX_SECTORS_PER_READ: u32 = 27;
+
+/// Reads a range of sectors' user data in chunks of at most [`MAX_SECTORS_PER_READ`], yielding
+/// each chunk along with the number of sectors remaining after it.
+#[derive(Debug)]
+pub struct SectorReader<'a, T: Transport> {
+    drive: &'a Drive<T>,
+    remaining: u32,
+    sectors_per_read: u32,
+    stop_at_leadout: bool,
+    command: ReadCd,
+}
+
+impl<'a, T: Transport> SectorReader<'a, T> {
+    pub fn new(drive: &'a Drive<T>, start: Lba, sectors: u32) -> Self {
+        Self {
+            drive,
+            remaining: sectors,
+            sectors_per_read: MAX_SECTORS_PER_READ,
+            stop_at_leadout: false,
+            command: ReadCd {
+                sector_type: SectorType::AllTypes,
+                starting_lba: start,
+                main_channel: MainChannelFlags::USER_DATA,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Like [`SectorReader::new`], reading at most `max_sectors_per_read` sectors with each READ
+    /// CD rather than [`MAX_SECTORS_PER_READ`], e.g. fewer for USB bridges that only accept
+    /// small transfers.
+    ///
+    /// If a read larger than [`MAX_SECTORS_PER_READ`] fails, the reader falls back to it, like
+    /// with [`SectorReader::with_prefetch`].
+    pub fn with_max_sectors_per_read(
+        drive: &'a Drive<T>,
+        start: Lba,
+        sectors: u32,
+        max_sectors_per_read: u32,
+    ) -> Self {
+        let mut reader = Self::new(drive, start, sectors);
+        reader.sectors_per_read = max_sectors_per_read.clamp(1, MAX_TRANSFER_LENGTH);
+        reader
+    }
+
+    /// Like [`SectorReader::with_max_sectors_per_read`], reading as many sectors as fit in the
+    /// Drive's buffer, from the CD/DVD Capabilities and Mechanical Status page. Falls back to
+    /// [`MAX_SECTORS_PER_READ`] if the Drive doesn't return the page or reports no buffer.
+    pub fn auto(drive: &'a Drive<T>, start: Lba, sectors: u32) -> Self {
+        let max_sectors_per_read = drive
+            .execute(ModeSense::<CapabilitiesPage>::new(
+                PageControl::Current,
+                0.into(),
+            ))
+            .ok()
+            .map(|page| u32::from(page.buffer_size) * 1024 / CDDA_USER_DATA_SIZE as u32)
+            .filter(|&sectors| sectors > 0)
+            .unwrap_or(MAX_SECTORS_PER_READ);
+
+        Self::with_max_sectors_per_read(drive, start, sectors, max_sectors_per_read)
+    }
+
+    /// Like [`SectorReader::new`], reading the entire 2352 bytes of the main channel of every
+    /// sector, including the sync, headers, and EDC/ECC of data sectors.
+    pub fn raw(drive: &'a Drive<T>, start: Lba, sectors: u32) -> Self {
+        let mut reader = Self::new(drive, start, sectors);
+        reader.command.main_channel = MainChannelFlags::SYNC
+            | MainChannelFlags::SUBHEADER
+            | MainChannelFlags::HEADER
+            | MainChannelFlags::USER_DATA
+            | MainChannelFlags::EDC_ECC;
+        reader
+    }
+
+    /// Like [`SectorReader::new`], starting from an LSN, e.g. one from a libcdio style track table.
+    /// An LSN is already the sector's LBA, see [`Lsn`], so nothing is added for the pregap.
+    pub fn from_lsn(drive: &'a Drive<T>, start: Lsn, sectors: u32) -> Self {
+        Self::new(drive, start.to_lba(), sectors)
+    }
+
+    /// Only reads sectors of `sector_type`, the Drive fails the read on any other. E.g.
+    /// [`SectorType::CdDa`] makes sure data sectors are never returned as audio.
+    pub fn sector_type(mut self, sector_type: SectorType) -> Self {
+        self.command.sector_type = sector_type;
+        self
+    }
+
+    /// Reads `windows` chunks of [`MAX_SECTORS_PER_READ`] with each READ CD rather than one, so
+    /// the Drive streams through them without the Host seeking between every chunk. Higher
+    /// throughput on clean discs, but not every Drive (or host adapter) accepts transfers this
+    /// large.
+    ///
+    /// If a prefetching read fails, the reader falls back to reading one chunk at a time from
+    /// the same position for the rest of the range.
+    pub fn with_prefetch(mut self, windows: u32) -> Self {
+        self.sectors_per_read = windows
+            .saturating_mul(MAX_SECTORS_PER_READ)
+            .clamp(MAX_SECTORS_PER_READ, MAX_TRANSFER_LENGTH);
+        self
+    }
+
+    /// Ends the read cleanly at the Lead-out rather than with an error, for when the range's end
+    /// isn't known exactly, e.g. a disc whose reported length is slightly off. A read that
+    /// crosses the Lead-out is retried a sector at a time to find exactly where it is.
+    pub fn stop_at_leadout(mut self, stop_at_leadout: bool) -> Self {
+        self.stop_at_leadout = stop_at_leadout;
+        self
+    }
+
+    /// The LBA of the next sector to be read.
+    pub fn position(&self) -> Lba {
+        self.command.starting_lba
+    }
+}
+
+impl<T: Transport> Iterator for SectorReader<'_, T> {
+    type Item = Result<(Vec<u8>, u32), crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let sectors_to_read = cmp::min(self.remaining, self.sectors_per_read);
+
+        self.command.transfer_length = sectors_to_read;
+
+        let data = self.drive.execute(self.command);
+
+        if self.stop_at_leadout && data.as_ref().is_err_and(is_past_leadout) {
+            if sectors_to_read == 1 {
+                self.remaining = 0;
+                return None;
+            }
+
+            self.sectors_per_read = 1;
+            return self.next();
+        }
+
+        if data.is_err() && self.sectors_per_read > MAX_SECTORS_PER_READ {
+            self.sectors_per_read = MAX_SECTORS_PER_READ;
+            return self.next();
+        }
+
+        // Never more than MAX_TRANSFER_LENGTH, so the conversion can't truncate
+        self.command.starting_lba += sectors_to_read as i32;
+        self.remaining -= sectors_to_read;
+
+        Some(data.map(|v| (v, self.remaining)))
+    }
+}
+
+/// Drives report reads past the end of the disc as an out of range LBA.
+fn is_past_leadout(error: &crate::Error) -> bool {
+    matches!(
+        error,
+        crate::Error::Scsi(ScsiError::MMCError(
+            MMCError::CDBOrParameterValidationError(
+                CDBOrParameterValidationError::LogicalBlockAddressOutOfRange
+            )
+        ))
+    )
+}
+
+pub fn read_audio_range<T: Transport>(
+    drive: &Drive<T>,
+    start: Lba,
+    sectors: u32,
+) -> Result<Vec<u8>, crate::Error> {
+    read_audio_range_with_progress(drive, start, sectors, |_, _| {})
+}
+
+/// Like [`read_audio_range`], starting from an LSN rather than an LBA, so track tables in LSNs
+/// (e.g. from `cdio_get_track_lsn`) can be used as is. Adding the 150 frame pregap to them by
+/// hand would start the read 150 sectors late.
+pub fn read_audio_range_lsn<T: Transport>(
+    drive: &Drive<T>,
+    start: Lsn,
+    sectors: u32,
+) -> Result<Vec<u8>, crate::Error> {
+    read_audio_range(drive, start.to_lba(), sectors)
+}
+
+/// Like [`read_audio_range`], calling `progress` after each chunk is read with the LBA of the next
+/// sector to be read and the number of sectors remaining.
+pub fn read_audio_range_with_progress<T: Transport>(
+    drive: &Drive<T>,
+    start: Lba,
+    sectors: u32,
+    mut progress: impl FnMut(Lba, u32),
+) -> Result<Vec<u8>, crate::Error> {
+    let mut out = Vec::<u8>::with_capacity(CDDA_USER_DATA_SIZE * sectors as usize);
+
+    let mut reader = SectorReader::new(drive, start, sectors);
+    while let Some(chunk) = reader.next() {
+        let (data, remaining) = chunk?;
+        out.extend_from_slice(&data);
+
+        progress(reader.position(), remaining);
+    }
+
+    Ok(out)
+}
+
+/// Like [`read_audio_range`], shifting the audio by the Drive's read offset in samples (e.g. as
+/// listed in the AccurateRip drive offset database) so that sample `n` of the result is sample
+/// `n` of the disc.
+///
+/// The start of the read is shifted by as many whole sectors as the offset spans, and one extra
+/// sector is read unless the offset is a whole number of sectors, e.g. an offset of +667 samples
+/// reads 11 sectors from `start + 1` for 10 sectors. The Drive must be able to read the sectors
+/// before or after the range that the offset reaches into.
+///
+/// Fails with [`Error::ShortRead`](crate::Error::ShortRead) if the Drive returns less audio than
+/// that, rather than returning fewer samples than requested.
+pub fn read_audio_range_with_offset<T: Transport>(
+    drive: &Drive<T>,
+    start: Lba,
+    sectors: u32,
+    offset: i32,
+) -> Result<Vec<u8>, crate::Error> {
+    let (read_start, read_sectors, skip) = offset_read_range(start, sectors, offset);
+
+    let mut audio = read_audio_range(drive, read_start, read_sectors)?;
+    let end = skip + sectors as usize * CDDA_USER_DATA_SIZE;
+    if audio.len() < end {
+        return Err(crate::Error::ShortRead {
+            expected: end,
+            received: audio.len(),
+        });
+    }
+
+    audio.truncate(end);
+    audio.drain(..skip);
+    Ok(audio)
+}
+
+/// 16-bit stereo PCM
+const CDDA_SAMPLE_SIZE: i64 = 4;
+
+/// The sectors to read to cover `sectors` from `start` shifted by `offset` samples, and the bytes
+/// to skip at the start of what's read.
+fn offset_read_range(start: Lba, sectors: u32, offset: i32) -> (Lba, u32, usize) {
+    let offset_bytes = i64::from(offset) * CDDA_SAMPLE_SIZE;
+    let sector_shift = offset_bytes.div_euclid(CDDA_USER_DATA_SIZE as i64);
+    let skip = offset_bytes.rem_euclid(CDDA_USER_DATA_SIZE as i64) as usize;
+
+    // The shift is at most i32::MAX * 4 / 2352 sectors, so it always fits
+    let read_start = start + sector_shift as i32;
+    let read_sectors = sectors + u32::from(skip != 0);
+
+    (read_start, read_sectors, skip)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scsi::mmc::commands::Response, transport::mock::MockTransport};
+
+    use super::*;
+
+    #[test]
+    fn read_cd_cdb() {
+        let read_cd = ReadCd {
+            sector_type: SectorType::CdDa,
+            dap: true,
+            starting_lba: Lba::from(0x012345),
+            transfer_length: 27,
+            main_channel: MainChannelFlags::SYNC
+                | MainChannelFlags::HEADER
+                | MainChannelFlags::USER_DATA,
+            c2_error_info: C2ErrorCode::BlockErrorByte,
+            sub_channel: SubChannelSelection::QSubChannel,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            read_cd.as_cdb(),
+            [0xBE, 0x06, 0x00, 0x01, 0x23, 0x45, 0x00, 0x00, 0x1B, 0xB4, 0x02, 0x00]
+        );
+        assert_eq!(
+            read_cd.allocation_len(),
+            27 * (CDDA_USER_DATA_SIZE + 296 + 16)
+        );
+    }
+
+    #[test]
+    fn c2_and_sub_channel_allocation_len() {
+        let user_data = ReadCd {
+            sector_type: SectorType::Mode1,
+            transfer_length: 10,
+            main_channel: MainChannelFlags::USER_DATA,
+            ..Default::default()
+        };
+
+        #[rustfmt::skip]
+        let combinations = [
+            (C2ErrorCode::None, SubChannelSelection::None, 0),
+            (C2ErrorCode::ErrorBits, SubChannelSelection::None, 294),
+            (C2ErrorCode::BlockErrorByte, SubChannelSelection::None, 296),
+            (C2ErrorCode::None, SubChannelSelection::QSubChannel, 16),
+            (C2ErrorCode::None, SubChannelSelection::RWSubChannel, 96),
+            (C2ErrorCode::ErrorBits, SubChannelSelection::QSubChannel, 294 + 16),
+            (C2ErrorCode::BlockErrorByte, SubChannelSelection::RWSubChannel, 296 + 96),
+        ];
+
+        for (c2_error_info, sub_channel, extra) in combinations {
+            let read_cd = ReadCd {
+                c2_error_info,
+                sub_channel,
+                ..user_data
+            };
+            assert_eq!(
+                read_cd.allocation_len(),
+                10 * (MODE1_USER_DATA_SIZE + extra),
+                "{c2_error_info:?} with {sub_channel:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn split_data_and_c2() {
+        let read_cd = ReadCd {
+            sector_type: SectorType::CdDa,
+            transfer_length: 2,
+            main_channel: MainChannelFlags::USER_DATA,
+            c2_error_info: C2ErrorCode::ErrorBits,
+            ..Default::default()
+        };
+
+        let mut c2 = [0u8; 294];
+        // Bytes 8 to 11 of the second sector
+        c2[1] = 0x0F;
+        let data = [
+            &[0x11; CDDA_USER_DATA_SIZE][..],
+            &[0; 294],
+            &[0x22; CDDA_USER_DATA_SIZE],
+            &c2,
+        ]
+        .concat();
+
+        let sectors: Vec<SectorParts> = read_cd.split_sectors(&data).collect();
+
+        assert_eq!(sectors.len(), 2);
+        assert_eq!(sectors[0].main_channel, [0x11; CDDA_USER_DATA_SIZE]);
+        assert_eq!(sectors[0].c2.len(), 294);
+        assert!(!sectors[0].has_c2_errors());
+
+        assert_eq!(sectors[1].main_channel, [0x22; CDDA_USER_DATA_SIZE]);
+        assert_eq!(sectors[1].c2, c2);
+        assert!(sectors[1].sub_channel.is_empty());
+        assert!(sectors[1].has_c2_errors());
+    }
+
+    #[test]
+    fn read_cd_allocation_len() {
+        let user_data = ReadCd {
+            sector_type: SectorType::Mode1,
+            transfer_length: 10,
+            main_channel: MainChannelFlags::USER_DATA,
+            ..Default::default()
+        };
+        assert_eq!(user_data.allocation_len(), 10 * MODE1_USER_DATA_SIZE);
+
+        let raw_with_c2 = ReadCd {
+            main_channel: MainChannelFlags::all(),
+            c2_error_info: C2ErrorCode::ErrorBits,
+            ..user_data
+        };
+        assert_eq!(raw_with_c2.sector_size(), 2352 + 294);
+        assert_eq!(
+            ReadCd {
+                sector_type: SectorType::Mode2Form2,
+                ..raw_with_c2
+            }
+            .sector_size(),
+            2352 + 294
+        );
+
+        let user_data_with_q = ReadCd {
+            sector_type: SectorType::Mode2Form1,
+            sub_channel: SubChannelSelection::QSubChannel,
+            ..user_data
+        };
+        assert_eq!(
+            user_data_with_q.allocation_len(),
+            10 * (MODE2_FORM1_USER_DATA_SIZE + 16)
+        );
+
+        // Any of the sectors could be audio, which is always returned whole
+        let headers = ReadCd {
+            sector_type: SectorType::AllTypes,
+            main_channel: MainChannelFlags::HEADER | MainChannelFlags::USER_DATA,
+            ..user_data
+        };
+        assert_eq!(headers.sector_size(), CDDA_USER_DATA_SIZE);
+    }
+
+    #[test]
+    fn sector_type_for_track() {
+        fn track_information(track_mode: u8, data_mode: u8) -> TrackInformation {
+            let mut response = [0u8; 48];
+            response[1] = 0x2E;
+            response[5] = track_mode;
+            response[6] = data_mode;
+            TrackInformation::from_bytes(&response).unwrap()
+        }
+
+        let audio = track_information(0x00, 0x0F);
+        assert_eq!(
+            SectorType::for_track(&audio, audio.track_mode),
+            SectorType::CdDa
+        );
+
+        let mode_1 = track_information(0x04, 0x01);
+        assert_eq!(
+            SectorType::for_track(&mode_1, mode_1.track_mode),
+            SectorType::Mode1
+        );
+
+        let mode_2 = track_information(0x04, 0x02);
+        assert_eq!(
+            SectorType::for_track(&mode_2, mode_2.track_mode),
+            SectorType::Mode2Form1
+        );
+
+        let unknown = track_information(0x04, 0x0F);
+        assert_eq!(
+            SectorType::for_track(&unknown, unknown.track_mode),
+            SectorType::AllTypes
+        );
+    }
+
+    #[test]
+    fn read_audio_range_reports_progress_per_chunk() {
+        let drive = Drive::with_transport(MockTransport::new().with_response(
+            0xBE,
+            vec![0u8; MAX_SECTORS_PER_READ as usize * CDDA_USER_DATA_SIZE],
+        ));
+
+        let mut calls = Vec::new();
+        let audio = read_audio_range_with_progress(&drive, Lba::from(100), 60, |lba, remaining| {
+            calls.push((lba, remaining))
+        })
+        .unwrap();
+
+        assert_eq!(audio.len(), 60 * CDDA_USER_DATA_SIZE);
+        assert_eq!(
+            calls,
+            [
+                (Lba::from(127), 33),
+                (Lba::from(154), 6),
+                (Lba::from(160), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn chunks_respect_max_sectors_per_read() {
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; 100 * CDDA_USER_DATA_SIZE]),
+        );
+
+        let sectors: Vec<usize> =
+            SectorReader::with_max_sectors_per_read(&drive, Lba::from(0), 25, 10)
+                .map(|chunk| chunk.unwrap().0.len() / CDDA_USER_DATA_SIZE)
+                .collect();
+        assert_eq!(sectors, [10, 10, 5]);
+
+        #[rustfmt::skip]
+        let capabilities: &[u8] = &[
+            0x00, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xAA, 0x14,
+            0x3B, 0x17, 0x71, 0x73, 0x29, 0x23,
+            0x21, 0x14, 0x01, 0x00,
+            // Buffer Size, 128 KBytes
+            0x00, 0x80,
+            0x10, 0x8A, 0x00, 0x00, 0x10, 0x8A, 0x10, 0x8A,
+        ];
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0x5A, capabilities)
+                .with_response(0xBE, vec![0u8; 100 * CDDA_USER_DATA_SIZE]),
+        );
+
+        let sectors: Vec<usize> = SectorReader::auto(&drive, Lba::from(0), 100)
+            .map(|chunk| chunk.unwrap().0.len() / CDDA_USER_DATA_SIZE)
+            .collect();
+        assert_eq!(sectors, [55, 45]);
+
+        // Without the page
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; 100 * CDDA_USER_DATA_SIZE]),
+        );
+        assert_eq!(SectorReader::auto(&drive, Lba::from(0), 100).count(), 4);
+    }
+
+    #[test]
+    fn prefetch_issues_fewer_reads() {
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; 100 * CDDA_USER_DATA_SIZE]),
+        );
+
+        let chunks: Vec<_> = SectorReader::new(&drive, Lba::from(0), 100)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(drive.transport().submitted_cdbs().len(), 4);
+
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; 100 * CDDA_USER_DATA_SIZE]),
+        );
+
+        let chunks: Vec<_> = SectorReader::new(&drive, Lba::from(0), 100)
+            .with_prefetch(4)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].0.len(), 100 * CDDA_USER_DATA_SIZE);
+        assert_eq!(drive.transport().submitted_cdbs().len(), 1);
+    }
+
+    #[test]
+    fn stop_at_leadout_after_30_sectors() {
+        let sector = vec![0u8; CDDA_USER_DATA_SIZE];
+        let mut transport = MockTransport::new()
+            .with_queued_response(0xBE, sector.repeat(27))
+            // LOGICAL BLOCK ADDRESS OUT OF RANGE
+            .with_queued_sense(0xBE, 0x05, 0x21, 0x00);
+        for _ in 0..3 {
+            transport = transport.with_queued_response(0xBE, sector.clone());
+        }
+        let drive = Drive::with_transport(transport.with_queued_sense(0xBE, 0x05, 0x21, 0x00));
+
+        let mut reader = SectorReader::new(&drive, Lba::from(0), 100).stop_at_leadout(true);
+        let sectors: usize = reader
+            .by_ref()
+            .map(|chunk| chunk.unwrap().0.len() / CDDA_USER_DATA_SIZE)
+            .sum();
+
+        assert_eq!(sectors, 30);
+        assert_eq!(reader.position(), Lba::from(30));
+        assert!(reader.next().is_none());
+
+        let drive =
+            Drive::with_transport(MockTransport::new().with_queued_sense(0xBE, 0x05, 0x21, 0x00));
+        let mut reader = SectorReader::new(&drive, Lba::from(0), 100);
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn lsn_addressed_read_targets_lba() {
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; CDDA_USER_DATA_SIZE]),
+        );
+        let starting_lbas = || -> Vec<i32> {
+            drive
+                .transport()
+                .submitted_cdbs()
+                .iter()
+                .map(|cdb| i32::from_be_bytes([cdb[2], cdb[3], cdb[4], cdb[5]]))
+                .collect()
+        };
+
+        // Track 2 of a libcdio track table, starting at LSN 20348
+        let mut reader = SectorReader::from_lsn(&drive, Lsn::from(20_348), 1);
+        assert_eq!(reader.position(), Lba::from(20_348));
+
+        reader.next().unwrap().unwrap();
+        assert_eq!(reader.position(), Lba::from(20_349));
+        assert_eq!(starting_lbas(), [20_348]);
+
+        let audio = read_audio_range_lsn(&drive, Lsn::from(0), 1).unwrap();
+        assert_eq!(audio.len(), CDDA_USER_DATA_SIZE);
+        assert_eq!(starting_lbas(), [20_348, 0]);
+    }
+
+    #[test]
+    fn read_offset_correction() {
+        // Each sample is its own index, so the shift is visible in the first sample
+        let samples: Vec<u8> = (0u32..(27 * 588)).flat_map(u32::to_be_bytes).collect();
+        let drive = Drive::with_transport(MockTransport::new().with_response(0xBE, samples));
+
+        assert_eq!(
+            offset_read_range(Lba::from(100), 10, 667),
+            (Lba::from(101), 11, 316)
+        );
+        let audio = read_audio_range_with_offset(&drive, Lba::from(100), 10, 667).unwrap();
+        assert_eq!(audio.len(), 10 * CDDA_USER_DATA_SIZE);
+        assert_eq!(audio[..4], 79u32.to_be_bytes());
+
+        assert_eq!(
+            offset_read_range(Lba::from(100), 10, -582),
+            (Lba::from(99), 11, 24)
+        );
+        let audio = read_audio_range_with_offset(&drive, Lba::from(100), 10, -582).unwrap();
+        assert_eq!(audio.len(), 10 * CDDA_USER_DATA_SIZE);
+        assert_eq!(audio[..4], 6u32.to_be_bytes());
+
+        assert_eq!(
+            offset_read_range(Lba::from(100), 10, 0),
+            (Lba::from(100), 10, 0)
+        );
+    }
+
+    #[test]
+    fn offset_correction_rejects_short_reads() {
+        // Only 10 of the 11 sectors needed for the shift
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0; 10 * CDDA_USER_DATA_SIZE]),
+        );
+
+        assert!(matches!(
+            read_audio_range_with_offset(&drive, Lba::from(100), 10, 667),
+            Err(crate::Error::ShortRead {
+                expected: 23_836,
+                received: 23_520,
+            })
+        ));
+    }
+}
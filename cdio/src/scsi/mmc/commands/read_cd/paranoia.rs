@@ -0,0 +1,439 @@
+use std::cmp;
+
+use crate::{core::addressing::Lba, device::Drive, transport::Transport};
+
+use super::{MainChannelFlags, ReadCd, SectorType, CDDA_USER_DATA_SIZE, MAX_SECTORS_PER_READ};
+
+/// 16-bit stereo PCM, reads are only ever misaligned by whole samples.
+const SAMPLE_SIZE: usize = 4;
+
+/// Joins overlapping reads back into a continuous stream of audio.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stitcher {
+    /// The number of bytes each read overlaps the previous one.
+    overlap: usize,
+    /// The end of the stream emitted so far, used to find where the next read continues it.
+    tail: Vec<u8>,
+    consecutive_failures: u32,
+    max_alignment_failures: u32,
+}
+
+impl Stitcher {
+    fn new(overlap: usize, max_alignment_failures: u32) -> Self {
+        Self {
+            overlap,
+            tail: Vec::new(),
+            consecutive_failures: 0,
+            max_alignment_failures,
+        }
+    }
+
+    /// Returns the part of `chunk` that continues the stream, where the first `lead` bytes of
+    /// `chunk` were requested to overlap the end of the previous read.
+    ///
+    /// Returns `None` if `chunk` couldn't be aligned and should be read again, until that's
+    /// happened `max_alignment_failures` times in a row. Then the overlap of that chunk alone is
+    /// discarded without being matched, and the next chunk is aligned as usual.
+    fn stitch(&mut self, chunk: &[u8], lead: usize) -> Option<Vec<u8>> {
+        let lead = cmp::min(lead, chunk.len());
+
+        let start = if self.tail.is_empty() || lead == 0 {
+            0
+        } else if let Some(start) = self.align(chunk, lead) {
+            self.consecutive_failures = 0;
+            start
+        } else {
+            self.consecutive_failures += 1;
+            if self.consecutive_failures < self.max_alignment_failures {
+                return None;
+            }
+            self.consecutive_failures = 0;
+            lead
+        };
+
+        let emitted = chunk[start..].to_vec();
+
+        self.tail.extend_from_slice(&emitted);
+        let excess = self.tail.len().saturating_sub(self.overlap);
+        self.tail.drain(..excess);
+
+        Some(emitted)
+    }
+
+    /// Finds the end of the previous read within `chunk`, matching the last half of the overlap
+    /// so reads that start up to half an overlap early or late can still be aligned.
+    ///
+    /// The search starts where the end would be without any jitter and works outward. Returns
+    /// `None` if the anchor isn't found, or if it's found at more than one offset that continues
+    /// the stream differently (e.g. silence giving way to music), as picking either could repeat
+    /// or skip samples. Where every offset continues it with the same samples (e.g. throughout
+    /// silence), the one closest to no jitter is used.
+    fn align(&self, chunk: &[u8], lead: usize) -> Option<usize> {
+        let anchor_len = cmp::min(lead / 2 / SAMPLE_SIZE * SAMPLE_SIZE, self.tail.len());
+        if anchor_len == 0 {
+            return None;
+        }
+
+        let anchor = &self.tail[(self.tail.len() - anchor_len)..];
+        let last_start = cmp::min(chunk.len(), lead + lead / 2).checked_sub(anchor_len)?;
+        let expected = cmp::min(lead - anchor_len, last_start);
+
+        let outward = (0..=cmp::max(expected, last_start - expected))
+            .step_by(SAMPLE_SIZE)
+            .flat_map(|distance| {
+                let later = (distance > 0).then_some(expected + distance);
+                [expected.checked_sub(distance), later]
+            })
+            .flatten()
+            .filter(|&i| i <= last_start);
+
+        let mut matches = outward
+            .filter(|&i| &chunk[i..(i + anchor_len)] == anchor)
+            .map(|i| i + anchor_len);
+        let first = matches.next()?;
+
+        // Later offsets continue the stream with fewer samples, only those have to agree
+        let ambiguous = matches.any(|i| {
+            let len = chunk.len() - cmp::max(first, i);
+            chunk[first..][..len] != chunk[i..][..len]
+        });
+        (!ambiguous).then_some(first)
+    }
+}
+
+/// Reads CD-DA with each read overlapping the end of the previous one, then aligns the two on
+/// their common samples to correct for the Drive's inaccurate positioning (jitter).
+#[derive(Debug)]
+pub struct ParanoidReader<'a, T: Transport> {
+    drive: &'a Drive<T>,
+    position: Lba,
+    remaining: u32,
+    overlap: u32,
+    stitcher: Stitcher,
+}
+
+impl<'a, T: Transport> ParanoidReader<'a, T> {
+    pub const DEFAULT_OVERLAP: u32 = 5;
+
+    /// Reads `sectors` from `start`, overlapping consecutive reads by `overlap` sectors. A read
+    /// that can't be aligned unambiguously is read again. After `max_alignment_failures`
+    /// consecutive reads that can't be aligned, that read's overlap is discarded unchecked like a
+    /// naive read, and the reads after it are aligned again.
+    ///
+    /// Returns `None` if `overlap` doesn't leave room for new sectors in each read.
+    pub fn new(
+        drive: &'a Drive<T>,
+        start: Lba,
+        sectors: u32,
+        overlap: u32,
+        max_alignment_failures: u32,
+    ) -> Option<Self> {
+        if overlap >= MAX_SECTORS_PER_READ {
+            return None;
+        }
+
+        Some(Self {
+            drive,
+            position: start,
+            remaining: sectors,
+            overlap,
+            stitcher: Stitcher::new(
+                overlap as usize * CDDA_USER_DATA_SIZE,
+                max_alignment_failures,
+            ),
+        })
+    }
+}
+
+impl<T: Transport> Iterator for ParanoidReader<'_, T> {
+    type Item = Result<Vec<u8>, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let lead = if self.stitcher.tail.is_empty() {
+            0
+        } else {
+            self.overlap
+        };
+        let sectors_to_read = cmp::min(self.remaining, MAX_SECTORS_PER_READ - lead);
+
+        loop {
+            let data = match self.drive.execute(ReadCd {
+                sector_type: SectorType::AllTypes,
+                starting_lba: self.position - lead as i32,
+                transfer_length: lead + sectors_to_read,
+                main_channel: MainChannelFlags::USER_DATA,
+                ..Default::default()
+            }) {
+                Ok(data) => data,
+                Err(error) => return Some(Err(error)),
+            };
+
+            if let Some(emitted) = self
+                .stitcher
+                .stitch(&data, lead as usize * CDDA_USER_DATA_SIZE)
+            {
+                self.position += sectors_to_read as i32;
+                self.remaining -= sectors_to_read;
+                return Some(Ok(emitted));
+            }
+        }
+    }
+}
+
+/// Detects the Drive's read offset in samples by finding `reference`, the known audio starting
+/// at `start` on a pressed disc, in what the Drive actually returns around it. The result is
+/// what [`read_audio_range_with_offset`](super::read_audio_range_with_offset) expects, and is
+/// searched for within `max_offset` samples either way.
+///
+/// The surrounding sectors are read with a [`ParanoidReader`] so jitter doesn't skew the result.
+/// Returns `None` if `reference` isn't found, or is found more than once (e.g. silence), as the
+/// offset can't be told apart.
+pub fn detect_read_offset<T: Transport>(
+    drive: &Drive<T>,
+    start: Lba,
+    reference: &[u8],
+    max_offset: u32,
+) -> Result<Option<i32>, crate::Error> {
+    if reference.is_empty() {
+        return Ok(None);
+    }
+
+    let margin = (max_offset as usize * SAMPLE_SIZE).div_ceil(CDDA_USER_DATA_SIZE) as u32;
+    let reference_sectors = reference.len().div_ceil(CDDA_USER_DATA_SIZE) as u32;
+
+    // The default overlap always leaves room for new sectors
+    let Some(reader) = ParanoidReader::new(
+        drive,
+        start - margin as i32,
+        reference_sectors + 2 * margin,
+        ParanoidReader::<T>::DEFAULT_OVERLAP,
+        3,
+    ) else {
+        return Ok(None);
+    };
+
+    let mut data = Vec::new();
+    for chunk in reader {
+        data.extend_from_slice(&chunk?);
+    }
+
+    // Where the reference would be found if the Drive had no offset
+    let expected = (margin as usize * CDDA_USER_DATA_SIZE / SAMPLE_SIZE) as i64;
+
+    let mut offsets = (0..=data.len().saturating_sub(reference.len()))
+        .step_by(SAMPLE_SIZE)
+        .filter(|&i| data[i..].starts_with(reference))
+        .map(|i| (i / SAMPLE_SIZE) as i64 - expected)
+        .filter(|offset| offset.unsigned_abs() <= u64::from(max_offset));
+
+    match (offsets.next(), offsets.next()) {
+        (Some(offset), None) => Ok(i32::try_from(offset).ok()),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, time::Duration};
+
+    use crate::{
+        device::Drive,
+        transport::{
+            mock::MockTransport,
+            sgio::{DxferDirection, ScsiError},
+        },
+    };
+
+    use super::*;
+
+    /// Samples numbered from zero, so any misalignment shows up as a discontinuity.
+    fn stream(samples: std::ops::Range<u32>) -> Vec<u8> {
+        samples.flat_map(u32::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn stitch_jittered_chunks() {
+        // 10 sample overlap
+        let lead = 10 * SAMPLE_SIZE;
+        let mut stitcher = Stitcher::new(lead, 3);
+
+        let mut out = stitcher.stitch(&stream(0..100), 0).unwrap();
+        // Requested from sample 90, the Drive started 3 samples late
+        out.extend(stitcher.stitch(&stream(93..200), lead).unwrap());
+        // Requested from sample 190, the Drive started 4 samples early
+        out.extend(stitcher.stitch(&stream(186..300), lead).unwrap());
+
+        assert_eq!(out, stream(0..300));
+        assert_eq!(stitcher.consecutive_failures, 0);
+    }
+
+    #[test]
+    fn falls_back_to_a_naive_read() {
+        let lead = 10 * SAMPLE_SIZE;
+        let mut stitcher = Stitcher::new(lead, 2);
+
+        assert_eq!(stitcher.stitch(&stream(0..100), 0), Some(stream(0..100)));
+        // The first failure asks for the chunk to be read again
+        assert_eq!(stitcher.stitch(&stream(1000..1100), lead), None);
+        assert_eq!(stitcher.consecutive_failures, 1);
+
+        // The second gives up, discarding this chunk's overlap unchecked
+        assert_eq!(
+            stitcher.stitch(&stream(1000..1100), lead),
+            Some(stream(1010..1100))
+        );
+        assert_eq!(stitcher.consecutive_failures, 0);
+
+        // The next chunk is aligned again, requested from sample 1090 but started 3 samples late
+        assert_eq!(
+            stitcher.stitch(&stream(1093..1200), lead),
+            Some(stream(1100..1200))
+        );
+    }
+
+    #[test]
+    fn ambiguous_alignment_is_retried() {
+        let lead = 10 * SAMPLE_SIZE;
+        let mut stitcher = Stitcher::new(lead, 3);
+
+        // A waveform repeating every 2 samples matches at several offsets
+        let wave = |samples: usize| [1, 2, 3, 4, 5, 6, 7, 8].repeat(samples / 2);
+        stitcher.stitch(&wave(100), 0).unwrap();
+
+        // Which all continue the stream the same way while the waveform lasts
+        assert_eq!(stitcher.stitch(&wave(100), lead), Some(wave(90)));
+
+        // But not once it gives way to other audio
+        let chunk = [wave(20), stream(1000..1080)].concat();
+        assert_eq!(stitcher.stitch(&chunk, lead), None);
+        assert_eq!(stitcher.consecutive_failures, 1);
+    }
+
+    /// A disc of `audio`, from LBA 0, which returns the sectors each READ CD asks for starting
+    /// `late[n]` samples late for the `n`th read, or exactly where asked if there's no entry.
+    struct SimulatedDisc {
+        audio: Vec<u8>,
+        late: Vec<usize>,
+        reads: Cell<u32>,
+    }
+
+    impl Transport for SimulatedDisc {
+        fn submit(
+            &self,
+            cdb: &[u8],
+            _dir: DxferDirection,
+            buf: &mut [u8],
+            _timeout: Duration,
+        ) -> Result<usize, ScsiError> {
+            let late = self.late.get(self.reads.get() as usize).copied();
+            self.reads.set(self.reads.get() + 1);
+
+            let lba = i32::from_be_bytes([cdb[2], cdb[3], cdb[4], cdb[5]]) as usize;
+            let sectors = u32::from_be_bytes([0, cdb[6], cdb[7], cdb[8]]) as usize;
+            let start = lba * CDDA_USER_DATA_SIZE + late.unwrap_or(0) * SAMPLE_SIZE;
+            let data = &self.audio[start..][..(sectors * CDDA_USER_DATA_SIZE)];
+
+            buf[..data.len()].copy_from_slice(data);
+            Ok(data.len())
+        }
+    }
+
+    /// Reads 60 sectors from LBA 10 in reads of 27 sectors overlapping by 5, so the chunks start
+    /// at LBA 10, 32, and 54. Returns what was read, the audio from LBA 10 on of the same length,
+    /// and the number of reads.
+    fn read_paranoid(audio: Vec<u8>, late: Vec<usize>) -> (Vec<u8>, Vec<u8>, u32) {
+        let drive = Drive::with_transport(SimulatedDisc {
+            audio,
+            late,
+            reads: Cell::new(0),
+        });
+
+        let reader = ParanoidReader::new(&drive, Lba::from(10), 60, 5, 3).unwrap();
+        let out = reader.collect::<Result<Vec<_>, _>>().unwrap().concat();
+
+        let expected = drive.transport().audio[(10 * CDDA_USER_DATA_SIZE)..][..out.len()].to_vec();
+        (out, expected, drive.transport().reads.get())
+    }
+
+    #[test]
+    fn consecutive_reads_over_silence() {
+        let (out, expected, reads) = read_paranoid(vec![0; 100 * CDDA_USER_DATA_SIZE], vec![]);
+
+        // The anchor matches everywhere, but every match continues the stream the same way
+        assert_eq!(out.len(), 60 * CDDA_USER_DATA_SIZE);
+        assert_eq!(out, expected);
+        assert_eq!(reads, 3);
+
+        // Silence up to LBA 45, which the second chunk's overlap can't be aligned within
+        let mut audio = stream(0..(100 * 588));
+        audio[..(45 * CDDA_USER_DATA_SIZE)].fill(0);
+        // The third chunk is read 3 samples late
+        let (out, expected, reads) = read_paranoid(audio, vec![0, 0, 0, 0, 3]);
+
+        // The second chunk is read 3 times before giving up on aligning, then alignment resumes
+        // with the third, so the stream continues seamlessly despite it starting late
+        assert_eq!(reads, 1 + 3 + 1);
+        assert_eq!(out.len(), 60 * CDDA_USER_DATA_SIZE + 3 * SAMPLE_SIZE);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn consecutive_reads_over_periodic_audio() {
+        // Repeats every 7 samples, so never on a sector boundary
+        let audio: Vec<u8> = (0..(100 * CDDA_USER_DATA_SIZE / SAMPLE_SIZE))
+            .flat_map(|sample| (sample as u32 % 7).to_be_bytes())
+            .collect();
+
+        let (out, expected, _) = read_paranoid(audio, vec![]);
+
+        assert_eq!(out.len(), 60 * CDDA_USER_DATA_SIZE);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn consecutive_reads_over_distinct_audio() {
+        let (out, expected, reads) = read_paranoid(stream(0..(100 * 588)), vec![]);
+
+        assert_eq!(out, expected);
+        assert_eq!(reads, 3);
+    }
+
+    #[test]
+    fn detect_injected_offset() {
+        // A sector of distinct samples, surrounded by silence on the disc
+        let reference = stream(1..589);
+        let offset = 30;
+
+        // The Drive returns the sectors on either side too, shifted by the offset
+        let mut data = vec![0u8; 3 * CDDA_USER_DATA_SIZE];
+        let at = CDDA_USER_DATA_SIZE + offset * SAMPLE_SIZE;
+        data[at..(at + reference.len())].copy_from_slice(&reference);
+        let drive = Drive::with_transport(MockTransport::new().with_response(0xBE, data));
+
+        assert_eq!(
+            detect_read_offset(&drive, Lba::from(1000), &reference, 588).unwrap(),
+            Some(30)
+        );
+        // Out of the searched range
+        assert_eq!(
+            detect_read_offset(&drive, Lba::from(1000), &reference, 20).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn silence_is_ambiguous() {
+        let drive = Drive::with_transport(
+            MockTransport::new().with_response(0xBE, vec![0u8; 3 * CDDA_USER_DATA_SIZE]),
+        );
+
+        assert_eq!(
+            detect_read_offset(&drive, Lba::from(1000), &[0; CDDA_USER_DATA_SIZE], 588).unwrap(),
+            None
+        );
+    }
+}
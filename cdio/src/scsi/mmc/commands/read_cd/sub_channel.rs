@@ -0,0 +1,300 @@
+use std::cmp;
+
+use crate::{
+    core::{addressing::Lba, msf::Msf, util::bcd_to_u8},
+    device::Drive,
+    rainbow_books::q_subcode::{self, Control},
+    transport::Transport,
+};
+
+use super::{SectorReader, SectorType, SubChannelSelection};
+
+/// The formatted Q sub-channel READ CD appends to a sector when
+/// [`SubChannelSelection::QSubChannel`] is selected.
+///
+/// See MMC-6 §6.19.2.11, Table 466.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QSubChannel {
+    pub adr: u8,
+    pub control: Control,
+    /// Only present for Mode 1 (ADR 1) frames in the Program Area. The other modes, e.g. the
+    /// Media Catalog Number and ISRC, replace the position roughly once every 100 frames.
+    pub position: Option<QPosition>,
+}
+
+/// Where a Mode 1 Q sub-channel frame is on the disc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QPosition {
+    pub track_number: u8,
+    /// 0 within a track's pregap, 1 onwards within the track itself.
+    pub index: u8,
+    pub absolute: Msf,
+}
+
+impl QSubChannel {
+    /// Returns `None` unless `bytes` is the 16 bytes of a formatted Q sub-channel. A Mode 1 frame
+    /// whose CRC doesn't match, or whose position isn't valid BCD, is returned without a position
+    /// so a misread frame can't be mistaken for a track or index change.
+    ///
+    /// Many Drives return the CRC zeroed rather than as recorded, so a CRC of 0000h is taken to
+    /// be missing and isn't checked.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != SubChannelSelection::QSubChannel.size() {
+            return None;
+        }
+
+        // Unlike the TOC, the Q sub-channel is returned as recorded, Control first
+        let adr = bytes[0] & 0x0F;
+        let control = Control::from_bits_retain(bytes[0] >> 4);
+
+        let crc = u16::from_be_bytes([bytes[10], bytes[11]]);
+        let crc_valid = crc == 0 || crc == q_subcode::crc(&bytes[..10]);
+
+        let position = (adr == 1 && crc_valid).then(|| {
+            Some(QPosition {
+                track_number: bcd_to_u8(bytes[1]).ok()?,
+                index: bcd_to_u8(bytes[2]).ok()?,
+                absolute: Msf::from_bcd_bytes([bytes[7], bytes[8], bytes[9]]).ok()?,
+            })
+        });
+
+        Some(Self {
+            adr,
+            control,
+            position: position.flatten(),
+        })
+    }
+}
+
+/// A [`SectorReader`] that also reads the Q sub-channel of every sector, see
+/// [`SectorReader::with_q_sub_channel`].
+#[derive(Debug)]
+pub struct QSubChannelReader<'a, T: Transport>(SectorReader<'a, T>);
+
+impl<'a, T: Transport> SectorReader<'a, T> {
+    /// Also reads the Q sub-channel of every sector, yielding each chunk's main channel data
+    /// along with one [`QSubChannel`] per sector. Used to find track and index boundaries exactly
+    /// while extracting, rather than trusting the TOC.
+    pub fn with_q_sub_channel(mut self) -> QSubChannelReader<'a, T> {
+        self.command.sub_channel = SubChannelSelection::QSubChannel;
+        QSubChannelReader(self)
+    }
+}
+
+impl<T: Transport> Iterator for QSubChannelReader<'_, T> {
+    type Item = Result<(Vec<u8>, Vec<QSubChannel>, u32), crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let command = self.0.command;
+
+        Some(self.0.next()?.map(|(data, remaining)| {
+            let mut main_channel = Vec::with_capacity(data.len());
+            let mut sub_channel = Vec::with_capacity(data.len() / command.sector_size());
+
+            for sector in command.split_sectors(&data) {
+                main_channel.extend_from_slice(sector.main_channel);
+                // Always the right length, split from a whole sector
+                sub_channel.extend(QSubChannel::from_bytes(sector.sub_channel));
+            }
+
+            (main_channel, sub_channel, remaining)
+        }))
+    }
+}
+
+/// How far either side of a track's start in the TOC [`pregap_length`] searches, 5 seconds.
+const PREGAP_SEARCH_WINDOW: i32 = 5 * 75;
+
+/// The length in frames of audio track `track_number`'s pregap, i.e. from INDEX 00 to INDEX 01,
+/// found by reading the sectors within [`PREGAP_SEARCH_WINDOW`] of `track_start`, the track's
+/// start in the TOC, with READ CD and the formatted Q sub-channel. READ SUB-CHANNEL isn't used,
+/// as the position it reports isn't tied to a particular sector. Returns `None` if INDEX 01 isn't
+/// found there.
+///
+/// Frames that fail their CRC are skipped. Only the part of the pregap within the window is
+/// counted, plenty for the usual 2 seconds.
+pub fn pregap_length<T: Transport>(
+    drive: &Drive<T>,
+    track_number: u8,
+    track_start: Lba,
+) -> Result<Option<u32>, crate::Error> {
+    let start = cmp::max(track_start - PREGAP_SEARCH_WINDOW, Lba::from(0));
+    let end = track_start + PREGAP_SEARCH_WINDOW;
+    let sectors = (i32::from(end) - i32::from(start)).max(0) as u32;
+
+    let reader = SectorReader::new(drive, start, sectors)
+        .sector_type(SectorType::CdDa)
+        .stop_at_leadout(true)
+        .with_q_sub_channel();
+
+    let mut lba = start;
+    let mut index_0 = None;
+    for chunk in reader {
+        let (_, sub_channel, _) = chunk?;
+
+        for q in sub_channel {
+            match q.position {
+                Some(position) if position.track_number == track_number => {
+                    if position.index == 0 {
+                        index_0.get_or_insert(lba);
+                    } else {
+                        let pregap =
+                            index_0.map_or(0, |index_0| i32::from(lba) - i32::from(index_0));
+                        return Ok(Some(pregap as u32));
+                    }
+                }
+                _ => {}
+            }
+
+            lba += 1;
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::util::u8_to_bcd,
+        scsi::mmc::commands::read_cd::{CDDA_USER_DATA_SIZE, MAX_SECTORS_PER_READ},
+        transport::mock::MockTransport,
+    };
+
+    use super::*;
+
+    #[test]
+    fn read_with_q_sub_channel() {
+        #[rustfmt::skip]
+        let q_sub_channels: [[u8; 16]; 3] = [
+            // Track 2 pregap at 04:31:73
+            [0x01, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00, 0x04, 0x31, 0x73, 0x58, 0x1C, 0, 0, 0, 0],
+            // Track 2 index 1 at 04:31:74
+            [0x01, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x31, 0x74, 0xC5, 0x79, 0, 0, 0, 0],
+            // Media Catalog Number
+            [0x02, 0x12, 0x34, 0x56, 0x78, 0x90, 0x12, 0x30, 0x00, 0x75, 0xC5, 0xE3, 0, 0, 0, 0],
+        ];
+        let response: Vec<u8> = q_sub_channels
+            .iter()
+            .enumerate()
+            .flat_map(|(i, q)| [vec![i as u8; CDDA_USER_DATA_SIZE], q.to_vec()].concat())
+            .collect();
+
+        let drive = Drive::with_transport(MockTransport::new().with_response(0xBE, response));
+        let (audio, sub_channel, remaining) = SectorReader::new(&drive, Lba::from(20_348), 3)
+            .sector_type(SectorType::CdDa)
+            .with_q_sub_channel()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(drive.transport().submitted_cdbs()[0][10], 0b010);
+        assert_eq!(remaining, 0);
+        assert_eq!(audio.len(), 3 * CDDA_USER_DATA_SIZE);
+        assert!(audio[CDDA_USER_DATA_SIZE..2 * CDDA_USER_DATA_SIZE]
+            .iter()
+            .all(|&byte| byte == 1));
+
+        assert_eq!(sub_channel.len(), 3);
+        let pregap = sub_channel[0].position.unwrap();
+        assert_eq!((pregap.track_number, pregap.index), (2, 0));
+        assert_eq!(pregap.absolute.to_string(), "04:31:73");
+
+        let index_1 = sub_channel[1].position.unwrap();
+        assert_eq!(index_1.index, 1);
+        assert!(!sub_channel[1].control.contains(Control::IS_DATA));
+
+        assert_eq!(sub_channel[2].adr, 2);
+        assert_eq!(sub_channel[2].position, None);
+    }
+
+    /// A CD-DA sector of silence whose Q sub-channel puts it at `lba` in `track_number` and
+    /// `index`, with a CRC that matches only if `crc_valid`.
+    fn q_sector(lba: i32, track_number: u8, index: u8, crc_valid: bool) -> Vec<u8> {
+        let frames = lba + 150;
+        let bcd = |value: i32| u8_to_bcd(value as u8).unwrap();
+
+        let mut q = [0; 16];
+        q[..10].copy_from_slice(&[
+            0x01,
+            bcd(track_number.into()),
+            bcd(index.into()),
+            0,
+            0,
+            0,
+            0,
+            bcd(frames / 4500),
+            bcd(frames / 75 % 60),
+            bcd(frames % 75),
+        ]);
+        let crc = q_subcode::crc(&q[..10]) ^ if crc_valid { 0 } else { 0x0001 };
+        q[10..12].copy_from_slice(&crc.to_be_bytes());
+
+        [vec![0; CDDA_USER_DATA_SIZE], q.to_vec()].concat()
+    }
+
+    /// Serves the reads [`pregap_length`] makes around LBA 1000.
+    fn pregap_drive(sector: impl Fn(i32) -> Vec<u8>) -> Drive<MockTransport> {
+        let transport = (625..1375).step_by(MAX_SECTORS_PER_READ as usize).fold(
+            MockTransport::new(),
+            |transport, chunk| {
+                let end = cmp::min(chunk + MAX_SECTORS_PER_READ as i32, 1375);
+                transport
+                    .with_queued_response(0xBE, (chunk..end).flat_map(&sector).collect::<Vec<u8>>())
+            },
+        );
+        Drive::with_transport(transport)
+    }
+
+    /// Track 2 starts at LBA 1000 in the TOC, its pregap at 850.
+    fn track_2_sector(lba: i32) -> Vec<u8> {
+        match lba {
+            ..850 => q_sector(lba, 1, 1, true),
+            850..1000 => q_sector(lba, 2, 0, true),
+            _ => q_sector(lba, 2, 1, true),
+        }
+    }
+
+    #[test]
+    fn pregap_from_index_change() {
+        let drive = pregap_drive(track_2_sector);
+        assert_eq!(
+            pregap_length(&drive, 2, Lba::from(1000)).unwrap(),
+            Some(150)
+        );
+        // The search stops once INDEX 01 is found
+        assert_eq!(drive.transport().submitted_cdbs().len(), 14);
+
+        // Track 3 never appears
+        let drive = pregap_drive(track_2_sector);
+        assert_eq!(pregap_length(&drive, 3, Lba::from(1000)).unwrap(), None);
+    }
+
+    #[test]
+    fn corrupt_frame_doesnt_shift_pregap() {
+        // A misread frame just before the index change, claiming INDEX 01 starts a sector early
+        let drive = pregap_drive(|lba| match lba {
+            999 => q_sector(lba, 2, 1, false),
+            _ => track_2_sector(lba),
+        });
+
+        assert_eq!(
+            pregap_length(&drive, 2, Lba::from(1000)).unwrap(),
+            Some(150)
+        );
+    }
+
+    #[test]
+    fn zeroed_crc_isnt_checked() {
+        let drive = pregap_drive(|lba| {
+            let mut sector = track_2_sector(lba);
+            sector[(CDDA_USER_DATA_SIZE + 10)..(CDDA_USER_DATA_SIZE + 12)].fill(0);
+            sector
+        });
+
+        assert_eq!(
+            pregap_length(&drive, 2, Lba::from(1000)).unwrap(),
+            Some(150)
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use std::io::Cursor;
+
+use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+/// A 2-bit value representing the recording status of the disc.
+///
+/// See MMC-6 §6.22.3.1.3, Table 502.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 2)]
+#[repr(u8)]
+pub enum DiscStatus {
+    Empty = 0b00,
+    /// The disc is appendable.
+    Incomplete = 0b01,
+    /// The disc is finalized and can no longer be appended to.
+    Complete = 0b10,
+    /// Random access media which can't be defined in terms of sessions (e.g. DVD-RAM).
+    Other = 0b11,
+}
+
+/// A 2-bit value representing the recording status of the last session on the disc.
+///
+/// See MMC-6 §6.22.3.1.3, Table 501.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 2)]
+#[repr(u8)]
+pub enum SessionState {
+    Empty = 0b00,
+    Incomplete = 0b01,
+    ReservedOrDamaged = 0b10,
+    Complete = 0b11,
+}
+
+/// A 2-bit value representing the status of a background format operation.
+///
+/// See MMC-6 §6.22.3.1.7, Table 503.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 2)]
+#[repr(u8)]
+pub enum BackgroundFormatStatus {
+    NotFormatted = 0b00,
+    Incomplete = 0b01,
+    InProgress = 0b10,
+    Complete = 0b11,
+}
+
+/// The type of the disc, as recorded in the first session's Lead-in.
+///
+/// See MMC-6 §6.22.3.1.8, Table 504.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8")]
+pub enum DiscType {
+    #[deku(id = "0x00")]
+    CddaOrCdrom,
+    #[deku(id = "0x10")]
+    Cdi,
+    #[deku(id = "0x20")]
+    CdromXa,
+    #[deku(id = "0xFF")]
+    Undefined,
+    #[deku(id_pat = "_")]
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadDiscInformation {
+    allocation_length: u16,
+    control: Control,
+}
+
+impl ReadDiscInformation {
+    pub fn new(allocation_length: u16, control: Control) -> Self {
+        Self {
+            allocation_length,
+            control,
+        }
+    }
+}
+
+/// The Standard Disc Information block (Disc Information Data Type 000b).
+///
+/// See MMC-6 §6.22.3.1, Table 500.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiscInformation {
+    #[deku(temp, endian = "big")]
+    _disc_information_length: u16,
+
+    #[deku(temp, bits = 3, assert_eq = "0b000")]
+    _disc_information_data_type: u8,
+    #[deku(bits = 1)]
+    pub erasable: bool,
+    pub state_of_last_session: SessionState,
+    pub disc_status: DiscStatus,
+
+    pub first_track_number: u8,
+
+    #[deku(temp)]
+    _number_of_sessions_lsb: u8,
+    #[deku(temp)]
+    _first_track_number_last_session_lsb: u8,
+    #[deku(temp)]
+    _last_track_number_last_session_lsb: u8,
+
+    #[deku(temp, bits = 1)]
+    _did_v: bool,
+    #[deku(temp, bits = 1)]
+    _dbc_v: bool,
+    /// Unrestricted Use Disc, only meaningful for CD-R/RW media.
+    #[deku(bits = 1)]
+    pub unrestricted_use: bool,
+    #[deku(temp, bits = 1)]
+    _dac_v: bool,
+    #[deku(pad_bits_before = "1", bits = 1)]
+    pub legacy: bool,
+    pub background_format_status: BackgroundFormatStatus,
+
+    pub disc_type: DiscType,
+
+    #[deku(temp)]
+    _number_of_sessions_msb: u8,
+    #[deku(temp)]
+    _first_track_number_last_session_msb: u8,
+    #[deku(temp)]
+    _last_track_number_last_session_msb: u8,
+
+    #[deku(temp, endian = "big")]
+    _disc_identification: u32,
+    /// For CD, the MSF as `00h M S F`. `None` (FFFFFFFFh) once the disc is complete and there
+    /// is no further session to start.
+    #[deku(
+        endian = "big",
+        map = "|address: u32| -> Result<_, DekuError> { Ok((address != u32::MAX).then_some(address)) }"
+    )]
+    pub last_session_lead_in_start_address: Option<u32>,
+    /// For CD, the MSF as `00h M S F`. `None` (FFFFFFFFh) once the disc is complete.
+    #[deku(
+        endian = "big",
+        map = "|address: u32| -> Result<_, DekuError> { Ok((address != u32::MAX).then_some(address)) }"
+    )]
+    pub last_possible_lead_out_start_address: Option<u32>,
+    #[deku(temp)]
+    _disc_bar_code: [u8; 8],
+    #[deku(temp)]
+    _disc_application_code: u8,
+
+    #[deku(
+        skip,
+        default = "u16::from_be_bytes([*_number_of_sessions_msb, *_number_of_sessions_lsb])"
+    )]
+    pub number_of_sessions: u16,
+    #[deku(
+        skip,
+        default = "u16::from_be_bytes([*_first_track_number_last_session_msb, *_first_track_number_last_session_lsb])"
+    )]
+    pub first_track_number_last_session: u16,
+    #[deku(
+        skip,
+        default = "u16::from_be_bytes([*_last_track_number_last_session_msb, *_last_track_number_last_session_lsb])"
+    )]
+    pub last_track_number_last_session: u16,
+
+    /// The number recorded on CD-R/RW media identifying the disc. `None` unless the Drive
+    /// flags it valid.
+    #[deku(skip, default = "_did_v.then_some(*_disc_identification)")]
+    pub disc_identification: Option<u32>,
+    /// The bar code printed on the disc, as read by the Drive. `None` unless the Drive flags it
+    /// valid, most have no bar code reader.
+    #[deku(skip, default = "_dbc_v.then_some(*_disc_bar_code)")]
+    pub disc_bar_code: Option<[u8; 8]>,
+    /// The restricted use the disc was made for, see the Orange Book. `None` unless the Drive
+    /// flags it valid.
+    #[deku(skip, default = "_dac_v.then_some(*_disc_application_code)")]
+    pub disc_application_code: Option<u8>,
+}
+
+impl Response for DiscInformation {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+type ReadDiscInformationOpCode = OpCode<0x51>;
+
+impl Command<ReadDiscInformationOpCode> for ReadDiscInformation {
+    type Response = DiscInformation;
+
+    fn as_cdb(&self) -> <ReadDiscInformationOpCode as OpCodeDef>::Cdb {
+        [
+            ReadDiscInformationOpCode::OP_CODE,
+            0b000, // Standard Disc Information
+            0,
+            0,
+            0,
+            0,
+            0,
+            (self.allocation_length >> 8) as u8,
+            self.allocation_length as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        self.allocation_length.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    const APPENDABLE_CD_R: &[u8] = &[
+        0x00, 0x20, 0x05, 0x01, 0x01, 0x01, 0x01, 0xF0,
+        0x00, 0x00, 0x00, 0x00,
+        // Disc Identification
+        0x12, 0x34, 0x56, 0x78,
+        // Lead-in at 79:59:74, Lead-out at 79:59:74
+        0x00, 0x4F, 0x3B, 0x4A, 0x00, 0x4F, 0x3B, 0x4A,
+        // Disc Bar Code
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        // Disc Application Code, Number of OPC Tables
+        0x40, 0x00,
+    ];
+
+    #[test]
+    fn optional_fields_with_valid_bits() {
+        let disc_information = DiscInformation::from_bytes(APPENDABLE_CD_R).unwrap();
+
+        assert_eq!(disc_information.disc_status, DiscStatus::Incomplete);
+        assert!(disc_information.unrestricted_use);
+        assert_eq!(disc_information.disc_identification, Some(0x12345678));
+        assert_eq!(
+            disc_information.disc_bar_code,
+            Some([0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08])
+        );
+        assert_eq!(disc_information.disc_application_code, Some(0x40));
+        assert_eq!(
+            disc_information.last_session_lead_in_start_address,
+            Some(0x004F3B4A)
+        );
+        assert_eq!(
+            disc_information.last_possible_lead_out_start_address,
+            Some(0x004F3B4A)
+        );
+    }
+
+    #[test]
+    fn optional_fields_without_valid_bits() {
+        let mut response = APPENDABLE_CD_R.to_vec();
+        // Complete, with only URU set
+        response[2] = 0x0E;
+        response[7] = 0x20;
+        response[16..24].fill(0xFF);
+
+        let disc_information = DiscInformation::from_bytes(&response).unwrap();
+
+        assert!(disc_information.unrestricted_use);
+        assert_eq!(disc_information.disc_identification, None);
+        assert_eq!(disc_information.disc_bar_code, None);
+        assert_eq!(disc_information.disc_application_code, None);
+        assert_eq!(disc_information.last_session_lead_in_start_address, None);
+        assert_eq!(disc_information.last_possible_lead_out_start_address, None);
+    }
+
+    #[test]
+    fn allocation_length_matches_cdb() {
+        let read_disc_information = ReadDiscInformation::new(34, 0.into());
+
+        assert_eq!(
+            read_disc_information.as_cdb(),
+            [0x51, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x22, 0x00]
+        );
+        assert_eq!(read_disc_information.allocation_len(), 34);
+    }
+}
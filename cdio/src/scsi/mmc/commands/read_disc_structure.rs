@@ -0,0 +1,279 @@
+use std::io::Cursor;
+
+use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+/// The class of media the requested structure is defined for.
+///
+/// See MMC-6 §6.23.2.1, Table 364.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MediaType {
+    /// DVD and HD DVD structures.
+    Dvd = 0b0000,
+    Bd = 0b0001,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadDiscStructure {
+    media_type: MediaType,
+    address: u32,
+    layer_number: u8,
+    format_code: u8,
+    allocation_length: u16,
+    agid: u8,
+    control: Control,
+}
+
+impl ReadDiscStructure {
+    pub fn new(
+        media_type: MediaType,
+        address: u32,
+        layer_number: u8,
+        format_code: u8,
+        allocation_length: u16,
+        control: Control,
+    ) -> Self {
+        Self {
+            media_type,
+            address,
+            layer_number,
+            format_code,
+            allocation_length,
+            agid: 0,
+            control,
+        }
+    }
+
+    /// Shorthand for the DVD Physical Format Information (Format Code 00h) of `layer_number`.
+    pub fn physical_format_information(
+        layer_number: u8,
+        allocation_length: u16,
+        control: Control,
+    ) -> Self {
+        Self::new(
+            MediaType::Dvd,
+            0,
+            layer_number,
+            PhysicalFormatInformation::FORMAT_CODE,
+            allocation_length,
+            control,
+        )
+    }
+
+    /// Sets the Authentication Grant ID, only meaningful for the formats involved in CSS/CPRM
+    /// authentication.
+    pub fn agid(mut self, agid: u8) -> Self {
+        self.agid = agid & 0b11;
+        self
+    }
+}
+
+/// The generic READ DISC STRUCTURE response: a 4-byte header followed by format specific
+/// structure data.
+///
+/// See MMC-6 §6.23.3.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiscStructure {
+    #[deku(temp, endian = "big")]
+    _disc_structure_data_length: u16,
+
+    #[deku(
+        pad_bytes_before = "2",
+        bytes_read = "(*_disc_structure_data_length as usize).saturating_sub(2)"
+    )]
+    pub data: Vec<u8>,
+}
+
+impl DiscStructure {
+    /// Interprets the structure data as the response to Format Code 00h on DVD media.
+    pub fn physical_format_information(&self) -> Result<PhysicalFormatInformation, DekuError> {
+        PhysicalFormatInformation::from_reader_with_ctx(
+            &mut Reader::new(Cursor::new(&self.data)),
+            (),
+        )
+    }
+}
+
+impl Response for DiscStructure {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+/// The DVD Book type the disc conforms to.
+///
+/// See MMC-6 §6.23.3.2.1, Table 372.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 4)]
+pub enum BookType {
+    #[deku(id = "0b0000")]
+    DvdRom,
+    #[deku(id = "0b0001")]
+    DvdRam,
+    #[deku(id = "0b0010")]
+    DvdR,
+    #[deku(id = "0b0011")]
+    DvdRw,
+    #[deku(id = "0b1001")]
+    DvdPlusRw,
+    #[deku(id = "0b1010")]
+    DvdPlusR,
+    #[deku(id = "0b1101")]
+    DvdPlusRwDualLayer,
+    #[deku(id = "0b1110")]
+    DvdPlusRDualLayer,
+    #[deku(id_pat = "_")]
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 4)]
+pub enum DiscSize {
+    #[deku(id = "0b0000")]
+    Diameter120mm,
+    #[deku(id = "0b0001")]
+    Diameter80mm,
+    #[deku(id_pat = "_")]
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 1)]
+#[repr(u8)]
+pub enum TrackPath {
+    /// Parallel Track Path, each layer is read from the inner to the outer radius. Also used by
+    /// single layer discs.
+    Parallel = 0b0,
+    /// Opposite Track Path, layer 1 is read from the outer radius back towards the inner.
+    Opposite = 0b1,
+}
+
+/// DVD Physical Format Information (Format Code 00h), as recorded in the Control Data Zone.
+///
+/// See MMC-6 §6.23.3.2.1, Table 371.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicalFormatInformation {
+    pub book_type: BookType,
+    #[deku(bits = 4)]
+    pub part_version: u8,
+
+    pub disc_size: DiscSize,
+    /// Maximum read rate, see MMC-6 Table 374. `0b1111` means "not specified".
+    #[deku(bits = 4)]
+    pub maximum_rate: u8,
+
+    #[deku(temp, pad_bits_before = "1", bits = 2)]
+    _number_of_layers: u8,
+    #[deku(skip, default = "*_number_of_layers + 1")]
+    pub number_of_layers: u8,
+    pub track_path: TrackPath,
+    #[deku(pad_bits_before = "1", bits = 1)]
+    pub rewritable_layer: bool,
+    #[deku(bits = 1)]
+    pub recordable_layer: bool,
+    #[deku(bits = 1)]
+    pub embossed_layer: bool,
+
+    #[deku(bits = 4)]
+    pub linear_density: u8,
+    #[deku(bits = 4)]
+    pub track_density: u8,
+
+    #[deku(pad_bytes_before = "1", bytes = "3", endian = "big")]
+    pub starting_physical_sector: u32,
+    #[deku(pad_bytes_before = "1", bytes = "3", endian = "big")]
+    pub end_physical_sector: u32,
+    /// Only meaningful for Opposite Track Path discs.
+    #[deku(pad_bytes_before = "1", bytes = "3", endian = "big")]
+    pub end_physical_sector_in_layer_0: u32,
+
+    /// Whether the Burst Cutting Area is present.
+    #[deku(bits = 1, pad_bits_after = "7")]
+    pub bca: bool,
+}
+
+impl PhysicalFormatInformation {
+    pub const FORMAT_CODE: u8 = 0x00;
+}
+
+type ReadDiscStructureOpCode = OpCode<0xAD>;
+
+impl Command<ReadDiscStructureOpCode> for ReadDiscStructure {
+    type Response = DiscStructure;
+
+    fn as_cdb(&self) -> <ReadDiscStructureOpCode as OpCodeDef>::Cdb {
+        [
+            ReadDiscStructureOpCode::OP_CODE,
+            (self.media_type as u8) & 0b1111,
+            (self.address >> 24) as u8,
+            (self.address >> 16) as u8,
+            (self.address >> 8) as u8,
+            self.address as u8,
+            self.layer_number,
+            self.format_code,
+            (self.allocation_length >> 8) as u8,
+            self.allocation_length as u8,
+            self.agid << 6,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        self.allocation_length.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dvd_rom_physical_format_information() {
+        // Single layer DVD-ROM
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x08, 0x02, 0x00, 0x00,
+            0x01, 0x0F, 0x01, 0x00,
+            0x00, 0x03, 0x00, 0x00,
+            0x00, 0x24, 0x1F, 0xFF,
+            0x00, 0x00, 0x00, 0x00,
+            0x00,
+        ];
+
+        let disc_structure =
+            DiscStructure::from_bytes(&[response, &[0u8; 2048 - 17]].concat()).unwrap();
+        assert_eq!(disc_structure.data.len(), 2048);
+
+        let pfi = disc_structure.physical_format_information().unwrap();
+
+        assert_eq!(pfi.book_type, BookType::DvdRom);
+        assert_eq!(pfi.part_version, 1);
+        assert_eq!(pfi.disc_size, DiscSize::Diameter120mm);
+        assert_eq!(pfi.maximum_rate, 0x0F);
+        assert_eq!(pfi.number_of_layers, 1);
+        assert_eq!(pfi.track_path, TrackPath::Parallel);
+        assert!(pfi.embossed_layer);
+        assert!(!pfi.recordable_layer);
+        assert!(!pfi.rewritable_layer);
+        assert_eq!(pfi.starting_physical_sector, 0x030000);
+        assert_eq!(pfi.end_physical_sector, 0x241FFF);
+        assert!(!pfi.bca);
+    }
+
+    #[test]
+    fn read_disc_structure_cdb() {
+        let read_disc_structure = ReadDiscStructure::physical_format_information(1, 2052, 0.into());
+
+        assert_eq!(
+            read_disc_structure.as_cdb(),
+            [0xAD, 0x00, 0, 0, 0, 0, 0x01, 0x00, 0x08, 0x04, 0x00, 0x00]
+        );
+        assert_eq!(read_disc_structure.allocation_len(), 2052);
+    }
+}
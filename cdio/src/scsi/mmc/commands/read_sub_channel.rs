@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+use crate::device::Drive;
+use crate::rainbow_books::{identifiers, identifiers::Mcn, q_subcode};
+use crate::scsi::mmc::commands::read_toc_pma_atip::formatted_toc::{
+    FormattedToc, TrackStartAddress,
+};
+use crate::transport::{sgio::ScsiError, Transport};
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+mod private {
+    pub trait SubChannelFormat {
+        const FORMAT_CODE: u8;
+    }
+}
+
+/// Only the Media Catalog Number and ISRC formats are supported for now; the CD Current Position
+/// is better read from the Q sub-channel returned by READ CD.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadSubChannel<R: ReadSubChannelResponse> {
+    _response_marker: PhantomData<R>,
+    track_number: u8,
+    control: Control,
+}
+
+pub trait ReadSubChannelResponse: private::SubChannelFormat + Response {}
+
+impl private::SubChannelFormat for MediaCatalogNumber {
+    const FORMAT_CODE: u8 = 0x02;
+}
+impl ReadSubChannelResponse for MediaCatalogNumber {}
+
+impl private::SubChannelFormat for Isrc {
+    const FORMAT_CODE: u8 = 0x03;
+}
+impl ReadSubChannelResponse for Isrc {}
+
+impl ReadSubChannel<MediaCatalogNumber> {
+    pub fn new(control: Control) -> Self {
+        Self {
+            _response_marker: PhantomData,
+            track_number: 0,
+            control,
+        }
+    }
+}
+
+impl ReadSubChannel<Isrc> {
+    /// The Drive seeks into `track_number` to find a Q sub-channel frame carrying its ISRC.
+    pub fn new(track_number: u8, control: Control) -> Self {
+        Self {
+            _response_marker: PhantomData,
+            track_number,
+            control,
+        }
+    }
+}
+
+/// The state of audio play operations, reported in the Sub-channel Data Header.
+///
+/// See MMC-6 §6.30.2.1, Table 560.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8")]
+pub enum AudioStatus {
+    #[deku(id = "0x00")]
+    NotSupported,
+    #[deku(id = "0x11")]
+    PlayInProgress,
+    #[deku(id = "0x12")]
+    Paused,
+    #[deku(id = "0x13")]
+    Completed,
+    #[deku(id = "0x14")]
+    StoppedByError,
+    #[deku(id = "0x15")]
+    NoStatus,
+    #[deku(id_pat = "_")]
+    Reserved(u8),
+}
+
+/// The disc's Media Catalog Number, the UPC/EAN barcode of the release (Sub-channel Data Format
+/// 02h).
+///
+/// See MMC-6 §6.30.3.2, Table 564.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MediaCatalogNumber {
+    #[deku(pad_bytes_before = "1")]
+    pub audio_status: AudioStatus,
+    #[deku(temp, endian = "big", assert = "*_sub_channel_data_length >= 20")]
+    _sub_channel_data_length: u16,
+
+    #[deku(temp, assert_eq = "0x02")]
+    _format_code: u8,
+
+    /// Whether the Media Catalog Number was found.
+    #[deku(pad_bytes_before = "3", bits = 1, pad_bits_after = "7")]
+    pub mcval: bool,
+    #[deku(pad_bytes_after = "2")]
+    pub media_catalog_number: [u8; 13],
+}
+
+impl MediaCatalogNumber {
+    /// The Media Catalog Number, if the Drive found a valid one.
+    pub fn mcn(&self) -> Option<Mcn> {
+        parse_valid(self.mcval, &self.media_catalog_number)
+    }
+}
+
+impl Response for MediaCatalogNumber {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+/// A track's International Standard Recording Code (Sub-channel Data Format 03h).
+///
+/// See MMC-6 §6.30.3.3, Table 565.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Isrc {
+    #[deku(pad_bytes_before = "1")]
+    pub audio_status: AudioStatus,
+    #[deku(temp, endian = "big", assert = "*_sub_channel_data_length >= 20")]
+    _sub_channel_data_length: u16,
+
+    #[deku(temp, assert_eq = "0x03")]
+    _format_code: u8,
+
+    #[deku(bits = 4)]
+    pub adr: u8,
+    pub control: q_subcode::Control,
+    #[deku(pad_bytes_after = "1")]
+    pub track_number: u8,
+
+    /// Whether the ISRC was found.
+    #[deku(bits = 1, pad_bits_after = "7")]
+    pub tcval: bool,
+    #[deku(pad_bytes_after = "3")]
+    pub isrc: [u8; 12],
+}
+
+impl Isrc {
+    /// The track's ISRC, if the Drive found a valid one.
+    pub fn code(&self) -> Option<identifiers::Isrc> {
+        parse_valid(self.tcval, &self.isrc)
+    }
+}
+
+impl Response for Isrc {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+/// Some Drives set the valid bit with an all zero or otherwise garbled code, so the code is
+/// validated as well.
+fn parse_valid<T: FromStr>(valid: bool, bytes: &[u8]) -> Option<T> {
+    if !valid {
+        return None;
+    }
+
+    std::str::from_utf8(bytes).ok()?.parse().ok()
+}
+
+/// The identifiers used for tagging a rip, see [`collect_identifiers`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DiscIdentifiers {
+    pub mcn: Option<Mcn>,
+    /// The ISRC of every audio track by track number, `None` if the track has none.
+    pub isrcs: BTreeMap<u8, Option<identifiers::Isrc>>,
+}
+
+/// Issues READ SUB-CHANNEL for the disc's Media Catalog Number, then for the ISRC of each audio
+/// track in `toc`. Data tracks have no ISRC and are skipped.
+///
+/// Some Drives answer ISRC requests with CHECK CONDITION rather than a cleared valid bit, e.g.
+/// for tracks they can't find a Mode 3 Q sub-channel frame in, so those tracks are `None` as
+/// well. Any other error is returned.
+pub fn collect_identifiers<T: Transport, A: TrackStartAddress>(
+    drive: &Drive<T>,
+    toc: &FormattedToc<A>,
+) -> Result<DiscIdentifiers, crate::Error> {
+    let mcn = drive.execute(ReadSubChannel::<MediaCatalogNumber>::new(0.into()))?;
+
+    let mut isrcs = BTreeMap::new();
+    for track in toc
+        .tracks()
+        .filter(|track| !track.control.contains(q_subcode::Control::IS_DATA))
+    {
+        let isrc = match drive.execute(ReadSubChannel::<Isrc>::new(track.track_number, 0.into())) {
+            Ok(isrc) => isrc.code(),
+            Err(crate::Error::Scsi(
+                ScsiError::MMCError(_) | ScsiError::UnknownSenseData { .. },
+            )) => None,
+            Err(error) => return Err(error),
+        };
+
+        isrcs.insert(track.track_number, isrc);
+    }
+
+    Ok(DiscIdentifiers {
+        mcn: mcn.mcn(),
+        isrcs,
+    })
+}
+
+/// The length of both the Media Catalog Number and ISRC responses, header included.
+const SUB_CHANNEL_LENGTH: u16 = 24;
+
+type ReadSubChannelOpCode = OpCode<0x42>;
+
+impl<R: ReadSubChannelResponse> Command<ReadSubChannelOpCode> for ReadSubChannel<R> {
+    type Response = R;
+
+    fn as_cdb(&self) -> <ReadSubChannelOpCode as OpCodeDef>::Cdb {
+        [
+            ReadSubChannelOpCode::OP_CODE,
+            0,      // MSF: neither format returns an address
+            1 << 6, // SubQ: return the Sub-channel Data, not just the header
+            R::FORMAT_CODE,
+            0,
+            0,
+            self.track_number,
+            (SUB_CHANNEL_LENGTH >> 8) as u8,
+            SUB_CHANNEL_LENGTH as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        SUB_CHANNEL_LENGTH.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::addressing::Lba;
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    fn isrc_response(track_number: u8, isrc: &[u8; 12]) -> Vec<u8> {
+        [
+            &[0x00, 0x15, 0x00, 0x14, 0x03, 0x10, track_number, 0x00, 0x80][..],
+            isrc,
+            &[0x00, 0x00, 0x00],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn parse_isrc() {
+        let isrc = Isrc::from_bytes(&isrc_response(2, b"USRC17607839")).unwrap();
+
+        assert_eq!(isrc.audio_status, AudioStatus::NoStatus);
+        assert_eq!(isrc.adr, 1);
+        assert_eq!(isrc.track_number, 2);
+        assert_eq!(isrc.code(), Some("USRC17607839".parse().unwrap()));
+
+        // Valid bit set, but garbled by the Drive
+        let isrc = Isrc::from_bytes(&isrc_response(2, b"USRC1760783\xFF")).unwrap();
+        assert!(isrc.tcval);
+        assert_eq!(isrc.code(), None);
+    }
+
+    #[test]
+    fn mcn_not_found() {
+        let mut response = [0u8; 24];
+        response[..5].copy_from_slice(&[0x00, 0x15, 0x00, 0x14, 0x02]);
+
+        let mcn = MediaCatalogNumber::from_bytes(&response).unwrap();
+
+        assert!(!mcn.mcval);
+        assert_eq!(mcn.mcn(), None);
+    }
+
+    #[test]
+    fn read_sub_channel_isrc_cdb() {
+        assert_eq!(
+            ReadSubChannel::<Isrc>::new(3, 0.into()).as_cdb(),
+            [0x42, 0x00, 0x40, 0x03, 0x00, 0x00, 0x03, 0x00, 0x18, 0x00]
+        );
+    }
+
+    #[test]
+    fn collect_isrcs_for_two_of_three_tracks() {
+        #[rustfmt::skip]
+        let toc: &[u8] = &[
+            0x00, 0x2A, 0x01, 0x04,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x00, 0x10, 0x00,
+            0x00, 0x10, 0x03, 0x00, 0x00, 0x00, 0x20, 0x00,
+            0x00, 0x14, 0x04, 0x00, 0x00, 0x00, 0x30, 0x00,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x40, 0x00,
+        ];
+        let toc = FormattedToc::<Lba>::from_bytes(toc).unwrap();
+
+        let mcn = [
+            &[0x00, 0x15, 0x00, 0x14, 0x02, 0x00, 0x00, 0x00, 0x80][..],
+            b"0724384960650",
+            &[0x00, 0x00],
+        ]
+        .concat();
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_queued_response(0x42, mcn)
+                .with_queued_response(0x42, isrc_response(1, b"GBAYE0601498"))
+                // NO SEEK COMPLETE
+                .with_queued_sense(0x42, 0x03, 0x02, 0x00)
+                .with_queued_response(0x42, isrc_response(3, b"GBAYE0601499")),
+        );
+
+        let identifiers = collect_identifiers(&drive, &toc).unwrap();
+
+        assert_eq!(
+            identifiers.mcn.map(|mcn| mcn.to_string()).as_deref(),
+            Some("0724384960650")
+        );
+        assert_eq!(
+            identifiers.isrcs,
+            BTreeMap::from([
+                (1, Some("GBAYE0601498".parse().unwrap())),
+                (2, None),
+                (3, Some("GBAYE0601499".parse().unwrap())),
+            ])
+        );
+
+        // The data track is never asked for an ISRC
+        let track_numbers: Vec<u8> = drive.transport().submitted_cdbs()[1..]
+            .iter()
+            .map(|cdb| cdb[6])
+            .collect();
+        assert_eq!(track_numbers, [1, 2, 3]);
+    }
+}
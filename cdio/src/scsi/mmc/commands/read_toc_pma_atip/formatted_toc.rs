@@ -1,9 +1,12 @@
-use std::io::{Cursor, Seek, SeekFrom};
+use std::{
+    io::{Cursor, Seek, SeekFrom},
+    ops::Range,
+};
 
-use crate::core::msf::Msf;
+use crate::core::{constants::LEADOUT_TRACK_NUM, msf::Msf};
 use deku::{ctx::Endian, deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
 
-use super::AddressingMode;
+use super::{multi_session_info::MultiSessionInformation, AddressingMode};
 use crate::rainbow_books::q_subcode;
 use crate::{core::addressing::Lba, scsi::mmc::commands::Response};
 
@@ -57,6 +60,171 @@ impl<A: TrackStartAddress> Response for FormattedToc<A> {
     }
 }
 
+impl<A: TrackStartAddress> FormattedToc<A> {
+    /// The descriptors of the actual tracks, excluding the Lead-out.
+    pub fn tracks(&self) -> impl Iterator<Item = &TocTrackDescriptor<A>> + Clone {
+        self.toc_track_descriptors
+            .iter()
+            .filter(|descriptor| !descriptor.is_leadout())
+    }
+
+    /// The Lead-out's descriptor, whose start address is the end of the last track, and so the
+    /// final boundary when computing track lengths.
+    pub fn leadout(&self) -> Option<&TocTrackDescriptor<A>> {
+        self.toc_track_descriptors
+            .iter()
+            .find(|descriptor| descriptor.is_leadout())
+    }
+
+    /// Every track's number and length in sectors, from its start to the start of the next
+    /// track, or to the start of the Lead-out for the last track. The last track is omitted if
+    /// the TOC has no Lead-out.
+    ///
+    /// A length includes the pregap of the track after it, see [`FormattedToc::gaps`].
+    pub fn track_lengths(&self) -> Vec<(u8, u32)> {
+        let ends = self.tracks().skip(1).map(Some).chain([self.leadout()]);
+
+        self.tracks()
+            .zip(ends)
+            .filter_map(|(track, end)| {
+                let length = i32::from(end?.track_start_address.to_lba())
+                    - i32::from(track.track_start_address.to_lba());
+
+                // Out of order descriptors from a malformed TOC
+                Some((track.track_number, length.max(0) as u32))
+            })
+            .collect()
+    }
+
+    /// Yields the minimum gaps the Red Book and Yellow Book require wherever the track type
+    /// changes between audio and data: 2 seconds before the second track's start, a pre-gap or
+    /// post-gap belonging to neither track's content, which a ripper would otherwise append to
+    /// the previous track, see [`FormattedToc::track_lengths`].
+    ///
+    /// The TOC records no gap lengths, so these are assumed rather than measured, and the actual
+    /// gap may be longer. The pregaps (index 0) between audio tracks aren't yielded at all. Use
+    /// [`pregap_length`](crate::scsi::mmc::commands::read_cd::sub_channel::pregap_length) to find
+    /// where index 0 actually begins.
+    pub fn gaps(&self) -> impl Iterator<Item = TrackGap> + '_ {
+        let is_data =
+            |track: &TocTrackDescriptor<A>| track.control.contains(q_subcode::Control::IS_DATA);
+        let tracks = self.tracks();
+
+        tracks
+            .clone()
+            .zip(tracks.skip(1))
+            .filter(move |(previous, track)| is_data(previous) != is_data(track))
+            .filter_map(|(previous, track)| {
+                let start = track.track_start_address.to_lba();
+                // Never more than the previous track, should the TOC be malformed
+                let length = (i32::from(start) - i32::from(previous.track_start_address.to_lba()))
+                    .clamp(0, TRACK_TYPE_GAP);
+
+                (length > 0).then_some(TrackGap {
+                    track_number: track.track_number,
+                    control: track.control,
+                    start: start - length,
+                    length: length as u32,
+                })
+            })
+    }
+
+    /// The program's running time, from the start of track 1 to the start of the Lead-out.
+    /// Returns `None` if either descriptor is missing.
+    pub fn total_length(&self) -> Option<Msf> {
+        let track_one = self
+            .tracks()
+            .find(|descriptor| descriptor.track_number == 1)?;
+        let leadout = self.leadout()?;
+
+        let frames = i32::from(leadout.track_start_address.to_lba())
+            - i32::from(track_one.track_start_address.to_lba());
+
+        Msf::from_frames(u32::try_from(frames).ok()?)
+    }
+
+    /// The sectors of Hidden Track One Audio (HTOA), if track 1 is an audio track whose index 0
+    /// extends past the mandatory 150 frame pregap, i.e. track 1 starts after LBA 0.
+    ///
+    /// Index 0 of track 1 always begins at 00:00:00, so the TOC's start of track 1 (index 1) is
+    /// enough to find where the hidden audio ends.
+    pub fn hidden_track_one_audio(&self) -> Option<Range<Lba>> {
+        let track_one = self
+            .toc_track_descriptors
+            .iter()
+            .find(|descriptor| descriptor.track_number == 1)?;
+
+        if track_one.control.contains(q_subcode::Control::IS_DATA) {
+            return None;
+        }
+
+        let start = Lba::from(0);
+        let end = track_one.track_start_address.to_lba();
+
+        (end > start).then_some(start..end)
+    }
+
+    /// Splits the tracks of an Enhanced CD (CD-Extra, Blue Book) into the audio tracks and the
+    /// data session after them, using the first track of the last session from `session_info`.
+    ///
+    /// The data session is only split off when it follows at least one other session and all of
+    /// its tracks are data tracks, otherwise every track is returned in `audio`. Note the
+    /// Lead-out in the TOC belongs to the last session, so it isn't the end of the audio.
+    pub fn split_data_session<'a>(
+        &'a self,
+        session_info: &MultiSessionInformation<A>,
+    ) -> EnhancedCdTracks<'a, A> {
+        let last_session_start = session_info
+            .toc_track_descriptors
+            .first()
+            .filter(|_| session_info.last_complete_session_number > 1)
+            .map(|descriptor| descriptor.first_track_number_last_complete_session);
+
+        let (data_session, audio): (Vec<_>, Vec<_>) = self.tracks().partition(|track| {
+            last_session_start.is_some_and(|first_track| track.track_number >= first_track)
+        });
+
+        if data_session
+            .iter()
+            .all(|track| track.control.contains(q_subcode::Control::IS_DATA))
+        {
+            EnhancedCdTracks {
+                audio,
+                data_session,
+            }
+        } else {
+            EnhancedCdTracks {
+                audio: self.tracks().collect(),
+                data_session: Vec::new(),
+            }
+        }
+    }
+}
+
+/// The minimum gap between an audio and a data track, 2 seconds. It's the data track's pre-gap
+/// when the data track comes second, or its post-gap when it comes first.
+const TRACK_TYPE_GAP: i32 = 2 * 75;
+
+/// The minimum number of sectors before a track's declared start that belong to neither it nor
+/// the previous track, see [`FormattedToc::gaps`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackGap {
+    /// The track the gap precedes.
+    pub track_number: u8,
+    /// The control flags of the track the gap precedes, e.g. whether it is pre-emphasized.
+    pub control: q_subcode::Control,
+    pub start: Lba,
+    pub length: u32,
+}
+
+/// The tracks of a TOC split by [`FormattedToc::split_data_session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnhancedCdTracks<'a, A: TrackStartAddress> {
+    pub audio: Vec<&'a TocTrackDescriptor<A>>,
+    /// Empty unless the disc has a trailing data session.
+    pub data_session: Vec<&'a TocTrackDescriptor<A>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, DekuRead)]
 pub struct TocTrackDescriptor<A: TrackStartAddress> {
     #[deku(pad_bytes_before = "1", bits = 4)]
@@ -69,3 +237,215 @@ pub struct TocTrackDescriptor<A: TrackStartAddress> {
     #[deku(bytes = 4, reader = "A::read_track_start_address(deku::reader)")]
     pub track_start_address: A,
 }
+
+impl<A: TrackStartAddress> TocTrackDescriptor<A> {
+    pub fn is_leadout(&self) -> bool {
+        self.track_number == LEADOUT_TRACK_NUM
+    }
+
+    pub fn track_type(&self) -> q_subcode::TrackType {
+        self.control.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swapped_track_numbers_do_not_panic() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x12, 0x0A, 0x01,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x10, 0x00,
+        ];
+
+        let toc = FormattedToc::<Lba>::from_bytes(response).unwrap();
+
+        assert_eq!(toc.first_track_number, 10);
+        assert_eq!(toc.last_track_number, 1);
+        assert_eq!(toc.toc_track_descriptors.len(), 2);
+        assert_eq!(toc.toc_track_descriptors[1].track_number, 0xAA);
+        assert_eq!(
+            toc.toc_track_descriptors[1].track_start_address,
+            Lba::from(0x1000)
+        );
+
+        // A TOC Data Length too short to even cover the track numbers
+        let toc = FormattedToc::<Lba>::from_bytes(&[0x00, 0x01, 0x0A, 0x01]).unwrap();
+        assert!(toc.toc_track_descriptors.is_empty());
+    }
+
+    #[test]
+    fn tracks_exclude_leadout() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x1A, 0x01, 0x02,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x00, 0x10, 0x00,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x20, 0x00,
+        ];
+
+        let toc = FormattedToc::<Lba>::from_bytes(response).unwrap();
+
+        let track_numbers: Vec<u8> = toc.tracks().map(|track| track.track_number).collect();
+        assert_eq!(track_numbers, [1, 2]);
+
+        let leadout = toc.leadout().unwrap();
+        assert!(leadout.is_leadout());
+        assert_eq!(leadout.track_start_address, Lba::from(0x2000));
+    }
+
+    #[test]
+    fn track_lengths_include_last_track() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x22, 0x01, 0x03,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x00, 0x10, 0x00,
+            0x00, 0x10, 0x03, 0x00, 0x00, 0x00, 0x18, 0x00,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x30, 0x00,
+        ];
+
+        let toc = FormattedToc::<Lba>::from_bytes(response).unwrap();
+
+        assert_eq!(toc.track_lengths(), [(1, 0x1000), (2, 0x0800), (3, 0x1800)]);
+
+        // Without the Lead-out there's nothing to end the last track
+        let mut toc = toc;
+        toc.toc_track_descriptors.pop();
+        assert_eq!(toc.track_lengths(), [(1, 0x1000), (2, 0x0800)]);
+    }
+
+    #[test]
+    fn total_length() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x22, 0x01, 0x03,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x04, 0x10, 0x20,
+            0x00, 0x10, 0x03, 0x00, 0x00, 0x09, 0x03, 0x05,
+            // Lead-out at 42:17:33
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x2A, 0x11, 0x21,
+        ];
+
+        let toc = FormattedToc::<Msf>::from_bytes(response).unwrap();
+
+        assert_eq!(toc.total_length().unwrap().to_string(), "42:15:33");
+    }
+
+    #[test]
+    fn split_enhanced_cd_data_session() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x22, 0x01, 0x03,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x00, 0x40, 0x00,
+            // The data track, alone in session 2
+            0x00, 0x14, 0x03, 0x00, 0x00, 0x00, 0xAC, 0x88,
+            0x00, 0x14, 0xAA, 0x00, 0x00, 0x00, 0xC0, 0x00,
+        ];
+        let toc = FormattedToc::<Lba>::from_bytes(response).unwrap();
+
+        let session_info = MultiSessionInformation::<Lba>::from_bytes(&[
+            0x00, 0x0A, 0x01, 0x02, 0x00, 0x14, 0x03, 0x00, 0x00, 0x00, 0xAC, 0x88,
+        ])
+        .unwrap();
+        let tracks = toc.split_data_session(&session_info);
+
+        let audio: Vec<u8> = tracks
+            .audio
+            .iter()
+            .map(|track| track.track_number)
+            .collect();
+        assert_eq!(audio, [1, 2]);
+        assert_eq!(tracks.data_session.len(), 1);
+        assert_eq!(tracks.data_session[0].track_number, 3);
+
+        // A single session disc has no data session to split off
+        let session_info = MultiSessionInformation::<Lba>::from_bytes(&[
+            0x00, 0x0A, 0x01, 0x01, 0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ])
+        .unwrap();
+        let tracks = toc.split_data_session(&session_info);
+
+        assert_eq!(tracks.audio.len(), 3);
+        assert!(tracks.data_session.is_empty());
+    }
+
+    #[test]
+    fn hidden_track_one_audio() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x1A, 0x01, 0x02,
+            // Track 1 starts at 00:32:00, 30 seconds after the pregap
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x20, 0x00,
+            0x00, 0x10, 0x02, 0x00, 0x00, 0x03, 0x20, 0x10,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x07, 0x01, 0x2C,
+        ];
+
+        let toc = FormattedToc::<Msf>::from_bytes(response).unwrap();
+
+        assert_eq!(
+            toc.hidden_track_one_audio(),
+            Some(Lba::from(0)..Lba::from(30 * 75))
+        );
+    }
+
+    #[test]
+    fn no_hidden_track_one_audio_without_pregap_audio() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x12, 0x01, 0x01,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x07, 0x01, 0x2C,
+        ];
+
+        let toc = FormattedToc::<Msf>::from_bytes(response).unwrap();
+
+        assert_eq!(toc.hidden_track_one_audio(), None);
+    }
+
+    #[test]
+    fn gap_between_audio_and_data() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x2A, 0x01, 0x04,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Pre-emphasized audio
+            0x00, 0x11, 0x02, 0x00, 0x00, 0x00, 0x10, 0x00,
+            // Data, after a pre-gap at the end of track 2
+            0x00, 0x14, 0x03, 0x00, 0x00, 0x00, 0x20, 0x96,
+            // Audio again, after the data track's post-gap
+            0x00, 0x10, 0x04, 0x00, 0x00, 0x00, 0x30, 0x00,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x00, 0x40, 0x00,
+        ];
+
+        let toc = FormattedToc::<Lba>::from_bytes(response).unwrap();
+        let gaps: Vec<TrackGap> = toc.gaps().collect();
+
+        assert_eq!(
+            gaps,
+            [
+                TrackGap {
+                    track_number: 3,
+                    control: q_subcode::Control::IS_DATA,
+                    start: Lba::from(0x2000),
+                    length: 150,
+                },
+                TrackGap {
+                    track_number: 4,
+                    control: q_subcode::Control::empty(),
+                    start: Lba::from(0x3000 - 150),
+                    length: 150,
+                },
+            ]
+        );
+
+        // Each gap lies within the length of the track before it
+        let lengths = toc.track_lengths();
+        assert_eq!(lengths[1], (2, 0x1096));
+        assert!(gaps[0].length <= lengths[1].1);
+    }
+}
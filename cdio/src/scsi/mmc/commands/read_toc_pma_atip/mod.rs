@@ -1,6 +1,12 @@
 use std::marker::PhantomData;
 
-use crate::core::{addressing::Lba, msf::Msf};
+use crate::core::{
+    addressing::{Address, Lba},
+    constants::{LEADOUT_TRACK_NUM, MAX_TRACKS},
+    msf::Msf,
+};
+use crate::device::Drive;
+use crate::transport::Transport;
 
 use super::{Command, Control, OpCode, OpCodeDef, Response};
 
@@ -21,7 +27,7 @@ mod private {
     }
 }
 
-pub trait AddressingMode: private::AddressingModeSeal {}
+pub trait AddressingMode: private::AddressingModeSeal + Address {}
 
 impl private::AddressingModeSeal for Msf {
     const MSF: bool = true;
@@ -99,12 +105,32 @@ impl ReadTocPmaAtipResponse for cd_text::CdText {}
 
 // Distinct impls for each to enable specificity like in the constructors for example
 impl<A: formatted_toc::TrackStartAddress> ReadTocPmaAtip<formatted_toc::FormattedToc<A>> {
-    pub fn new(track_number: u8, allocation_length: u16, control: Control) -> Self {
-        Self {
+    /// Requests the descriptors from `track_number` onwards, always followed by the Lead-out's.
+    /// Track 0 requests every track, and [`LEADOUT_TRACK_NUM`] only the Lead-out.
+    ///
+    /// Returns `None` for any other track number over [`MAX_TRACKS`], which the Drive would
+    /// reject with CHECK CONDITION.
+    pub fn new(track_number: u8, allocation_length: u16, control: Control) -> Option<Self> {
+        if track_number > MAX_TRACKS && track_number != LEADOUT_TRACK_NUM {
+            return None;
+        }
+
+        Some(Self {
             _response_marker: PhantomData,
             track_session_number: track_number,
             allocation_length,
             control,
+        })
+    }
+
+    /// Requests the whole TOC, every track and the Lead-out, e.g. for the length of the last
+    /// track.
+    pub fn all_tracks(allocation_length: u16, control: Control) -> Self {
+        Self {
+            _response_marker: PhantomData,
+            track_session_number: 0,
+            allocation_length,
+            control,
         }
     }
 }
@@ -185,4 +211,82 @@ impl<R: ReadTocPmaAtipResponse> Command<ReadTocPmaAtipOpCode> for ReadTocPmaAtip
             self.control.into(),
         ]
     }
+
+    fn describe(&self) -> String {
+        format!(
+            "READ TOC/PMA/ATIP format {:04b}b, track/session {}{}",
+            R::FORMAT,
+            self.track_session_number,
+            if R::MSF { ", MSF" } else { "" }
+        )
+    }
+}
+
+/// The start of the disc's Lead-out, for the length of the last track. Read from the Raw TOC's
+/// A2h POINT where possible, falling back to the formatted TOC's Lead-out descriptor for Drives
+/// that don't support the Raw TOC, e.g. for non-CD media. Returns `None` if neither has it.
+pub fn leadout_address<T: Transport>(drive: &Drive<T>) -> Result<Option<Lba>, crate::Error> {
+    let raw_toc = drive.execute(ReadTocPmaAtip::<raw_toc::RawToc>::new(1, 4096, 0.into()));
+    if let Some(leadout) = raw_toc.ok().and_then(|toc| toc.leadout_address()) {
+        return Ok(Some(leadout));
+    }
+
+    let toc = drive
+        .execute(ReadTocPmaAtip::<formatted_toc::FormattedToc<Lba>>::all_tracks(4096, 0.into()))?;
+
+    Ok(toc
+        .leadout()
+        .map(|descriptor| descriptor.track_start_address))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[test]
+    fn formatted_toc_track_numbers() {
+        assert_eq!(
+            ReadTocPmaAtip::<formatted_toc::FormattedToc<Msf>>::all_tracks(4096, 0.into()).as_cdb(),
+            [0x43, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00]
+        );
+
+        let leadout_only =
+            ReadTocPmaAtip::<formatted_toc::FormattedToc<Lba>>::new(0xAA, 4096, 0.into()).unwrap();
+        assert_eq!(leadout_only.as_cdb()[6], 0xAA);
+
+        assert!(
+            ReadTocPmaAtip::<formatted_toc::FormattedToc<Lba>>::new(99, 4096, 0.into()).is_some()
+        );
+        assert!(
+            ReadTocPmaAtip::<formatted_toc::FormattedToc<Lba>>::new(100, 4096, 0.into()).is_none()
+        );
+    }
+
+    #[test]
+    fn leadout_falls_back_to_formatted_toc() {
+        #[rustfmt::skip]
+        let formatted_toc: &[u8] = &[
+            0x00, 0x12, 0x01, 0x01,
+            0x00, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x10, 0xAA, 0x00, 0x00, 0x03, 0x0D, 0x40,
+        ];
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                // INVALID FIELD IN CDB
+                .with_queued_sense(0x43, 0x05, 0x24, 0x00)
+                .with_queued_response(0x43, formatted_toc),
+        );
+
+        assert_eq!(leadout_address(&drive).unwrap(), Some(Lba::from(0x030D40)));
+
+        let formats: Vec<u8> = drive
+            .transport()
+            .submitted_cdbs()
+            .iter()
+            .map(|cdb| cdb[2])
+            .collect();
+        assert_eq!(formats, [0b0010, 0b0000]);
+    }
 }
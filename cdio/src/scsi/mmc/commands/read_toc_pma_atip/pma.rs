@@ -1,9 +1,18 @@
+use std::collections::BTreeMap;
 use std::io::Cursor;
 
 use deku::{deku_derive, reader::Reader, DekuError, DekuReader};
 
-use crate::{rainbow_books::q_subcode::Control, scsi::mmc::commands::Response};
+use crate::{
+    core::{msf::Msf, util::bcd_to_u8},
+    rainbow_books::q_subcode::Control,
+    scsi::mmc::commands::Response,
+};
 
+/// The Program Memory Area of a CD-R/RW, where the Drive records each track as it's written
+/// until the session is closed and a TOC is written to the lead-in.
+///
+/// See MMC-6 §6.33.3.5, Table 496.
 #[deku_derive(DekuRead)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Pma {
@@ -11,7 +20,22 @@ pub struct Pma {
     _pma_data_length: usize,
 
     #[deku(count = "_pma_data_length.saturating_sub(2) / 11")]
-    pub toc_track_descriptors: Vec<PmaDescriptor>,
+    pub pma_descriptors: Vec<PmaDescriptor>,
+}
+
+impl Pma {
+    /// The tentative track list of an unclosed session, ordered by track number.
+    ///
+    /// Each item may be recorded in the PMA more than once, in which case the last one read wins.
+    pub fn tentative_tracks(&self) -> Vec<PmaTrack> {
+        self.pma_descriptors
+            .iter()
+            .filter_map(PmaDescriptor::tentative_track)
+            .map(|track| (track.track_number, track))
+            .collect::<BTreeMap<_, _>>()
+            .into_values()
+            .collect()
+    }
 }
 
 impl Response for Pma {
@@ -22,19 +46,92 @@ impl Response for Pma {
     }
 }
 
+/// A single PMA item, returned as it's recorded, i.e. with POINT and the times in BCD.
+///
+/// The meaning of the fields depends on `adr`, see [`PmaDescriptor::tentative_track`] for Mode 1.
 #[deku_derive(DekuRead)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PmaDescriptor {
     #[deku(pad_bytes_before = "1", bits = 4)]
-    adr: u8,
-    control: Control,
-    tno: u8,
-    point: u8,
-    min: u8,
-    sec: u8,
-    frame: u8,
-    zero: u8,
-    pmin: u8,
-    psec: u8,
-    pframe: u8,
+    pub adr: u8,
+    pub control: Control,
+    pub tno: u8,
+    pub point: u8,
+    pub min: u8,
+    pub sec: u8,
+    pub frame: u8,
+    pub zero: u8,
+    pub pmin: u8,
+    pub psec: u8,
+    pub pframe: u8,
+}
+
+impl PmaDescriptor {
+    /// Mode 1 items give a track's start time in PMIN/PSEC/PFRAME and its stop time in
+    /// MIN/SEC/FRAME. Returns `None` for any other item, or one that isn't valid BCD.
+    pub fn tentative_track(&self) -> Option<PmaTrack> {
+        if self.adr != 1 {
+            return None;
+        }
+
+        let track_number = bcd_to_u8(self.point).ok()?;
+        if !(1..=99).contains(&track_number) {
+            return None;
+        }
+
+        Some(PmaTrack {
+            track_number,
+            control: self.control,
+            start: Msf::from_bcd_bytes([self.pmin, self.psec, self.pframe]).ok()?,
+            end: Msf::from_bcd_bytes([self.min, self.sec, self.frame]).ok()?,
+        })
+    }
+}
+
+/// A track recorded in the PMA, see [`Pma::tentative_tracks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PmaTrack {
+    pub track_number: u8,
+    pub control: Control,
+    pub start: Msf,
+    /// The time of the track's last frame.
+    pub end: Msf,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tentative_tracks() {
+        // Captured from a CD-R with two audio tracks and an open session
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x39, 0x00, 0x00,
+            0x00, 0x10, 0x00, 0x01, 0x03, 0x12, 0x40, 0x00, 0x00, 0x02, 0x00,
+            0x00, 0x10, 0x00, 0x02, 0x07, 0x45, 0x11, 0x00, 0x03, 0x14, 0x41,
+            // Disc identification
+            0x00, 0x20, 0x00, 0x00, 0x00, 0x12, 0x34, 0x00, 0x00, 0x00, 0x00,
+            // Track 1 again
+            0x00, 0x10, 0x00, 0x01, 0x03, 0x12, 0x40, 0x00, 0x00, 0x02, 0x00,
+            // Stop time isn't BCD
+            0x00, 0x10, 0x00, 0x03, 0x0A, 0x00, 0x00, 0x00, 0x07, 0x45, 0x12,
+        ];
+
+        let pma = Pma::from_bytes(response).unwrap();
+        assert_eq!(pma.pma_descriptors.len(), 5);
+        assert_eq!(pma.pma_descriptors[2].adr, 2);
+
+        let tracks = pma.tentative_tracks();
+        assert_eq!(tracks.len(), 2);
+
+        assert_eq!(tracks[0].track_number, 1);
+        assert_eq!(tracks[0].start.to_string(), "00:02:00");
+        assert_eq!(tracks[0].end.to_string(), "03:12:40");
+
+        assert_eq!(tracks[1].track_number, 2);
+        assert_eq!(tracks[1].start.to_string(), "03:14:41");
+        assert_eq!(tracks[1].end.to_string(), "07:45:11");
+        assert!(!tracks[1].control.contains(Control::IS_DATA));
+    }
 }
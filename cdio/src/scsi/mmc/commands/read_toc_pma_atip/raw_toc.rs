@@ -1,5 +1,10 @@
 use std::io::Cursor;
 
+use crate::core::{
+    addressing::{Address, Lba},
+    constants::LEADOUT_START_POINT,
+    msf::Msf,
+};
 use crate::rainbow_books::q_subcode::Control;
 use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
 
@@ -18,6 +23,22 @@ pub struct RawToc {
     pub toc_track_descriptors: Vec<TocTrackDescriptor>,
 }
 
+impl RawToc {
+    /// The start of the Lead-out of the last complete session from its A2h POINT, i.e. the true
+    /// end of the program area. Unlike the formatted TOC's AAh descriptor, the Drive always
+    /// returns it, straight from the Lead-in.
+    pub fn leadout_address(&self) -> Option<Lba> {
+        self.toc_track_descriptors
+            .iter()
+            .filter(|descriptor| descriptor.adr == 1 && descriptor.point == LEADOUT_START_POINT)
+            .max_by_key(|descriptor| {
+                descriptor.session_number == self.last_complete_session_number
+            })?
+            .pmsf()
+            .map(|msf| msf.to_lba())
+    }
+}
+
 impl Response for RawToc {
     type Error = DekuError;
 
@@ -42,3 +63,49 @@ pub struct TocTrackDescriptor {
     psec: u8,
     pframe: u8,
 }
+
+impl TocTrackDescriptor {
+    /// PMIN/PSEC/PFRAME, which the Drive converts from BCD for the Raw TOC.
+    fn pmsf(&self) -> Option<Msf> {
+        Some(Msf::new(
+            self.pmin.try_into().ok()?,
+            self.psec.try_into().ok()?,
+            self.pframe.try_into().ok()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leadout_from_a2_point() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x2E, 0x01, 0x01,
+            // A0: first track 1, audio
+            0x01, 0x10, 0x00, 0xA0, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+            // A1: last track 12
+            0x01, 0x10, 0x00, 0xA1, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00,
+            // A2: Lead-out at 45:12:34
+            0x01, 0x10, 0x00, 0xA2, 0x00, 0x00, 0x00, 0x00, 45, 12, 34,
+            // Track 1 at 00:02:00
+            0x01, 0x10, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00,
+        ];
+
+        let toc = RawToc::from_bytes(response).unwrap();
+
+        assert_eq!(toc.toc_track_descriptors.len(), 4);
+        assert_eq!(
+            toc.leadout_address(),
+            Some(Lba::from((45 * 60 + 12) * 75 + 34 - 150))
+        );
+
+        let no_a2 = RawToc {
+            toc_track_descriptors: toc.toc_track_descriptors[..2].to_vec(),
+            ..toc
+        };
+        assert_eq!(no_a2.leadout_address(), None);
+    }
+}
@@ -0,0 +1,281 @@
+use std::io::Cursor;
+
+use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+use crate::rainbow_books::q_subcode;
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+/// How the LOGICAL BLOCK ADDRESS/TRACK/SESSION NUMBER field is interpreted.
+///
+/// See MMC-6 §6.26.2.2, Table 514.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum AddressType {
+    Lba = 0b00,
+    Ltn = 0b01,
+    SessionNum = 0b10,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadTrackInformation {
+    open: bool,
+    address_type: AddressType,
+    address_number: u32,
+    allocation_length: u16,
+    control: Control,
+}
+
+impl ReadTrackInformation {
+    pub fn new(
+        open: bool,
+        address_type: AddressType,
+        address_number: u32,
+        control: Control,
+    ) -> Self {
+        Self {
+            open,
+            address_type,
+            address_number,
+            allocation_length: TrackInformation::LENGTH,
+            control,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 2)]
+#[repr(u8)]
+pub enum LayerJumpRecordingStatus {
+    None = 0b00,
+    Unspecified = 0b01,
+    Manual = 0b10,
+    RegularInterval = 0b11,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 4)]
+#[repr(u8)]
+pub enum DataMode {
+    /// Mode 1 (ISO/IEC 10149)
+    Mode1 = 0x1,
+    /// Mode 2 (ISO/IEC 10149 or CD-ROM XA)
+    Mode2 = 0x2,
+    /// Data Block Type unknown (no track descriptor block), 0xF, or any reserved value
+    #[deku(id_pat = "_")]
+    Unknown = 0xF,
+}
+
+/// See MMC-6 §6.26.3, Table 516.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrackInformation {
+    #[deku(temp, endian = "big")]
+    _data_length: u16,
+
+    #[deku(temp)]
+    _logical_track_number_lsb: u8,
+    #[deku(temp, pad_bytes_after = "1")]
+    _session_number_lsb: u8,
+
+    pub ljrs: LayerJumpRecordingStatus,
+    #[deku(bits = 1)]
+    pub damage: bool,
+    #[deku(bits = 1)]
+    pub copy: bool,
+    /// Though originally defined for CD media, the subchannel control field's meaning has been
+    /// adapted for other media based on their characteristics.
+    ///
+    /// See MMC-6 §6.26.3.9, Table 521.
+    pub track_mode: q_subcode::Control,
+
+    #[deku(bits = 1)]
+    pub rt: bool,
+    #[deku(bits = 1)]
+    pub blank: bool,
+    #[deku(bits = 1)]
+    pub packet_inc: bool,
+    #[deku(bits = 1)]
+    pub fp: bool,
+    pub data_mode: DataMode,
+
+    #[deku(pad_bits_before = "6", bits = 1)]
+    pub lra_v: bool,
+    #[deku(bits = 1)]
+    pub nwa_v: bool,
+
+    #[deku(endian = "big")]
+    pub logical_track_start_addr: i32,
+    #[deku(endian = "big")]
+    pub next_writable_addr: i32,
+    #[deku(endian = "big")]
+    pub free_blocks: u32,
+    #[deku(endian = "big")]
+    pub fixed_packet_size: u32,
+    #[deku(endian = "big")]
+    pub logical_track_size: u32,
+
+    // The fields from here on were added to the Track Information block over time. Drives
+    // predating them return a shorter block, so each is `None` unless the Data Length covers it.
+    #[deku(endian = "big", cond = "TrackInformation::covers(*_data_length, 32)")]
+    pub last_recorded_addr: Option<i32>,
+
+    #[deku(temp, cond = "TrackInformation::covers(*_data_length, 33)")]
+    _logical_track_number_msb: Option<u8>,
+    #[deku(temp, cond = "TrackInformation::covers(*_data_length, 34)")]
+    _session_number_msb: Option<u8>,
+    #[deku(temp, cond = "TrackInformation::covers(*_data_length, 36)")]
+    _reserved: Option<u16>,
+
+    #[deku(endian = "big", cond = "TrackInformation::covers(*_data_length, 40)")]
+    pub read_compatibility_lba: Option<i32>,
+    #[deku(endian = "big", cond = "TrackInformation::covers(*_data_length, 44)")]
+    pub next_layer_jump_addr: Option<i32>,
+    #[deku(endian = "big", cond = "TrackInformation::covers(*_data_length, 48)")]
+    pub last_layer_jump_addr: Option<i32>,
+
+    #[deku(
+        skip,
+        default = "u16::from_be_bytes([_logical_track_number_msb.unwrap_or(0), *_logical_track_number_lsb])"
+    )]
+    pub logical_track_number: u16,
+    #[deku(
+        skip,
+        default = "u16::from_be_bytes([_session_number_msb.unwrap_or(0), *_session_number_lsb])"
+    )]
+    pub session_number: u16,
+}
+
+impl TrackInformation {
+    const LENGTH: u16 = 48;
+
+    /// Whether a block with Data Length `data_length`, which doesn't count the field itself,
+    /// reaches up to byte `end`.
+    fn covers(data_length: u16, end: usize) -> bool {
+        usize::from(data_length) + 2 >= end
+    }
+}
+
+impl Response for TrackInformation {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+type ReadTrackInformationOpCode = OpCode<0x52>;
+
+impl Command<ReadTrackInformationOpCode> for ReadTrackInformation {
+    type Response = TrackInformation;
+
+    fn as_cdb(&self) -> <ReadTrackInformationOpCode as OpCodeDef>::Cdb {
+        [
+            ReadTrackInformationOpCode::OP_CODE,
+            (u8::from(self.open) << 2) | (self.address_type as u8),
+            (self.address_number >> 24) as u8,
+            (self.address_number >> 16) as u8,
+            (self.address_number >> 8) as u8,
+            self.address_number as u8,
+            0,
+            (self.allocation_length >> 8) as u8,
+            self.allocation_length as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        self.allocation_length.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_and_session_number_msb_lsb() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x2E, 0x02, 0x01, 0x00, 0x04, 0x01, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // Logical Track Number (MSB), Session Number (MSB)
+            0x01, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let track_information = TrackInformation::from_bytes(response).unwrap();
+
+        assert_eq!(track_information.logical_track_number, 0x0102);
+        assert_eq!(track_information.logical_track_number, 258);
+        assert_eq!(track_information.session_number, 1);
+        assert_eq!(track_information.track_mode, q_subcode::Control::IS_DATA);
+        assert_eq!(track_information.data_mode, DataMode::Mode1);
+        assert_eq!(track_information.logical_track_size, 0x1000);
+        assert_eq!(track_information.last_recorded_addr, Some(0));
+        assert_eq!(track_information.last_layer_jump_addr, Some(0));
+    }
+
+    #[test]
+    fn reserved_data_mode_is_unknown() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            // Data Mode 0x3, reserved
+            0x00, 0x22, 0x01, 0x01, 0x00, 0x04, 0x03, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x0F, 0xFF,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let track_information = TrackInformation::from_bytes(response).unwrap();
+
+        assert_eq!(track_information.data_mode, DataMode::Unknown);
+        assert_eq!(track_information.logical_track_size, 0x1000);
+    }
+
+    #[test]
+    fn legacy_track_information_blocks() {
+        #[rustfmt::skip]
+        let response: &[u8] = &[
+            0x00, 0x22, 0x05, 0x01, 0x00, 0x04, 0x01, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x0F, 0xFF,
+            // Logical Track Number (MSB), Session Number (MSB)
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        // The 36 byte block from before the layer jump fields
+        let track_information = TrackInformation::from_bytes(response).unwrap();
+        assert_eq!(track_information.logical_track_number, 5);
+        assert_eq!(track_information.logical_track_size, 0x1000);
+        assert_eq!(track_information.last_recorded_addr, Some(0x0FFF));
+        assert_eq!(track_information.read_compatibility_lba, None);
+        assert_eq!(track_information.last_layer_jump_addr, None);
+
+        // The original 28 byte block, without even the Last Recorded Address
+        let mut response = response[..28].to_vec();
+        response[1] = 0x1A;
+        let track_information = TrackInformation::from_bytes(&response).unwrap();
+        assert_eq!(track_information.logical_track_number, 5);
+        assert_eq!(track_information.session_number, 1);
+        assert_eq!(track_information.logical_track_size, 0x1000);
+        assert_eq!(track_information.last_recorded_addr, None);
+    }
+
+    #[test]
+    fn allocation_length_matches_cdb() {
+        let read_track_information =
+            ReadTrackInformation::new(false, AddressType::Ltn, 2, 0.into());
+
+        assert_eq!(
+            read_track_information.as_cdb(),
+            [0x52, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x30, 0x00]
+        );
+        assert_eq!(read_track_information.allocation_len(), 48);
+    }
+}
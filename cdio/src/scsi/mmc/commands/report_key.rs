@@ -0,0 +1,229 @@
+use std::io::Cursor;
+use std::marker::PhantomData;
+
+use deku::{deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+use crate::device::Drive;
+use crate::transport::Transport;
+
+use super::{Command, Control, OpCode, OpCodeDef, Response};
+
+mod private {
+    pub trait ReportKeyFormat {
+        const KEY_FORMAT: u8;
+    }
+}
+
+/// Only the RPC State is supported for now; the remaining formats are part of the CSS/CPPM
+/// authentication handshake.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReportKey<R: ReportKeyResponse> {
+    _response_marker: PhantomData<R>,
+    allocation_length: u16,
+    control: Control,
+}
+
+pub trait ReportKeyResponse: private::ReportKeyFormat + Response {}
+
+impl private::ReportKeyFormat for RpcState {
+    const KEY_FORMAT: u8 = 0b001000;
+}
+impl ReportKeyResponse for RpcState {}
+
+impl ReportKey<RpcState> {
+    pub fn new(control: Control) -> Self {
+        Self {
+            _response_marker: PhantomData,
+            allocation_length: RpcState::LENGTH,
+            control,
+        }
+    }
+}
+
+/// How the Drive's region has been set.
+///
+/// See MMC-6 §6.29.2.8, Table 545.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8", bits = 2)]
+#[repr(u8)]
+pub enum TypeCode {
+    NoRegionSetting = 0b00,
+    RegionSet = 0b01,
+    /// The region is set, and additional restrictions are required to make a change.
+    RegionSetWithRestrictions = 0b10,
+    /// The region has been set permanently, but may be reset by the vendor if necessary.
+    RegionSetPermanently = 0b11,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(id_type = "u8")]
+pub enum RpcScheme {
+    /// The Drive doesn't enforce Region Playback Control Phase II.
+    #[deku(id = "0x00")]
+    Unknown,
+    #[deku(id = "0x01")]
+    RpcPhaseII,
+    #[deku(id_pat = "_")]
+    Reserved(u8),
+}
+
+/// The Region Playback Control state of the Drive (Key Format 001000b).
+///
+/// See MMC-6 §6.29.2.8, Table 544.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RpcState {
+    #[deku(temp, endian = "big", assert = "*_report_key_data_length >= 6")]
+    _report_key_data_length: u16,
+
+    #[deku(pad_bytes_before = "2")]
+    pub type_code: TypeCode,
+    #[deku(bits = 3)]
+    pub vendor_resets_available: u8,
+    #[deku(bits = 3)]
+    pub user_changes_available: u8,
+
+    /// Each bit set to one is a region the Drive will *not* play, bit 0 being region 1.
+    pub region_mask: u8,
+
+    #[deku(pad_bytes_after = "1")]
+    pub rpc_scheme: RpcScheme,
+}
+
+impl RpcState {
+    const LENGTH: u16 = 8;
+}
+
+impl Response for RpcState {
+    type Error = DekuError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ())
+    }
+}
+
+/// The regions a Drive will play, decoded from the [`RpcState`] region mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriveRegion {
+    /// The mask is all zeros, so discs of every region are played.
+    RegionFree,
+    /// Only discs of the region (1 through 8) are played.
+    Region(u8),
+    /// Any other combination, as the raw mask where each bit set is a region *not* played.
+    Mask(u8),
+}
+
+impl From<u8> for DriveRegion {
+    fn from(region_mask: u8) -> Self {
+        match region_mask {
+            0x00 => Self::RegionFree,
+            mask if mask.count_zeros() == 1 => Self::Region(mask.trailing_ones() as u8 + 1),
+            mask => Self::Mask(mask),
+        }
+    }
+}
+
+/// A summary of the Drive's Region Playback Control state, e.g. how many times its region can
+/// still be changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionStatus {
+    pub scheme: RpcScheme,
+    pub type_code: TypeCode,
+    pub region: DriveRegion,
+    pub user_changes_remaining: u8,
+    pub vendor_resets_remaining: u8,
+}
+
+impl From<RpcState> for RegionStatus {
+    fn from(rpc_state: RpcState) -> Self {
+        Self {
+            scheme: rpc_state.rpc_scheme,
+            type_code: rpc_state.type_code,
+            region: DriveRegion::from(rpc_state.region_mask),
+            user_changes_remaining: rpc_state.user_changes_available,
+            vendor_resets_remaining: rpc_state.vendor_resets_available,
+        }
+    }
+}
+
+/// Issues REPORT KEY for the RPC State.
+pub fn region_status<T: Transport>(drive: &Drive<T>) -> Result<RegionStatus, crate::Error> {
+    drive
+        .execute(ReportKey::<RpcState>::new(0.into()))
+        .map(RegionStatus::from)
+}
+
+type ReportKeyOpCode = OpCode<0xA4>;
+
+impl<R: ReportKeyResponse> Command<ReportKeyOpCode> for ReportKey<R> {
+    type Response = R;
+
+    fn as_cdb(&self) -> <ReportKeyOpCode as OpCodeDef>::Cdb {
+        [
+            ReportKeyOpCode::OP_CODE,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0, // Key Class: DVD CSS/CPPM or CPRM
+            (self.allocation_length >> 8) as u8,
+            self.allocation_length as u8,
+            R::KEY_FORMAT & 0b111111,
+            self.control.into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::mock::MockTransport;
+
+    use super::*;
+
+    #[test]
+    fn parse_rpc_state() {
+        // Region 2 set, 3 user changes and 4 vendor resets remaining
+        let rpc_state =
+            RpcState::from_bytes(&[0x00, 0x06, 0x00, 0x00, 0x63, 0xFD, 0x01, 0x00]).unwrap();
+
+        assert_eq!(rpc_state.type_code, TypeCode::RegionSet);
+        assert_eq!(rpc_state.vendor_resets_available, 4);
+        assert_eq!(rpc_state.user_changes_available, 3);
+        assert_eq!(rpc_state.region_mask, 0xFD);
+        assert_eq!(rpc_state.rpc_scheme, RpcScheme::RpcPhaseII);
+    }
+
+    #[test]
+    fn region_status_region_1() {
+        // Region 1 set, 4 user changes and 4 vendor resets remaining
+        let drive = Drive::with_transport(
+            MockTransport::new()
+                .with_response(0xA4, [0x00, 0x06, 0x00, 0x00, 0x64, 0xFE, 0x01, 0x00]),
+        );
+
+        assert_eq!(
+            region_status(&drive).unwrap(),
+            RegionStatus {
+                scheme: RpcScheme::RpcPhaseII,
+                type_code: TypeCode::RegionSet,
+                region: DriveRegion::Region(1),
+                user_changes_remaining: 4,
+                vendor_resets_remaining: 4,
+            }
+        );
+
+        assert_eq!(DriveRegion::from(0x00), DriveRegion::RegionFree);
+        assert_eq!(DriveRegion::from(0x7F), DriveRegion::Region(8));
+        assert_eq!(DriveRegion::from(0xFC), DriveRegion::Mask(0xFC));
+    }
+
+    #[test]
+    fn report_key_rpc_state_cdb() {
+        assert_eq!(
+            ReportKey::<RpcState>::new(0.into()).as_cdb(),
+            [0xA4, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x08, 0x08, 0x00]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::core::util::BitWriter;
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReserveTrack {
+    rmz: bool,
+    arsv: bool,
+    reservation_size: u32,
+    control: Control,
+}
+
+impl ReserveTrack {
+    /// Reserves `reservation_size` logical blocks for the invisible/incomplete track.
+    ///
+    /// `rmz` reserves an RZone rather than a track on DVD-R media. `arsv` (Address Reservation)
+    /// is only valid when the Incremental Streaming Writable Feature reports ARSV support.
+    pub fn new(rmz: bool, arsv: bool, reservation_size: u32, control: Control) -> Self {
+        Self {
+            rmz,
+            arsv,
+            reservation_size,
+            control,
+        }
+    }
+}
+
+type ReserveTrackOpCode = OpCode<0x53>;
+
+impl Command<ReserveTrackOpCode> for ReserveTrack {
+    type Response = ();
+
+    fn as_cdb(&self) -> <ReserveTrackOpCode as OpCodeDef>::Cdb {
+        let reservation_size = self.reservation_size.to_be_bytes();
+
+        [
+            ReserveTrackOpCode::OP_CODE,
+            BitWriter::new()
+                .set_bit(0b0000_0010, self.rmz)
+                .set_bit(0b0000_0001, self.arsv)
+                .into(),
+            0,
+            0,
+            0,
+            reservation_size[0],
+            reservation_size[1],
+            reservation_size[2],
+            reservation_size[3],
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_track_cdb() {
+        let reserve_track = ReserveTrack::new(false, true, 0x0001_2345, 0.into());
+
+        assert_eq!(
+            reserve_track.as_cdb(),
+            [0x53, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x23, 0x45, 0x00]
+        );
+        assert_eq!(reserve_track.allocation_len(), 0);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::core::{addressing::Lba, msf::Msf, util::BitWriter};
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanDirection {
+    Forward,
+    Reverse,
+}
+
+/// Where the scan starts, which also selects the Type field of the CDB.
+///
+/// See MMC-3 §6.1.16, Table 190.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanAddress {
+    Lba(Lba),
+    Msf(Msf),
+    TrackNumber(u8),
+}
+
+impl ScanAddress {
+    fn type_field(&self) -> u8 {
+        match self {
+            Self::Lba(_) => 0b00,
+            Self::Msf(_) => 0b01,
+            Self::TrackNumber(_) => 0b10,
+        }
+    }
+
+    fn as_bytes(&self) -> [u8; 4] {
+        match *self {
+            Self::Lba(lba) => i32::from(lba).to_be_bytes(),
+            Self::Msf(msf) => [
+                0,
+                Msf::min(&msf).into(),
+                msf.sec().into(),
+                msf.frame().into(),
+            ],
+            Self::TrackNumber(track_number) => [0, 0, 0, track_number],
+        }
+    }
+}
+
+/// Plays audio in fast forward or fast reverse from `address`, e.g. for previewing while
+/// seeking. The scan continues until a PLAY or STOP PLAY/SCAN command is issued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scan {
+    direction: ScanDirection,
+    address: ScanAddress,
+    control: Control,
+}
+
+impl Scan {
+    pub fn new(direction: ScanDirection, address: ScanAddress, control: Control) -> Self {
+        Self {
+            direction,
+            address,
+            control,
+        }
+    }
+}
+
+type ScanOpCode = OpCode<0xBA>;
+
+impl Command<ScanOpCode> for Scan {
+    type Response = ();
+
+    fn as_cdb(&self) -> <ScanOpCode as OpCodeDef>::Cdb {
+        let address = self.address.as_bytes();
+
+        [
+            ScanOpCode::OP_CODE,
+            BitWriter::new()
+                .set_bit(0b0001_0000, self.direction == ScanDirection::Reverse)
+                .into(),
+            address[0],
+            address[1],
+            address[2],
+            address[3],
+            0,
+            0,
+            0,
+            BitWriter::new()
+                .set_field(6, 0b11, self.address.type_field())
+                .into(),
+            0,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::msf::{Frame, Minute, Second};
+
+    use super::*;
+
+    #[test]
+    fn scan_forward_from_lba() {
+        let scan = Scan::new(
+            ScanDirection::Forward,
+            ScanAddress::Lba(Lba::from(0x1234)),
+            0.into(),
+        );
+
+        assert_eq!(
+            scan.as_cdb(),
+            [0xBA, 0x00, 0x00, 0x00, 0x12, 0x34, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(scan.allocation_len(), 0);
+    }
+
+    #[test]
+    fn scan_reverse_from_msf_and_track() {
+        let msf = Msf::new(
+            Minute::try_from(3).unwrap(),
+            Second::try_from(25).unwrap(),
+            Frame::try_from(60).unwrap(),
+        );
+
+        assert_eq!(
+            Scan::new(ScanDirection::Reverse, ScanAddress::Msf(msf), 0.into()).as_cdb(),
+            [0xBA, 0x10, 0x00, 0x03, 0x19, 0x3C, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00]
+        );
+        assert_eq!(
+            Scan::new(
+                ScanDirection::Reverse,
+                ScanAddress::TrackNumber(7),
+                0.into()
+            )
+            .as_cdb(),
+            [0xBA, 0x10, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use crate::core::addressing::Lba;
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// Moves the pickup to `lba` without transferring any data, e.g. to time how long the Drive
+/// takes to get there.
+///
+/// Obsolete as of MMC-2, but still accepted by virtually every Drive. See SBC-2 §5.9.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seek10 {
+    lba: Lba,
+    control: Control,
+}
+
+impl Seek10 {
+    pub fn new(lba: Lba, control: Control) -> Self {
+        Self { lba, control }
+    }
+}
+
+type Seek10OpCode = OpCode<0x2B>;
+
+impl Command<Seek10OpCode> for Seek10 {
+    type Response = ();
+
+    fn as_cdb(&self) -> <Seek10OpCode as OpCodeDef>::Cdb {
+        let lba = i32::from(self.lba).to_be_bytes();
+
+        [
+            Seek10OpCode::OP_CODE,
+            0,
+            lba[0],
+            lba[1],
+            lba[2],
+            lba[3],
+            0,
+            0,
+            0,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seek_10_cdb() {
+        let seek = Seek10::new(Lba::from(0x0001_2345), 0.into());
+
+        assert_eq!(seek.as_cdb(), [0x2B, 0, 0x00, 0x01, 0x23, 0x45, 0, 0, 0, 0]);
+        assert_eq!(seek.allocation_len(), 0);
+    }
+}
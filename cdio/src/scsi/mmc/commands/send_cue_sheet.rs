@@ -0,0 +1,137 @@
+use crate::{core::msf::Msf, rainbow_books::q_subcode};
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// A single 8 byte entry of a Session At Once cue sheet, describing where a track (or index)
+/// starts in the absolute time of the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CueSheetEntry {
+    pub control: q_subcode::Control,
+    pub adr: u8,
+    /// 00h for the Lead-in, AAh for the Lead-out.
+    pub track_number: u8,
+    pub index: u8,
+    /// The form of the main channel data the Host sends for this entry, e.g. 00h for CD-DA or 10h
+    /// for CD-ROM Mode 1.
+    pub data_form: u8,
+    /// Serial Copy Management System, sets the copy bit of the audio track alternately.
+    pub scms: bool,
+    pub absolute_time: Msf,
+}
+
+impl CueSheetEntry {
+    const LENGTH: usize = 8;
+
+    fn to_bytes(self) -> [u8; Self::LENGTH] {
+        [
+            (self.control.bits() << 4) | (self.adr & 0x0F),
+            self.track_number,
+            self.index,
+            self.data_form,
+            u8::from(self.scms) << 7,
+            Msf::min(&self.absolute_time).into(),
+            self.absolute_time.sec().into(),
+            self.absolute_time.frame().into(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendCueSheet {
+    cue_sheet: Vec<u8>,
+    control: Control,
+}
+
+impl SendCueSheet {
+    /// The largest cue sheet the 3 byte CUE SHEET SIZE field can describe.
+    pub const MAX_CUE_SHEET_SIZE: usize = 0x00FF_FFFF;
+
+    /// Encodes `entries` into a cue sheet. Returns `None` if it would be larger than
+    /// [`Self::MAX_CUE_SHEET_SIZE`].
+    pub fn new(entries: &[CueSheetEntry], control: Control) -> Option<Self> {
+        if entries.len() * CueSheetEntry::LENGTH > Self::MAX_CUE_SHEET_SIZE {
+            return None;
+        }
+
+        Some(Self {
+            cue_sheet: entries.iter().flat_map(|entry| entry.to_bytes()).collect(),
+            control,
+        })
+    }
+}
+
+type SendCueSheetOpCode = OpCode<0x5D>;
+
+impl Command<SendCueSheetOpCode> for SendCueSheet {
+    type Response = ();
+
+    fn as_cdb(&self) -> <SendCueSheetOpCode as OpCodeDef>::Cdb {
+        let cue_sheet_size = (self.cue_sheet.len() as u32).to_be_bytes();
+
+        [
+            SendCueSheetOpCode::OP_CODE,
+            0,
+            0,
+            0,
+            0,
+            0,
+            cue_sheet_size[1],
+            cue_sheet_size[2],
+            cue_sheet_size[3],
+            self.control.into(),
+        ]
+    }
+
+    fn parameter_list(&self) -> Option<&[u8]> {
+        Some(&self.cue_sheet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::addressing::{Address, Lba};
+
+    use super::*;
+
+    #[test]
+    fn two_track_cue_sheet() {
+        let entry = |track_number, index, absolute_time| CueSheetEntry {
+            control: q_subcode::Control::empty(),
+            adr: 1,
+            track_number,
+            index,
+            data_form: 0x00,
+            scms: false,
+            absolute_time: Msf::from_lba(Lba::from(absolute_time)).unwrap(),
+        };
+
+        let send_cue_sheet = SendCueSheet::new(
+            &[
+                entry(0x00, 0, -150),
+                entry(1, 0, -150),
+                entry(1, 1, 0),
+                entry(2, 1, 4503),
+                entry(0xAA, 1, 9000),
+            ],
+            0.into(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            send_cue_sheet.as_cdb(),
+            [0x5D, 0, 0, 0, 0, 0, 0x00, 0x00, 0x28, 0x00]
+        );
+
+        #[rustfmt::skip]
+        assert_eq!(
+            send_cue_sheet.parameter_list().unwrap(),
+            [
+                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00,
+                0x01, 0x02, 0x01, 0x00, 0x00, 0x01, 0x02, 0x03,
+                0x01, 0xAA, 0x01, 0x00, 0x00, 0x02, 0x02, 0x00,
+            ]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// Only the RPC Structure (setting the Drive's region) is supported for now; the remaining
+/// formats are part of the CSS/CPPM authentication handshake.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SendKey {
+    key_format: u8,
+    parameter_list: Vec<u8>,
+    control: Control,
+}
+
+impl SendKey {
+    /// RPC Structure (Key Format 000110b), setting the Drive's region to `region` (1 through 8).
+    /// Returns `None` if `region` is out of range.
+    ///
+    /// Each successful call uses up one of the Drive's limited user controlled changes.
+    ///
+    /// See MMC-6 §6.37.3.4, Table 629.
+    pub fn set_region(region: u8, control: Control) -> Option<Self> {
+        if !(1..=8).contains(&region) {
+            return None;
+        }
+
+        // The Preferred Drive Region Code is a mask with only the desired region's bit cleared
+        let preferred_drive_region_code = !(1u8 << (region - 1));
+
+        Some(Self {
+            key_format: 0b000110,
+            parameter_list: vec![0x00, 0x06, 0, 0, preferred_drive_region_code, 0, 0, 0],
+            control,
+        })
+    }
+}
+
+type SendKeyOpCode = OpCode<0xA3>;
+
+impl Command<SendKeyOpCode> for SendKey {
+    type Response = ();
+
+    fn as_cdb(&self) -> <SendKeyOpCode as OpCodeDef>::Cdb {
+        let parameter_list_length = self.parameter_list.len() as u16;
+
+        [
+            SendKeyOpCode::OP_CODE,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0, // Key Class: DVD CSS/CPPM or CPRM
+            (parameter_list_length >> 8) as u8,
+            parameter_list_length as u8,
+            self.key_format & 0b111111,
+            self.control.into(),
+        ]
+    }
+
+    fn parameter_list(&self) -> Option<&[u8]> {
+        Some(&self.parameter_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{device::Drive, transport::mock::MockTransport};
+
+    use super::*;
+
+    #[test]
+    fn set_region_rpc_structure() {
+        let set_region_2 = SendKey::set_region(2, 0.into()).unwrap();
+
+        // Key Format 000110b with AGID 0, and 8 bytes of parameter list
+        assert_eq!(
+            set_region_2.as_cdb(),
+            [0xA3, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x08, 0x06, 0x00]
+        );
+        // Every region but 2 masked out, like REPORT KEY's region mask
+        assert_eq!(
+            set_region_2.parameter_list().unwrap(),
+            [0x00, 0x06, 0x00, 0x00, 0xFD, 0x00, 0x00, 0x00]
+        );
+
+        let drive = Drive::with_transport(MockTransport::new().with_response(0xA3, []));
+        drive
+            .execute(SendKey::set_region(8, 0.into()).unwrap())
+            .unwrap();
+        assert_eq!(
+            drive.transport().sent_data(),
+            [[0x00, 0x06, 0x00, 0x00, 0x7F, 0x00, 0x00, 0x00]]
+        );
+
+        assert_eq!(SendKey::set_region(0, 0.into()), None);
+        assert_eq!(SendKey::set_region(9, 0.into()), None);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::core::addressing::Lba;
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// Requests that the Drive begin prefetching from `read_ahead_lba` once it reads
+/// `trigger_lba`.
+///
+/// See MMC-6 §6.38.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetReadAhead {
+    trigger_lba: Lba,
+    read_ahead_lba: Lba,
+    control: Control,
+}
+
+impl SetReadAhead {
+    pub fn new(trigger_lba: Lba, read_ahead_lba: Lba, control: Control) -> Self {
+        Self {
+            trigger_lba,
+            read_ahead_lba,
+            control,
+        }
+    }
+}
+
+type SetReadAheadOpCode = OpCode<0xA7>;
+
+impl Command<SetReadAheadOpCode> for SetReadAhead {
+    type Response = ();
+
+    fn as_cdb(&self) -> <SetReadAheadOpCode as OpCodeDef>::Cdb {
+        let trigger_lba = i32::from(self.trigger_lba).to_be_bytes();
+        let read_ahead_lba = i32::from(self.read_ahead_lba).to_be_bytes();
+
+        [
+            SetReadAheadOpCode::OP_CODE,
+            0,
+            trigger_lba[0],
+            trigger_lba[1],
+            trigger_lba[2],
+            trigger_lba[3],
+            read_ahead_lba[0],
+            read_ahead_lba[1],
+            read_ahead_lba[2],
+            read_ahead_lba[3],
+            0,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_read_ahead_cdb() {
+        let set_read_ahead = SetReadAhead::new(Lba::from(0x1234), Lba::from(0x012345), 0.into());
+
+        assert_eq!(
+            set_read_ahead.as_cdb(),
+            [0xA7, 0, 0x00, 0x00, 0x12, 0x34, 0x00, 0x01, 0x23, 0x45, 0, 0]
+        );
+        assert_eq!(set_read_ahead.allocation_len(), 0);
+    }
+
+    #[test]
+    fn negative_lba_is_twos_complement() {
+        let cdb = SetReadAhead::new(Lba::from(-150), Lba::from(0), 0.into()).as_cdb();
+
+        assert_eq!(cdb[2..6], [0xFF, 0xFF, 0xFF, 0x6A]);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::core::addressing::Lba;
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// See MMC-6 §6.39.3.1, Table 614.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum WriteRotationControl {
+    /// The Drive's default rotational control for the media.
+    #[default]
+    Default = 0b00,
+    /// Constant Angular Velocity.
+    Cav = 0b01,
+}
+
+/// The performance the Host is requesting for the extent `start_lba..=end_lba`. Sizes are in
+/// kilobytes (1000 bytes) and times are in milliseconds, so the requested rate is the size divided
+/// by the time.
+///
+/// See MMC-6 §6.39.3.1, Table 613.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerformanceDescriptor {
+    pub wrc: WriteRotationControl,
+    /// Restore Drive Defaults, all other fields are ignored when set.
+    pub rdd: bool,
+    /// The Drive shall either honor the request exactly or fail it.
+    pub exact: bool,
+    /// Random Access, the performance is requested for random rather than sequential access.
+    pub ra: bool,
+    pub start_lba: Lba,
+    pub end_lba: Lba,
+    pub read_size: u32,
+    pub read_time: u32,
+    pub write_size: u32,
+    pub write_time: u32,
+}
+
+impl PerformanceDescriptor {
+    const LENGTH: usize = 28;
+
+    fn to_bytes(self) -> [u8; Self::LENGTH] {
+        let mut bytes = [0u8; Self::LENGTH];
+
+        bytes[0] = ((self.wrc as u8) << 3)
+            | (u8::from(self.rdd) << 2)
+            | (u8::from(self.exact) << 1)
+            | u8::from(self.ra);
+        bytes[4..8].copy_from_slice(&i32::from(self.start_lba).to_be_bytes());
+        bytes[8..12].copy_from_slice(&i32::from(self.end_lba).to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.read_size.to_be_bytes());
+        bytes[16..20].copy_from_slice(&self.read_time.to_be_bytes());
+        bytes[20..24].copy_from_slice(&self.write_size.to_be_bytes());
+        bytes[24..28].copy_from_slice(&self.write_time.to_be_bytes());
+
+        bytes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetStreaming {
+    parameter_list: [u8; PerformanceDescriptor::LENGTH],
+    control: Control,
+}
+
+impl SetStreaming {
+    /// SET STREAMING with a Performance Descriptor (Type 00h).
+    pub fn new(performance_descriptor: PerformanceDescriptor, control: Control) -> Self {
+        Self {
+            parameter_list: performance_descriptor.to_bytes(),
+            control,
+        }
+    }
+}
+
+type SetStreamingOpCode = OpCode<0xB6>;
+
+impl Command<SetStreamingOpCode> for SetStreaming {
+    type Response = ();
+
+    fn as_cdb(&self) -> <SetStreamingOpCode as OpCodeDef>::Cdb {
+        let parameter_list_length = self.parameter_list.len() as u16;
+
+        [
+            SetStreamingOpCode::OP_CODE,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0x00, // Type: Performance Descriptor
+            (parameter_list_length >> 8) as u8,
+            parameter_list_length as u8,
+            self.control.into(),
+        ]
+    }
+
+    fn parameter_list(&self) -> Option<&[u8]> {
+        Some(&self.parameter_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn performance_descriptor_layout() {
+        let set_streaming = SetStreaming::new(
+            PerformanceDescriptor {
+                wrc: WriteRotationControl::Cav,
+                rdd: false,
+                exact: true,
+                ra: false,
+                start_lba: Lba::from(0),
+                end_lba: Lba::from(0x0005_7E3F),
+                read_size: 7056,
+                read_time: 1000,
+                write_size: 0,
+                write_time: 1000,
+            },
+            0.into(),
+        );
+
+        assert_eq!(
+            set_streaming.as_cdb(),
+            [0xB6, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x00, 0x1C, 0x00]
+        );
+
+        #[rustfmt::skip]
+        assert_eq!(
+            set_streaming.parameter_list().unwrap(),
+            [
+                0x0A, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00,
+                0x00, 0x05, 0x7E, 0x3F,
+                0x00, 0x00, 0x1B, 0x90,
+                0x00, 0x00, 0x03, 0xE8,
+                0x00, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x03, 0xE8,
+            ]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use num_enum::IntoPrimitive;
+
+use crate::core::util::BitWriter;
+
+use super::{Command, Control, OpCode, OpCodeDef};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadEjectOperation {
+    StopDisc,
+    StartDisc,
+    EjectIfPermitted,
+    LoadAndStartDisc,
+    /// Only the lower two bits of the Format-Layer Number are sent to the Drive.
+    JumptToFormatLayer(u8),
+    ChangePowerCondition(PowerCondition),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IntoPrimitive)]
+#[repr(u8)]
+pub enum PowerCondition {
+    NoChange = 0x0,
+    Idle = 0x2,
+    StandBy = 0x3,
+    Sleep = 0x5,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StartStopUnit {
+    immediate: bool,
+    load_eject_operation: LoadEjectOperation,
+    control: Control,
+}
+
+impl StartStopUnit {
+    pub fn new(immediate: bool, operation: LoadEjectOperation, control: Control) -> Self {
+        Self {
+            immediate,
+            load_eject_operation: operation,
+            control,
+        }
+    }
+}
+
+type StartStopUnitOpCode = OpCode<0x1B>;
+
+impl Command<StartStopUnitOpCode> for StartStopUnit {
+    type Response = ();
+
+    fn as_cdb(&self) -> <StartStopUnitOpCode as OpCodeDef>::Cdb {
+        let (fl, fl_num, loej, start, pow_cond) = match self.load_eject_operation {
+            LoadEjectOperation::StopDisc => (false, 0, false, false, PowerCondition::NoChange),
+            LoadEjectOperation::StartDisc => (false, 0, false, true, PowerCondition::NoChange),
+            LoadEjectOperation::EjectIfPermitted => {
+                (false, 0, true, false, PowerCondition::NoChange)
+            }
+            LoadEjectOperation::LoadAndStartDisc => {
+                (false, 0, true, true, PowerCondition::NoChange)
+            }
+            LoadEjectOperation::JumptToFormatLayer(n) => {
+                (true, n, true, true, PowerCondition::NoChange)
+            }
+            LoadEjectOperation::ChangePowerCondition(c) => (false, 0, false, false, c),
+        };
+
+        [
+            StartStopUnitOpCode::OP_CODE,
+            BitWriter::new().set_bit(0b0000_0001, self.immediate).into(),
+            0,
+            // Format-Layer Number occupies bits 1-0, the rest are reserved
+            BitWriter::new().set_field(0, 0b11, fl_num).into(),
+            BitWriter::new()
+                .set_field(4, 0b1111, pow_cond.into())
+                .set_bit(0b0000_0100, fl)
+                .set_bit(0b0000_0010, loej)
+                .set_bit(0b0000_0001, start)
+                .into(),
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "START STOP UNIT {:?}{}",
+            self.load_eject_operation,
+            if self.immediate { ", immediate" } else { "" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cdb(operation: LoadEjectOperation) -> [u8; 6] {
+        StartStopUnit::new(false, operation, 0.into()).as_cdb()
+    }
+
+    #[test]
+    fn jump_to_format_layer_cdb() {
+        assert_eq!(
+            cdb(LoadEjectOperation::JumptToFormatLayer(3)),
+            [0x1B, 0x00, 0x00, 0x03, 0x07, 0x00]
+        );
+    }
+
+    #[test]
+    fn change_power_condition_cdb() {
+        for (condition, byte_4) in [
+            (PowerCondition::NoChange, 0x00),
+            (PowerCondition::Idle, 0x20),
+            (PowerCondition::StandBy, 0x30),
+            (PowerCondition::Sleep, 0x50),
+        ] {
+            assert_eq!(
+                cdb(LoadEjectOperation::ChangePowerCondition(condition)),
+                [0x1B, 0x00, 0x00, 0x00, byte_4, 0x00]
+            );
+        }
+    }
+
+    #[test]
+    fn describe_start_stop_unit() {
+        assert_eq!(
+            StartStopUnit::new(true, LoadEjectOperation::EjectIfPermitted, 0.into()).describe(),
+            "START STOP UNIT EjectIfPermitted, immediate"
+        );
+        assert_eq!(
+            StartStopUnit::new(false, LoadEjectOperation::LoadAndStartDisc, 0.into()).describe(),
+            "START STOP UNIT LoadAndStartDisc"
+        );
+    }
+
+    #[test]
+    fn load_eject_cdb() {
+        assert_eq!(
+            StartStopUnit::new(true, LoadEjectOperation::EjectIfPermitted, 0.into()).as_cdb(),
+            [0x1B, 0x01, 0x00, 0x00, 0x02, 0x00]
+        );
+        assert_eq!(
+            cdb(LoadEjectOperation::LoadAndStartDisc),
+            [0x1B, 0x00, 0x00, 0x00, 0x03, 0x00]
+        );
+        assert_eq!(
+            StartStopUnit::new(false, LoadEjectOperation::StopDisc, 0.into()).allocation_len(),
+            0
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// Stops any audio PLAY or SCAN in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StopPlayScan {
+    control: Control,
+}
+
+impl StopPlayScan {
+    pub fn new(control: Control) -> Self {
+        Self { control }
+    }
+}
+
+type StopPlayScanOpCode = OpCode<0x4E>;
+
+impl Command<StopPlayScanOpCode> for StopPlayScan {
+    type Response = ();
+
+    fn as_cdb(&self) -> <StopPlayScanOpCode as OpCodeDef>::Cdb {
+        [
+            StopPlayScanOpCode::OP_CODE,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_play_scan_cdb() {
+        let stop_play_scan = StopPlayScan::new(0.into());
+
+        assert_eq!(
+            stop_play_scan.as_cdb(),
+            [0x4E, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(stop_play_scan.allocation_len(), 0);
+    }
+}
@@ -0,0 +1,63 @@
+use super::{Command, Control, OpCode, OpCodeDef};
+
+/// Asks whether the Drive is ready for media access commands. Nothing is returned on success;
+/// otherwise the sense data says why not, e.g. MEDIUM NOT PRESENT, or a pending UNIT ATTENTION
+/// after the media was changed.
+///
+/// See SPC-4 §6.47.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TestUnitReady {
+    control: Control,
+}
+
+impl TestUnitReady {
+    pub fn new(control: Control) -> Self {
+        Self { control }
+    }
+}
+
+type TestUnitReadyOpCode = OpCode<0x00>;
+
+impl Command<TestUnitReadyOpCode> for TestUnitReady {
+    type Response = ();
+
+    fn as_cdb(&self) -> <TestUnitReadyOpCode as OpCodeDef>::Cdb {
+        [
+            TestUnitReadyOpCode::OP_CODE,
+            0,
+            0,
+            0,
+            0,
+            self.control.into(),
+        ]
+    }
+
+    fn allocation_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{device::Drive, transport::mock::MockTransport};
+
+    use super::*;
+
+    #[test]
+    fn drive_test_unit_ready_transfers_no_data() {
+        // Anything the Drive returned would land in a data buffer, if one were allocated
+        let drive = Drive::with_transport(MockTransport::new().with_response(0x00, [0xAA; 8]));
+
+        let ((), completion) = drive
+            .execute_detailed(TestUnitReady::new(0.into()))
+            .unwrap();
+
+        assert_eq!(
+            drive.transport().submitted_cdbs(),
+            [[0x00, 0x00, 0x00, 0x00, 0x00, 0x00]]
+        );
+        assert!(drive.transport().sent_data().is_empty());
+        assert_eq!(completion.transferred, 0);
+        assert_eq!(completion.residual, 0);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::core::{addressing::Lba, util::BitWriter};
+
+use super::{read_cd::MODE1_USER_DATA_SIZE, Command, Control, OpCode, OpCodeDef};
+
+/// Has the Drive verify that blocks were recorded correctly, e.g. after a write, without
+/// transferring them to the Host. Nothing is returned on success; a block that fails
+/// verification ends the command with CHECK CONDITION. That's surfaced as an
+/// [`MMCError`](crate::transport::error::MMCError) such as UNRECOVERED READ ERROR, or, for a
+/// MISCOMPARE DURING VERIFY OPERATION with BYTCHK set, as
+/// [`ScsiError::UnknownSenseData`](crate::transport::sgio::ScsiError::UnknownSenseData).
+///
+/// See MMC-6 §6.49.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verify10 {
+    starting_lba: Lba,
+    verification_length: u16,
+    /// The blocks to compare against when BYTCHK is set.
+    expected: Option<Vec<u8>>,
+    control: Control,
+}
+
+impl Verify10 {
+    /// Verifies `verification_length` blocks from `starting_lba` can be read back from the
+    /// media. A length of zero verifies nothing.
+    pub fn new(starting_lba: Lba, verification_length: u16, control: Control) -> Self {
+        Self {
+            starting_lba,
+            verification_length,
+            expected: None,
+            control,
+        }
+    }
+
+    /// Sets BYTCHK, so that the Drive also compares the blocks against `expected`, which is sent
+    /// with the command. Returns `None` unless `expected` is exactly the verification length in
+    /// 2048 byte blocks.
+    pub fn with_byte_check(mut self, expected: Vec<u8>) -> Option<Self> {
+        if expected.len() != usize::from(self.verification_length) * MODE1_USER_DATA_SIZE {
+            return None;
+        }
+
+        self.expected = Some(expected);
+        Some(self)
+    }
+}
+
+type Verify10OpCode = OpCode<0x2F>;
+
+impl Command<Verify10OpCode> for Verify10 {
+    type Response = ();
+
+    fn as_cdb(&self) -> <Verify10OpCode as OpCodeDef>::Cdb {
+        let starting_lba = i32::from(self.starting_lba).to_be_bytes();
+        let verification_length = self.verification_length.to_be_bytes();
+
+        [
+            Verify10OpCode::OP_CODE,
+            BitWriter::new()
+                .set_bit(0b0000_0010, self.expected.is_some())
+                .into(),
+            starting_lba[0],
+            starting_lba[1],
+            starting_lba[2],
+            starting_lba[3],
+            0,
+            verification_length[0],
+            verification_length[1],
+            self.control.into(),
+        ]
+    }
+
+    /// Nothing is returned, and without BYTCHK nothing is sent either.
+    fn allocation_len(&self) -> usize {
+        0
+    }
+
+    fn parameter_list(&self) -> Option<&[u8]> {
+        self.expected.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_10_cdb() {
+        let verify = Verify10::new(Lba::from(0x012345), 0x0102, 0.into());
+
+        assert_eq!(
+            verify.as_cdb(),
+            [0x2F, 0x00, 0x00, 0x01, 0x23, 0x45, 0x00, 0x01, 0x02, 0x00]
+        );
+        assert_eq!(verify.parameter_list(), None);
+        assert_eq!(verify.allocation_len(), 0);
+
+        let verify = Verify10::new(Lba::from(16), 2, 0.into())
+            .with_byte_check(vec![0; 2 * MODE1_USER_DATA_SIZE])
+            .unwrap();
+
+        assert_eq!(verify.as_cdb()[1], 0x02);
+        assert_eq!(
+            verify.parameter_list().map(<[u8]>::len),
+            Some(2 * MODE1_USER_DATA_SIZE)
+        );
+        assert!(Verify10::new(Lba::from(16), 2, 0.into())
+            .with_byte_check(vec![0; MODE1_USER_DATA_SIZE])
+            .is_none());
+    }
+}
@@ -0,0 +1,15 @@
+use deku::DekuRead;
+
+/// The ability to read CD specific structures (001Eh).
+///
+/// See MMC-6 §5.3.8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+pub struct CdReadDescriptor {
+    /// Digital Audio Play
+    #[deku(bits = 1)]
+    pub dap: bool,
+    #[deku(pad_bits_before = "5", bits = 1)]
+    pub c2_flags: bool,
+    #[deku(bits = 1, pad_bytes_after = "3")]
+    pub cd_text: bool,
+}
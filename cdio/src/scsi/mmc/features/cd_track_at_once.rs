@@ -0,0 +1,63 @@
+use deku::{deku_derive, DekuError};
+
+use super::incremental_streaming_writable::DataBlockTypes;
+
+/// CD Track at Once Feature (002Dh).
+///
+/// See MMC-6 §5.3.22, Table 137.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CdTrackAtOnceDescriptor {
+    /// Buffer Underrun Free recording
+    #[deku(pad_bits_before = "1", bits = 1)]
+    pub buf: bool,
+    #[deku(pad_bits_before = "1", bits = 1)]
+    pub rw_raw: bool,
+    #[deku(bits = 1)]
+    pub rw_pack: bool,
+    #[deku(bits = 1)]
+    pub test_write: bool,
+    /// CD-RW is supported
+    #[deku(bits = 1)]
+    pub cd_rw: bool,
+    /// R-W Sub-code is supported
+    #[deku(bits = 1, pad_bytes_after = "1")]
+    pub rw_subcode: bool,
+    #[deku(
+        endian = "big",
+        map = "|bits: u16| -> Result<_, DekuError> { Ok(DataBlockTypes::from_bits_retain(bits)) }"
+    )]
+    pub data_type_supported: DataBlockTypes,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use deku::{reader::Reader, DekuReader};
+
+    use crate::scsi::mmc::features::{Feature, FeatureData};
+
+    #[test]
+    fn parse_cd_track_at_once() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            0x00, 0x2D, 0x08, 0x04,
+            0x5E, 0x00, 0x3F, 0x0F,
+        ];
+
+        let feature =
+            Feature::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ()).unwrap();
+
+        let FeatureData::CdTrackAtOnce(tao) = feature.feature_data else {
+            panic!("{:?}", feature.feature_data);
+        };
+        assert!(tao.buf && tao.rw_raw && tao.rw_pack && tao.test_write && tao.cd_rw);
+        assert!(!tao.rw_subcode);
+        assert_eq!(tao.data_type_supported.bits(), 0x3F0F);
+        assert_eq!(
+            feature.to_string().split(": ").nth(1),
+            Some("BUF, R-W Raw, R-W Pack, Test Write, CD-RW")
+        );
+    }
+}
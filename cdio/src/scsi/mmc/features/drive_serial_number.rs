@@ -0,0 +1,53 @@
+use deku::{ctx::ByteSize, DekuRead};
+
+/// Drive Serial Number Feature (0108h).
+///
+/// See MMC-6 §5.3.49
+#[derive(Debug, Clone, PartialEq, Eq, Hash, DekuRead)]
+#[deku(ctx = "bytes: ByteSize")]
+pub struct DriveSerialNumberDescriptor {
+    /// The serial number as returned, ASCII padded with spaces to a multiple of 4 bytes.
+    #[deku(bytes_read = "bytes.0")]
+    pub raw: Vec<u8>,
+}
+
+impl DriveSerialNumberDescriptor {
+    /// The serial number without its padding. Some Drives pad with NULs or other bytes that
+    /// aren't ASCII, so any invalid UTF-8 is replaced rather than failing the whole Feature list.
+    pub fn serial_number(&self) -> String {
+        String::from_utf8_lossy(&self.raw)
+            .trim_end_matches([' ', '\0'])
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use deku::{reader::Reader, DekuReader};
+
+    use crate::scsi::mmc::features::{Feature, FeatureData};
+
+    #[test]
+    fn serial_number_with_invalid_utf8() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            0x01, 0x08, 0x01, 0x0C,
+            b'K', b'Z', b'P', b'4', b'1', b'2', b'3', 0xFF,
+            b'9', b' ', b'\0', b'\0',
+        ];
+
+        let feature =
+            Feature::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ()).unwrap();
+
+        let FeatureData::DriveSerialNumber(serial_number) = &feature.feature_data else {
+            panic!("{:?}", feature.feature_data);
+        };
+        assert_eq!(serial_number.serial_number(), "KZP4123\u{FFFD}9");
+        assert_eq!(
+            feature.to_string(),
+            "0108h Drive Serial Number v0 [current]: KZP4123\u{FFFD}9"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use deku::DekuRead;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FirmwareDateError {
+    #[error("Firmware {field} {value:04X}h isn't two ASCII digits")]
+    InvalidDigits { field: &'static str, value: u16 },
+    #[error("Firmware date {0} isn't a valid date and time")]
+    Implausible(String),
+}
+
+/// The creation date of the Drive's firmware (010Ch).
+///
+/// Each field is two ASCII digits, e.g. `0x3136` ("16") for the year 2016 with a century of
+/// `0x3230` ("20").
+///
+/// See MMC-6 §5.3.50
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
+#[deku(endian = "big")]
+pub struct FirmwareInformationDescriptor {
+    pub century: u16,
+    pub year: u16,
+    pub month: u16,
+    pub day: u16,
+    pub hour: u16,
+    pub minute: u16,
+    #[deku(pad_bytes_after = "2")]
+    pub second: u16,
+}
+
+impl FirmwareInformationDescriptor {
+    /// Decodes the ASCII fields, with the century and year combined into the full year.
+    pub fn date_time(&self) -> Result<NaiveDateTime, FirmwareDateError> {
+        let digits = |field: &'static str, value: u16| {
+            let [tens, ones] = value.to_be_bytes();
+            if tens.is_ascii_digit() && ones.is_ascii_digit() {
+                Ok(u32::from(tens - b'0') * 10 + u32::from(ones - b'0'))
+            } else {
+                Err(FirmwareDateError::InvalidDigits { field, value })
+            }
+        };
+
+        let year = digits("century", self.century)? * 100 + digits("year", self.year)?;
+        let month = digits("month", self.month)?;
+        let day = digits("day", self.day)?;
+        let hour = digits("hour", self.hour)?;
+        let minute = digits("minute", self.minute)?;
+        let second = digits("second", self.second)?;
+
+        NaiveDate::from_ymd_opt(year as i32, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, second))
+            .ok_or_else(|| {
+                FirmwareDateError::Implausible(format!(
+                    "{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02}"
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use deku::DekuContainerRead;
+
+    use super::*;
+
+    #[test]
+    fn firmware_date_time() {
+        let (_, descriptor) =
+            FirmwareInformationDescriptor::from_bytes((b"2016031214302200", 0)).unwrap();
+
+        assert_eq!(descriptor.century, 0x3230);
+        assert_eq!(
+            descriptor.date_time().unwrap().to_string(),
+            "2016-03-12 14:30:22"
+        );
+    }
+
+    #[test]
+    fn implausible_firmware_date() {
+        let (_, descriptor) =
+            FirmwareInformationDescriptor::from_bytes((b"2016133214302200", 0)).unwrap();
+        assert_eq!(
+            descriptor.date_time(),
+            Err(FirmwareDateError::Implausible(
+                "2016-13-32 14:30:22".to_string()
+            ))
+        );
+
+        let (_, descriptor) =
+            FirmwareInformationDescriptor::from_bytes((b"20\x01\x06031214302200", 0)).unwrap();
+        assert_eq!(
+            descriptor.date_time(),
+            Err(FirmwareDateError::InvalidDigits {
+                field: "year",
+                value: 0x0106
+            })
+        );
+    }
+}
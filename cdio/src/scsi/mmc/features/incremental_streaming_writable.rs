@@ -0,0 +1,131 @@
+use bitflags::bitflags;
+use deku::{deku_derive, DekuError};
+
+use crate::scsi::mmc::commands::read_cd::{SectorType, SubChannelSelection};
+
+bitflags! {
+    /// The Data Block Types a Drive supports, bit `n` being Data Block Type `n`.
+    ///
+    /// See MMC-6 §5.3.11 and §7.5.4.13, Table 648.
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct DataBlockTypes: u16 {
+        /// 2352 bytes of raw data.
+        const RAW = 1 << 0;
+        /// Raw data followed by 16 bytes of P and Q sub-channel.
+        const PQ_SUBCHANNEL = 1 << 1;
+        /// Raw data followed by 96 bytes of packed P-W sub-channel.
+        const PW_SUBCHANNEL_PACK = 1 << 2;
+        /// Raw data followed by 96 bytes of raw P-W sub-channel.
+        const PW_SUBCHANNEL_RAW = 1 << 3;
+        const MODE1 = 1 << 8;
+        const MODE2 = 1 << 9;
+        const MODE2_XA_FORM1 = 1 << 10;
+        /// Form 1 with its 8 byte subheader.
+        const MODE2_XA_FORM1_SUBHEADER = 1 << 11;
+        const MODE2_XA_FORM2 = 1 << 12;
+        /// Form 1 or Form 2, as given by each sector's subheader.
+        const MODE2_XA_MIXED = 1 << 13;
+    }
+}
+
+impl DataBlockTypes {
+    /// The READ CD Sector Types matching the supported Data Block Types, to check a read mode
+    /// against before issuing READ CD. Only the raw and Mode bits are considered here, the
+    /// sub-channel is reported by [`DataBlockTypes::supported_sub_channels`].
+    pub fn supported_sector_types(self) -> Vec<SectorType> {
+        [
+            (Self::RAW, SectorType::CdDa),
+            (Self::MODE1, SectorType::Mode1),
+            (Self::MODE2, SectorType::Mode2Formless),
+            (
+                Self::MODE2_XA_FORM1 | Self::MODE2_XA_FORM1_SUBHEADER | Self::MODE2_XA_MIXED,
+                SectorType::Mode2Form1,
+            ),
+            (
+                Self::MODE2_XA_FORM2 | Self::MODE2_XA_MIXED,
+                SectorType::Mode2Form2,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(types, sector_type)| self.intersects(types).then_some(sector_type))
+        .collect()
+    }
+
+    /// The READ CD Sub-channel Selections matching the supported Data Block Types with
+    /// sub-channel, not including [`SubChannelSelection::None`].
+    pub fn supported_sub_channels(self) -> Vec<SubChannelSelection> {
+        [
+            (Self::PQ_SUBCHANNEL, SubChannelSelection::QSubChannel),
+            (
+                Self::PW_SUBCHANNEL_PACK | Self::PW_SUBCHANNEL_RAW,
+                SubChannelSelection::RWSubChannel,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(types, sub_channel)| self.intersects(types).then_some(sub_channel))
+        .collect()
+    }
+}
+
+/// Incremental Streaming Writable Feature (0021h).
+///
+/// See MMC-6 §5.3.11, Table 116.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IncrementalStreamingWritableDescriptor {
+    #[deku(
+        endian = "big",
+        map = "|bits: u16| -> Result<_, DekuError> { Ok(DataBlockTypes::from_bits_retain(bits)) }"
+    )]
+    pub data_block_types_supported: DataBlockTypes,
+    /// Track Resources Information
+    #[deku(pad_bits_before = "5", bits = 1)]
+    pub trio: bool,
+    /// Address Reservation
+    #[deku(bits = 1)]
+    pub arsv: bool,
+    /// Buffer Underrun Free recording
+    #[deku(bits = 1)]
+    pub buf: bool,
+
+    #[deku(temp)]
+    _number_of_link_sizes: u8,
+    #[deku(count = "*_number_of_link_sizes")]
+    pub link_sizes: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sector_types_from_mixed_data_block_types() {
+        // Raw with raw P-W, Mode 1, and mixed form XA, as a typical CD-RW drive reports
+        let types = DataBlockTypes::from_bits_retain(0x2109);
+
+        assert_eq!(
+            types.supported_sector_types(),
+            [
+                SectorType::CdDa,
+                SectorType::Mode1,
+                SectorType::Mode2Form1,
+                SectorType::Mode2Form2,
+            ]
+        );
+        assert_eq!(
+            types.supported_sub_channels(),
+            [SubChannelSelection::RWSubChannel]
+        );
+
+        let form_1_only = DataBlockTypes::MODE2_XA_FORM1_SUBHEADER | DataBlockTypes::PQ_SUBCHANNEL;
+        assert_eq!(
+            form_1_only.supported_sector_types(),
+            [SectorType::Mode2Form1]
+        );
+        assert_eq!(
+            form_1_only.supported_sub_channels(),
+            [SubChannelSelection::QSubChannel]
+        );
+    }
+}
@@ -1,26 +1,33 @@
-use deku::{ctx::ByteSize, deku_derive, DekuRead};
+use std::{fmt, io::Cursor};
 
+use deku::{ctx::ByteSize, deku_derive, reader::Reader, DekuError, DekuRead, DekuReader};
+
+pub mod cd_read;
+pub mod cd_track_at_once;
 pub mod core_feature;
+pub mod drive_serial_number;
+pub mod firmware_information;
+pub mod incremental_streaming_writable;
+pub mod ossc;
 pub mod profile_list;
 
 #[deku_derive(DekuRead)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Feature {
-    #[deku(temp, endian = "big")]
-    _feature_code: u16,
-    #[deku(temp, pad_bits_before = "2", bits = 4)]
-    _version: u8,
+    #[deku(endian = "big")]
+    pub feature_code: u16,
+    #[deku(pad_bits_before = "2", bits = 4)]
+    pub version: u8,
     #[deku(bits = 1)]
-    persistent: bool,
+    pub persistent: bool,
     #[deku(bits = 1)]
-    current: bool,
+    pub current: bool,
     #[deku(temp)]
     _additional_length: u8,
     #[deku(
-        bytes = "*_additional_length as usize",
-        ctx = "*_feature_code, *_version"
+        reader = "FeatureData::read(deku::reader, *_additional_length, *feature_code, *version)"
     )]
-    feature_data: FeatureData,
+    pub feature_data: FeatureData,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, DekuRead)]
@@ -33,12 +40,262 @@ pub enum FeatureData {
     ProfileList(#[deku(bytes_read = "bytes.0")] Vec<profile_list::ProfileDescriptor>),
     #[deku(id = "0x0001")]
     Core(#[deku(ctx = "version")] core_feature::CoreDescriptor),
+    #[deku(id = "0x001E")]
+    CdRead(cd_read::CdReadDescriptor),
+    #[deku(id = "0x0021")]
+    IncrementalStreamingWritable(
+        incremental_streaming_writable::IncrementalStreamingWritableDescriptor,
+    ),
+    #[deku(id = "0x002D")]
+    CdTrackAtOnce(cd_track_at_once::CdTrackAtOnceDescriptor),
+    #[deku(id = "0x0108")]
+    DriveSerialNumber(#[deku(ctx = "bytes")] drive_serial_number::DriveSerialNumberDescriptor),
+    #[deku(id = "0x010C")]
+    FirmwareInformation(firmware_information::FirmwareInformationDescriptor),
+    #[deku(id = "0x0142")]
+    Ossc(ossc::OsscDescriptor),
+    /// A Feature that isn't modeled (yet), or is vendor specific.
+    #[deku(id_pat = "_")]
+    Unknown(#[deku(bytes_read = "bytes.0")] Vec<u8>),
+}
+
+impl FeatureData {
+    /// Reads exactly `additional_length` bytes of feature dependent data, so a descriptor that's
+    /// longer than what's modeled for its version can't misalign the descriptors following it.
+    fn read<R: deku::no_std_io::Read + deku::no_std_io::Seek>(
+        reader: &mut Reader<R>,
+        additional_length: u8,
+        feature_code: u16,
+        version: u8,
+    ) -> Result<Self, DekuError> {
+        let expected = usize::from(additional_length);
+
+        let mut data = Vec::with_capacity(expected);
+        while data.len() < expected && !reader.end() {
+            data.push(u8::from_reader_with_ctx(reader, ())?);
+        }
+
+        if data.len() < expected {
+            return Err(DekuError::Parse(
+                format!(
+                    "Feature {feature_code:04X}h specified {expected} bytes of feature data, received {}",
+                    data.len()
+                )
+                .into(),
+            ));
+        }
+
+        Self::from_reader_with_ctx(
+            &mut Reader::new(Cursor::new(&data)),
+            (ByteSize(data.len()), feature_code, version),
+        )
+    }
+}
+
+impl FeatureData {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ProfileList(_) => "Profile List",
+            Self::Core(_) => "Core",
+            Self::CdRead(_) => "CD Read",
+            Self::IncrementalStreamingWritable(_) => "Incremental Streaming Writable",
+            Self::CdTrackAtOnce(_) => "CD Track at Once",
+            Self::DriveSerialNumber(_) => "Drive Serial Number",
+            Self::FirmwareInformation(_) => "Firmware Information",
+            Self::Ossc(_) => "TCG Optical Security Subsystem Class",
+            Self::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+/// A concise summary of the notable parts of the feature data, e.g. the flags that are set.
+impl fmt::Display for FeatureData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn flags(f: &mut fmt::Formatter<'_>, flags: &[(&str, bool)]) -> fmt::Result {
+            let set: Vec<&str> = flags
+                .iter()
+                .filter_map(|&(name, set)| set.then_some(name))
+                .collect();
+            write!(f, "{}", set.join(", "))
+        }
+
+        match self {
+            Self::ProfileList(profiles) => {
+                let profiles: Vec<String> = profiles.iter().map(ToString::to_string).collect();
+                write!(f, "{}", profiles.join(", "))
+            }
+            Self::Core(core) => match core {
+                core_feature::CoreDescriptor::V0 {
+                    physical_interface_standard,
+                } => write!(f, "{physical_interface_standard:?}"),
+                core_feature::CoreDescriptor::V1 {
+                    physical_interface_standard,
+                    device_busy_event,
+                } => {
+                    write!(f, "{physical_interface_standard:?}")?;
+                    if *device_busy_event {
+                        write!(f, ", DBE")?;
+                    }
+                    Ok(())
+                }
+                core_feature::CoreDescriptor::V2 {
+                    physical_interface_standard,
+                    inq2,
+                    device_busy_event,
+                } => {
+                    write!(f, "{physical_interface_standard:?}")?;
+                    if *inq2 || *device_busy_event {
+                        write!(f, ", ")?;
+                    }
+                    flags(f, &[("INQ2", *inq2), ("DBE", *device_busy_event)])
+                }
+            },
+            Self::CdRead(cd_read) => flags(
+                f,
+                &[
+                    ("DAP", cd_read.dap),
+                    ("C2 Flags", cd_read.c2_flags),
+                    ("CD-Text", cd_read.cd_text),
+                ],
+            ),
+            Self::IncrementalStreamingWritable(incremental) => flags(
+                f,
+                &[
+                    ("TRIO", incremental.trio),
+                    ("ARSV", incremental.arsv),
+                    ("BUF", incremental.buf),
+                ],
+            ),
+            Self::CdTrackAtOnce(tao) => flags(
+                f,
+                &[
+                    ("BUF", tao.buf),
+                    ("R-W Raw", tao.rw_raw),
+                    ("R-W Pack", tao.rw_pack),
+                    ("Test Write", tao.test_write),
+                    ("CD-RW", tao.cd_rw),
+                    ("R-W Subcode", tao.rw_subcode),
+                ],
+            ),
+            Self::DriveSerialNumber(serial_number) => {
+                write!(f, "{}", serial_number.serial_number())
+            }
+            Self::FirmwareInformation(firmware_information) => {
+                match firmware_information.date_time() {
+                    Ok(date_time) => write!(f, "{date_time}"),
+                    Err(_) => write!(f, "Invalid date"),
+                }
+            }
+            Self::Ossc(ossc) => flags(
+                f,
+                &[("PSAU", ossc.psau), ("LOSPB", ossc.lospb), ("ME", ossc.me)],
+            ),
+            Self::Unknown(data) => write!(f, "{} bytes", data.len()),
+        }
+    }
 }
 
-struct FeatureDescriptor<'a> {
-    feature_code: u16,
-    version: u8,
-    persistent: bool,
-    current: bool,
-    feature_dependent_data: &'a [u8],
+/// Renders one line per Feature, e.g. `001Eh CD Read v2 [current, persistent]: C2 Flags, CD-Text`
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04X}h {} v{}",
+            self.feature_code,
+            self.feature_data.name(),
+            self.version
+        )?;
+
+        match (self.current, self.persistent) {
+            (true, true) => write!(f, " [current, persistent]")?,
+            (true, false) => write!(f, " [current]")?,
+            (false, true) => write!(f, " [persistent]")?,
+            (false, false) => {}
+        }
+
+        let data = self.feature_data.to_string();
+        if !data.is_empty() {
+            write!(f, ": {data}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_features(bytes: &[u8]) -> Result<Vec<Feature>, DekuError> {
+        let mut reader = Reader::new(Cursor::new(bytes));
+        let mut features = Vec::new();
+        while !reader.end() {
+            features.push(Feature::from_reader_with_ctx(&mut reader, ())?);
+        }
+        Ok(features)
+    }
+
+    #[test]
+    fn oversized_descriptor_does_not_misalign_next() {
+        #[rustfmt::skip]
+        let bytes = [
+            &[0x00, 0x01, 0x0B, 0xFF][..],
+            &[0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00],
+            &[0xAA; 0xFF - 8],
+            // Vendor specific
+            &[0xFF, 0x00, 0x01, 0x04, 0x01, 0x02, 0x03, 0x04],
+        ]
+        .concat();
+
+        let features = parse_features(&bytes).unwrap();
+
+        assert_eq!(features.len(), 2);
+        assert!(matches!(features[0].feature_data, FeatureData::Core(_)));
+        assert_eq!(features[1].feature_code, 0xFF00);
+        assert_eq!(
+            features[1].feature_data,
+            FeatureData::Unknown(vec![0x01, 0x02, 0x03, 0x04])
+        );
+    }
+
+    #[test]
+    fn additional_length_past_end_is_error() {
+        let bytes = [0xFF, 0x00, 0x01, 0xFF, 0x01, 0x02, 0x03, 0x04];
+
+        assert!(matches!(
+            parse_features(&bytes),
+            Err(DekuError::Parse(message)) if message.ends_with("received 4")
+        ));
+    }
+
+    #[test]
+    fn display_core_and_cd_read() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x00, 0x01, 0x0B, 0x08, 0x00, 0x00, 0x00, 0x07, 0x03, 0x00, 0x00, 0x00,
+            0x00, 0x1E, 0x09, 0x04, 0x03, 0x00, 0x00, 0x00,
+        ];
+
+        let features = parse_features(&bytes).unwrap();
+
+        assert_eq!(
+            features[0].to_string(),
+            "0001h Core v2 [current, persistent]: SerialAtapi, INQ2, DBE"
+        );
+        assert_eq!(
+            features[1].to_string(),
+            "001Eh CD Read v2 [current]: C2 Flags, CD-Text"
+        );
+    }
+
+    #[test]
+    fn header_only_descriptor_reports_missing_data() {
+        let bytes = [0x00, 0x1E, 0x09, 0x0A];
+
+        assert!(matches!(
+            parse_features(&bytes),
+            Err(DekuError::Parse(message))
+                if message == "Feature 001Eh specified 10 bytes of feature data, received 0"
+        ));
+    }
 }
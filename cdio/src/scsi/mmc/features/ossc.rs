@@ -0,0 +1,57 @@
+use deku::deku_derive;
+
+/// TCG Optical Security Subsystem Class Feature (0142h).
+///
+/// See MMC-6 §5.3.66, Table 219.
+#[deku_derive(DekuRead)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OsscDescriptor {
+    /// Pseudo-Sessions Alternate Uses
+    #[deku(bits = 1)]
+    pub psau: bool,
+    /// Locking Of Sessions Per Band
+    #[deku(bits = 1)]
+    pub lospb: bool,
+    /// Mixed-Mode
+    #[deku(pad_bits_before = "5", bits = 1)]
+    pub me: bool,
+
+    #[deku(temp)]
+    _number_of_profiles: u8,
+    /// The TCG SSC Profiles supported by the Drive.
+    #[deku(count = "*_number_of_profiles", endian = "big")]
+    pub profile_numbers: Vec<u16>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use deku::{reader::Reader, DekuReader};
+
+    use crate::scsi::mmc::features::{Feature, FeatureData};
+
+    use super::*;
+
+    #[test]
+    fn parse_ossc_profile_numbers() {
+        #[rustfmt::skip]
+        let bytes: &[u8] = &[
+            0x01, 0x42, 0x00, 0x08,
+            0x81, 0x02, 0x00, 0x01, 0x01, 0x02, 0x00, 0x00,
+        ];
+
+        let feature =
+            Feature::from_reader_with_ctx(&mut Reader::new(Cursor::new(bytes)), ()).unwrap();
+
+        assert_eq!(
+            feature.feature_data,
+            FeatureData::Ossc(OsscDescriptor {
+                psau: true,
+                lospb: false,
+                me: true,
+                profile_numbers: vec![0x0001, 0x0102],
+            })
+        );
+    }
+}
@@ -1,12 +1,24 @@
+use std::fmt;
+
 use deku::DekuRead;
 
 // Only one version (0b0000), all future versions will be backwards compatible so no versioning needed
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DekuRead)]
 pub struct ProfileDescriptor {
-    profile_number: Profile, // Not sure if it would be beneficial to make this an enum at some point like in the old impl
+    pub profile_number: Profile, // Not sure if it would be beneficial to make this an enum at some point like in the old impl
     #[deku(pad_bits_before = "7", bits = 1, pad_bytes_after = "1")]
-    current_profile: bool,
+    pub current_profile: bool,
+}
+
+impl fmt::Display for ProfileDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.profile_number)?;
+        if self.current_profile {
+            write!(f, " (current)")?;
+        }
+        Ok(())
+    }
 }
 
 /// A 16-bit value representing a Drive Profile.
@@ -160,3 +172,131 @@ pub enum Profile {
     #[deku(id_pat = "_")]
     Reserved(u16) = 0x0000,
 }
+
+/// The family of media a [`Profile`] belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaFamily {
+    /// Compact Disc, including Double Density CD.
+    Cd,
+    Dvd,
+    /// Blu-ray Disc
+    Bd,
+    HdDvd,
+    /// Magneto-Optical and the other legacy optical disk cartridges.
+    Mo,
+    /// Not optical disc media, a non-conforming Drive, or a reserved Profile Number.
+    Unknown,
+}
+
+impl Profile {
+    pub fn media_family(&self) -> MediaFamily {
+        match self {
+            Self::CdRom | Self::CdR | Self::CdRw | Self::DdcdRom | Self::DdcdR | Self::DdcdRw => {
+                MediaFamily::Cd
+            }
+            Self::DvdRom
+            | Self::DvdRSequentialRecording
+            | Self::DvdRam
+            | Self::DvdRwRestrictedOverwrite
+            | Self::DvdRwSequentialRecording
+            | Self::DvdRDualLayerSequentialRecording
+            | Self::DvdRDualLayerJumpRecording
+            | Self::DvdRwDualLayer
+            | Self::DvdDownloadDiscRecording
+            | Self::DvdPlusRw
+            | Self::DvdPlusR
+            | Self::DvdPlusRwDualLayer
+            | Self::DvdPlusRDualLayer => MediaFamily::Dvd,
+            Self::BdRom | Self::BdRSrm | Self::BdRRrm | Self::BdRe => MediaFamily::Bd,
+            Self::HdDvdRom
+            | Self::HdDvdR
+            | Self::HdDvdRam
+            | Self::HdDvdRw
+            | Self::HdDvdRDualLayer
+            | Self::HdDvdRwDualLayer => MediaFamily::HdDvd,
+            Self::MoErasable | Self::OpticalWriteOnce | Self::AsMo => MediaFamily::Mo,
+            Self::NonRemovableDisk
+            | Self::RemovableDisk
+            | Self::NonConforming
+            | Self::Reserved(_) => MediaFamily::Unknown,
+        }
+    }
+
+    /// Whether the Profile's media can be written to, either once or repeatedly.
+    pub fn is_writable(&self) -> bool {
+        !matches!(
+            self,
+            Self::CdRom
+                | Self::DvdRom
+                | Self::DdcdRom
+                | Self::BdRom
+                | Self::HdDvdRom
+                | Self::NonConforming
+                | Self::Reserved(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, io::Cursor};
+
+    use deku::{reader::Reader, DekuReader};
+
+    use super::*;
+    use crate::scsi::mmc::features::{Feature, FeatureData};
+
+    #[test]
+    fn profile_list_feature_parses_every_profile() {
+        // Through GET CONFIGURATION's feature parser, a Profile List with a single descriptor
+        let parse_feature = |number: u16| {
+            let [high, low] = number.to_be_bytes();
+            let feature = Feature::from_reader_with_ctx(
+                &mut Reader::new(Cursor::new(&[
+                    0x00, 0x00, 0x03, 0x04, high, low, 0x01, 0x00,
+                ])),
+                (),
+            )
+            .unwrap();
+            match feature.feature_data {
+                FeatureData::ProfileList(descriptors) => descriptors[0].profile_number,
+                data => panic!("{number:04X}h parsed as {data:?}"),
+            }
+        };
+
+        // MMC-6 reserves everything from 0100h to FFFEh
+        let mut named = HashSet::new();
+        for number in (0x0000..=0x00FF_u16).chain([0xFFFF]) {
+            let profile = Profile::from_reader_with_ctx(
+                &mut Reader::new(Cursor::new(&number.to_be_bytes())),
+                (),
+            )
+            .unwrap();
+            assert_eq!(parse_feature(number), profile, "{number:04X}h");
+
+            match profile {
+                Profile::Reserved(reserved) => assert_eq!(reserved, number),
+                // Each named Profile has exactly one Profile Number
+                profile => assert!(named.insert(profile), "{number:04X}h"),
+            }
+        }
+
+        assert!(named.contains(&crate::scsi::mmc::types::Profile::NonRemovableDisk));
+    }
+
+    #[test]
+    fn profile_media_family_and_writability() {
+        assert_eq!(Profile::CdR.media_family(), MediaFamily::Cd);
+        assert!(Profile::CdR.is_writable());
+
+        assert_eq!(Profile::BdRom.media_family(), MediaFamily::Bd);
+        assert!(!Profile::BdRom.is_writable());
+
+        assert_eq!(Profile::HdDvdRam.media_family(), MediaFamily::HdDvd);
+        assert_eq!(
+            Profile::Reserved(0x0060).media_family(),
+            MediaFamily::Unknown
+        );
+        assert!(!Profile::Reserved(0x0060).is_writable());
+    }
+}
@@ -1,5 +1,10 @@
+use std::fmt;
+
 use derive_more::Debug;
 
+// The one Profile type, alongside FeatureCode, parsed wherever a Profile Number is reported
+pub use super::features::profile_list::Profile;
+
 pub mod spc {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u8)]
@@ -42,6 +47,12 @@ pub mod spc {
     }
 }
 
+/// Identifies a Feature reported by GET CONFIGURATION.
+///
+/// This is the only compiled definition. `features_old.rs` still assigns codes of its own through
+/// `impl_feature!`, but it isn't declared as a module, so nothing can diverge from these.
+///
+/// See MMC-6 §5.3.
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FeatureCode {
@@ -169,6 +180,8 @@ pub enum LoadingMechanism {
     PopUp = 0b010,
     EmbeddedIndividuallyChangeable = 0b100,
     EmbeddedMagazine = 0b101,
+    /// 011b, 110b and 111b are reserved. Some slot loading Drives report 011b regardless, these
+    /// are left as reserved rather than guessing at what the vendor meant.
     Reserved(u8),
 }
 
@@ -184,3 +197,47 @@ impl From<u8> for LoadingMechanism {
         }
     }
 }
+
+impl fmt::Display for LoadingMechanism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CaddySlot => write!(f, "Caddy/Slot"),
+            Self::Tray => write!(f, "Tray"),
+            Self::PopUp => write!(f, "Pop-up"),
+            Self::EmbeddedIndividuallyChangeable => {
+                write!(f, "Embedded changer with individually changeable discs")
+            }
+            Self::EmbeddedMagazine => write!(f, "Embedded changer using a magazine"),
+            Self::Reserved(v) => write!(f, "Reserved ({v:03b}b)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loading_mechanism_from_every_value() {
+        let expected = [
+            LoadingMechanism::CaddySlot,
+            LoadingMechanism::Tray,
+            LoadingMechanism::PopUp,
+            LoadingMechanism::Reserved(0b011),
+            LoadingMechanism::EmbeddedIndividuallyChangeable,
+            LoadingMechanism::EmbeddedMagazine,
+            LoadingMechanism::Reserved(0b110),
+            LoadingMechanism::Reserved(0b111),
+        ];
+
+        for (value, loading_mechanism) in (0u8..8).zip(expected) {
+            assert_eq!(LoadingMechanism::from(value), loading_mechanism);
+        }
+
+        assert_eq!(LoadingMechanism::CaddySlot.to_string(), "Caddy/Slot");
+        assert_eq!(
+            LoadingMechanism::Reserved(0b011).to_string(),
+            "Reserved (011b)"
+        );
+    }
+}
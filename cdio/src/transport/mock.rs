@@ -0,0 +1,163 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+use super::{
+    error::{CDBOrParameterValidationError, MMCError},
+    sgio::{DxferDirection, ScsiError, StatusCondition},
+    Completion, Transport,
+};
+
+/// A single queued answer to a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MockResponse {
+    Data(Vec<u8>),
+    /// CHECK CONDITION with the Sense Key, ASC, and ASCQ.
+    Sense {
+        sk: u8,
+        asc: u8,
+        ascq: u8,
+    },
+}
+
+/// A [`Transport`] that answers commands with canned response bytes keyed by OPERATION CODE.
+///
+/// Commands without a canned response fail the way a real Drive would, with INVALID COMMAND
+/// OPERATION CODE.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: HashMap<u8, Vec<u8>>,
+    queued: Mutex<HashMap<u8, VecDeque<MockResponse>>>,
+    submitted: Mutex<Vec<Vec<u8>>>,
+    buffer_lens: Mutex<Vec<usize>>,
+    sent: Mutex<Vec<Vec<u8>>>,
+    durations: Mutex<VecDeque<Duration>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Responds to every command with `op_code` with `bytes`, truncated to the size of the
+    /// caller's buffer.
+    pub fn with_response(mut self, op_code: u8, bytes: impl Into<Vec<u8>>) -> Self {
+        self.responses.insert(op_code, bytes.into());
+        self
+    }
+
+    /// Responds to the next command with `op_code` with `bytes`. Queued responses are used up
+    /// in order before falling back to the one from [`MockTransport::with_response`].
+    pub fn with_queued_response(self, op_code: u8, bytes: impl Into<Vec<u8>>) -> Self {
+        self.queue(op_code, MockResponse::Data(bytes.into()))
+    }
+
+    /// Fails the next command with `op_code` with CHECK CONDITION and the given sense data, see
+    /// [`MockTransport::with_queued_response`].
+    pub fn with_queued_sense(self, op_code: u8, sk: u8, asc: u8, ascq: u8) -> Self {
+        self.queue(op_code, MockResponse::Sense { sk, asc, ascq })
+    }
+
+    /// Reports `durations` from [`Transport::submit_detailed`] for the next commands, one each in
+    /// order. Any command after them takes no time.
+    pub fn with_durations(self, durations: impl IntoIterator<Item = Duration>) -> Self {
+        self.durations.lock().unwrap().extend(durations);
+        self
+    }
+
+    fn queue(self, op_code: u8, response: MockResponse) -> Self {
+        self.queued
+            .lock()
+            .unwrap()
+            .entry(op_code)
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Every CDB submitted so far, in order.
+    pub fn submitted_cdbs(&self) -> Vec<Vec<u8>> {
+        self.submitted.lock().unwrap().clone()
+    }
+
+    /// The length of every submitted command's data buffer so far, in order. Commands without
+    /// data are submitted with an empty one.
+    pub fn buffer_lens(&self) -> Vec<usize> {
+        self.buffer_lens.lock().unwrap().clone()
+    }
+
+    /// Every buffer transferred to the Drive so far (e.g. parameter lists), in order.
+    pub fn sent_data(&self) -> Vec<Vec<u8>> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn submit(
+        &self,
+        cdb: &[u8],
+        dir: DxferDirection,
+        buf: &mut [u8],
+        _timeout: Duration,
+    ) -> Result<usize, ScsiError> {
+        self.submitted.lock().unwrap().push(cdb.to_vec());
+        self.buffer_lens.lock().unwrap().push(buf.len());
+        if matches!(dir, DxferDirection::ToDev) {
+            self.sent.lock().unwrap().push(buf.to_vec());
+        }
+
+        let op_code = cdb.first().copied();
+        let queued = op_code.and_then(|op_code| {
+            self.queued
+                .lock()
+                .unwrap()
+                .get_mut(&op_code)
+                .and_then(VecDeque::pop_front)
+        });
+
+        let response = match queued {
+            Some(MockResponse::Data(bytes)) => bytes,
+            Some(MockResponse::Sense { sk, asc, ascq }) => {
+                return Err(match MMCError::from_codes(sk, asc, ascq) {
+                    Some(mmc_error) => ScsiError::MMCError(mmc_error),
+                    None => ScsiError::UnknownSenseData {
+                        status: StatusCondition::CheckCondition,
+                        sk,
+                        asc,
+                        ascq,
+                    },
+                });
+            }
+            None => op_code
+                .and_then(|op_code| self.responses.get(&op_code))
+                .cloned()
+                .ok_or(ScsiError::MMCError(
+                    MMCError::CDBOrParameterValidationError(
+                        CDBOrParameterValidationError::InvalidCommandOperationCode,
+                    ),
+                ))?,
+        };
+
+        let len = response.len().min(buf.len());
+        buf[..len].copy_from_slice(&response[..len]);
+        Ok(len)
+    }
+
+    fn submit_detailed(
+        &self,
+        cdb: &[u8],
+        dir: DxferDirection,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> Result<Completion, ScsiError> {
+        let duration = self.durations.lock().unwrap().pop_front();
+        let transferred = self.submit(cdb, dir, buf, timeout)?;
+        Ok(Completion {
+            transferred,
+            residual: buf.len() - transferred,
+            duration: duration.unwrap_or_default(),
+        })
+    }
+}
@@ -1,2 +1,55 @@
 pub mod error;
+pub mod mock;
+pub mod sense;
 pub mod sgio;
+
+use std::time::{Duration, Instant};
+
+use sgio::{DxferDirection, ScsiError};
+
+/// What a [`Transport`] reports about a command it completed successfully.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Completion {
+    /// The number of bytes transferred to/from the buffer.
+    pub transferred: usize,
+    /// The number of bytes of the buffer left untransferred, e.g. when the Drive returned less
+    /// data than was allocated for it.
+    pub residual: usize,
+    pub duration: Duration,
+}
+
+/// A means of delivering a CDB to a Drive and collecting the data it transfers back.
+///
+/// This is the only place commands touch hardware, so swapping the implementation (e.g. for a
+/// [`mock::MockTransport`]) lets everything above it be exercised without a real device.
+pub trait Transport {
+    /// Submits `cdb` to the Drive. Data is transferred to/from `buf` according to `dir`, and the
+    /// number of bytes actually transferred is returned. The command is aborted if the Drive
+    /// hasn't completed it within `timeout`.
+    fn submit(
+        &self,
+        cdb: &[u8],
+        dir: DxferDirection,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, ScsiError>;
+
+    /// Like [`Transport::submit`], also returning the residual and how long the command took. By
+    /// default it's timed on the Host, so it includes the time spent getting the command to the
+    /// Drive.
+    fn submit_detailed(
+        &self,
+        cdb: &[u8],
+        dir: DxferDirection,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> Result<Completion, ScsiError> {
+        let start = Instant::now();
+        let transferred = self.submit(cdb, dir, buf, timeout)?;
+        Ok(Completion {
+            transferred,
+            residual: buf.len().saturating_sub(transferred),
+            duration: start.elapsed(),
+        })
+    }
+}
@@ -0,0 +1,182 @@
+//! Sense data returned with CHECK CONDITION, in either of the formats from SPC-4 §4.5
+
+use crate::core::util::DescriptorCursor;
+
+use super::error::MMCError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SenseFormat {
+    /// Response code 70h (current) or 71h (deferred).
+    Fixed,
+    /// Response code 72h (current) or 73h (deferred), common from SAT and USB bridges.
+    Descriptor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SenseData {
+    pub format: SenseFormat,
+    /// Whether the error is for a previous command rather than the one that returned it.
+    pub deferred: bool,
+    pub sense_key: u8,
+    pub asc: u8,
+    pub ascq: u8,
+    /// Depends on the command, e.g. the LBA of the sector that couldn't be read. Only present
+    /// when the Drive marks it valid.
+    pub information: Option<u64>,
+    /// Also depends on the command, `None` if the Drive reported none (or all zeros).
+    pub command_specific_information: Option<u64>,
+}
+
+impl SenseData {
+    /// Descriptor type of the Information sense data descriptor.
+    const INFORMATION_DESCRIPTOR: u8 = 0x00;
+    /// Descriptor type of the Command-specific information sense data descriptor.
+    const COMMAND_SPECIFIC_DESCRIPTOR: u8 = 0x01;
+
+    /// Parses the sense data written by the Drive, returning `None` if the response code isn't
+    /// one of the known formats or the data is too short to hold the ASC and ASCQ.
+    pub fn parse(sense: &[u8]) -> Option<Self> {
+        let response_code = sense.first()? & 0x7F;
+        let deferred = matches!(response_code, 0x71 | 0x73);
+
+        match response_code {
+            0x70 | 0x71 => Some(Self {
+                format: SenseFormat::Fixed,
+                deferred,
+                sense_key: sense.get(2)? & 0x0F,
+                asc: *sense.get(12)?,
+                ascq: *sense.get(13)?,
+                // The VALID bit only covers the INFORMATION field
+                information: (sense[0] & 0x80 != 0)
+                    .then(|| be_u64(sense.get(3..7)?))
+                    .flatten(),
+                command_specific_information: sense
+                    .get(8..12)
+                    .and_then(be_u64)
+                    .filter(|&information| information != 0),
+            }),
+            0x72 | 0x73 => {
+                let mut sense_data = Self {
+                    format: SenseFormat::Descriptor,
+                    deferred,
+                    sense_key: sense.get(1)? & 0x0F,
+                    asc: *sense.get(2)?,
+                    ascq: *sense.get(3)?,
+                    information: None,
+                    command_specific_information: None,
+                };
+
+                let additional_length = sense.get(7).copied().map_or(0, usize::from);
+                let end = sense.len().min(8 + additional_length);
+                let descriptors = sense.get(8..end).unwrap_or_default();
+                for descriptor in DescriptorCursor::length_prefixed(descriptors, 1, 2) {
+                    match descriptor[0] {
+                        Self::INFORMATION_DESCRIPTOR
+                            if descriptor.get(2).is_some_and(|byte| byte & 0x80 != 0) =>
+                        {
+                            sense_data.information = descriptor.get(4..12).and_then(be_u64);
+                        }
+                        Self::COMMAND_SPECIFIC_DESCRIPTOR => {
+                            sense_data.command_specific_information =
+                                descriptor.get(4..12).and_then(be_u64);
+                        }
+                        _ => {}
+                    }
+                }
+
+                Some(sense_data)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn mmc_error(&self) -> Option<MMCError> {
+        MMCError::from_codes(self.sense_key, self.asc, self.ascq)
+    }
+}
+
+fn be_u64(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() > 8 {
+        return None;
+    }
+
+    Some(
+        bytes
+            .iter()
+            .fold(0, |value, &byte| (value << 8) | u64::from(byte)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::error::ReadingError;
+
+    use super::*;
+
+    #[test]
+    fn fixed_format() {
+        // UNRECOVERED READ ERROR at LBA 0x1234
+        #[rustfmt::skip]
+        let sense = [
+            0xF0, 0x00, 0x03, 0x00, 0x00, 0x12, 0x34, 0x0A,
+            0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+
+        let sense_data = SenseData::parse(&sense).unwrap();
+
+        assert_eq!(sense_data.format, SenseFormat::Fixed);
+        assert!(!sense_data.deferred);
+        assert_eq!(
+            (sense_data.sense_key, sense_data.asc, sense_data.ascq),
+            (0x03, 0x11, 0x00)
+        );
+        assert_eq!(sense_data.information, Some(0x1234));
+        assert_eq!(sense_data.command_specific_information, None);
+        assert!(matches!(
+            sense_data.mmc_error(),
+            Some(MMCError::ReadingError(ReadingError::UnrecoveredReadError))
+        ));
+
+        // Only as much as the Drive wrote is considered
+        assert_eq!(SenseData::parse(&sense[..8]), None);
+    }
+
+    #[test]
+    fn descriptor_format() {
+        // UNRECOVERED READ ERROR at LBA 0x1234, with a Command-specific information descriptor
+        #[rustfmt::skip]
+        let sense = [
+            0x72, 0x03, 0x11, 0x00, 0x00, 0x00, 0x00, 0x18,
+            0x00, 0x0A, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12, 0x34,
+            0x01, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07,
+        ];
+
+        let sense_data = SenseData::parse(&sense).unwrap();
+
+        assert_eq!(sense_data.format, SenseFormat::Descriptor);
+        assert_eq!(
+            (sense_data.sense_key, sense_data.asc, sense_data.ascq),
+            (0x03, 0x11, 0x00)
+        );
+        assert_eq!(sense_data.information, Some(0x1234));
+        assert_eq!(sense_data.command_specific_information, Some(0x07));
+
+        // A deferred error with a descriptor cut off by the sense buffer
+        let sense_data =
+            SenseData::parse(&[0x73, 0x05, 0x21, 0x00, 0, 0, 0, 0x0C, 0x00, 0x0A]).unwrap();
+        assert!(sense_data.deferred);
+        assert_eq!(sense_data.information, None);
+
+        assert_eq!(SenseData::parse(&[0x73, 0x05]), None);
+        assert_eq!(SenseData::parse(&[]), None);
+    }
+
+    #[test]
+    fn truncated_information_descriptor() {
+        // An Information descriptor with no room for its VALID bit, let alone the INFORMATION
+        let sense_data = SenseData::parse(&[0x72, 0x03, 0x11, 0x00, 0, 0, 0, 0x02, 0x00, 0x00]);
+
+        assert_eq!(sense_data.unwrap().information, None);
+    }
+}
@@ -1,13 +1,20 @@
 use std::{
     ffi::{c_uchar, c_void},
-    ptr,
+    os::fd::{AsRawFd, OwnedFd},
+    ptr, thread,
+    time::Duration,
 };
 
-use nix::ioctl_read_bad;
+use nix::{
+    errno::Errno,
+    fcntl::{open, OFlag},
+    ioctl_read_bad,
+    sys::stat::Mode,
+};
 use num_enum::TryFromPrimitive;
 use thiserror::Error;
 
-use super::error::MMCError;
+use super::{error::MMCError, sense::SenseData, Completion, Transport};
 
 #[derive(Debug, Error)]
 pub enum ScsiError {
@@ -16,9 +23,16 @@ pub enum ScsiError {
     #[error("Data must be < 2^32 bytes in length, received: {0}")]
     InvalidData(usize),
     #[error("Syscall to ioctl failed")]
-    IOCTLFailed(#[from] nix::errno::Errno),
-    #[error("Residual must be non-negative and < allocation ({allocated}), received: {resid}")]
+    IOCTLFailed(#[from] Errno),
+    #[error("Failed to open the device node")]
+    Open(#[from] OpenError),
+    #[error("Residual must be non-negative and <= allocation ({allocated}), received: {resid}")]
     InvalidResidual { resid: i32, allocated: u32 },
+    #[error("Drive transferred {transferred} of the {allocated} bytes allocated")]
+    Underrun {
+        transferred: usize,
+        allocated: usize,
+    },
     #[error("SG IO failed with status code `{_0:?}`")]
     BadStatus(StatusCondition),
     #[error("MMC Error: {0:?}")]
@@ -36,10 +50,76 @@ pub enum ScsiError {
     },
 }
 
+/// How [`run_sgio`] treats a Drive transferring less data than was allocated for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ResidualPolicy {
+    /// Returns however much was transferred. Plenty of commands legitimately return less than
+    /// the allocation length, e.g. GET CONFIGURATION with a generous buffer.
+    #[default]
+    AllowUnderrun,
+    /// Fails with [`ScsiError::Underrun`] unless the whole buffer was transferred.
+    Strict,
+}
+
+impl ResidualPolicy {
+    /// The number of bytes transferred into an `allocation_len` byte buffer, given the residual
+    /// the kernel reported.
+    fn transferred_len(self, allocation_len: usize, resid: i32) -> Result<usize, ScsiError> {
+        // From the SCSI HOWTO: "In practice it only reports underruns (i.e. positive number) as data
+        // overruns should never happen"
+        let transferred = usize::try_from(resid)
+            .ok()
+            .and_then(|residual| allocation_len.checked_sub(residual))
+            .ok_or(ScsiError::InvalidResidual {
+                resid,
+                allocated: u32::try_from(allocation_len).unwrap_or(u32::MAX),
+            })?;
+
+        match self {
+            Self::Strict if transferred < allocation_len => Err(ScsiError::Underrun {
+                transferred,
+                allocated: allocation_len,
+            }),
+            _ => Ok(transferred),
+        }
+    }
+}
+
+/// Why the device node couldn't be opened, so that a Drive that's only briefly in use can be
+/// told apart from one that isn't there or can't be accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum OpenError {
+    /// Another process holds the device exclusively, e.g. a desktop automount just after the
+    /// disc was inserted. Usually transient.
+    #[error("Device node is busy")]
+    Busy,
+    #[error("Device node does not exist")]
+    NotFound,
+    #[error("Permission denied for device node")]
+    PermissionDenied,
+    #[error("open failed: {0}")]
+    Other(Errno),
+}
+
+impl From<Errno> for OpenError {
+    fn from(errno: Errno) -> Self {
+        match errno {
+            Errno::EBUSY => Self::Busy,
+            Errno::ENOENT | Errno::ENODEV | Errno::ENXIO => Self::NotFound,
+            Errno::EACCES | Errno::EPERM => Self::PermissionDenied,
+            errno => Self::Other(errno),
+        }
+    }
+}
+
 // Many of these are straight from the linux source code in linux/include/scsi/sg.h
 
 const SG_IO: u64 = 0x2285;
 
+/// Enough for fixed format sense data with a few bytes of additional sense, and most descriptor
+/// format sense data.
+pub const DEFAULT_SENSE_BUFFER_LEN: u8 = 64;
+
 #[repr(i32)]
 pub enum DxferDirection {
     /// SCSI Test Unit Ready command
@@ -98,33 +178,38 @@ struct SgIoHeader {
 
 ioctl_read_bad!(ioctl_sg_io, SG_IO, SgIoHeader);
 
+/// Returns the number of bytes transferred and left over, and how long the kernel reports the
+/// command took.
 pub fn run_sgio(
     fd: i32,
-    cdb: &mut [u8],
-    allocation_len: usize,
+    cdb: &[u8],
     dxfer_direction: DxferDirection,
-) -> Result<Vec<u8>, ScsiError> {
-    const SENSE_BUF_SIZE: u8 = 64;
-
-    let mut sense = [0u8; SENSE_BUF_SIZE as usize];
+    data: &mut [u8],
+    timeout: Duration,
+    sense_buffer_len: u8,
+    residual_policy: ResidualPolicy,
+) -> Result<Completion, ScsiError> {
+    let mut sense = vec![0u8; sense_buffer_len.into()];
 
-    let mut data = vec![0u8; allocation_len];
+    let allocation_len = data.len();
 
     let cdb_len = cdb.len();
     let cmd_len = u8::try_from(cdb_len).map_err(|_| ScsiError::InvalidCDB(cdb_len))?;
-    let dxfer_len = u32::try_from(allocation_len).map_err(|_| ScsiError::InvalidData(allocation_len))?;
+    let dxfer_len =
+        u32::try_from(allocation_len).map_err(|_| ScsiError::InvalidData(allocation_len))?;
 
     let mut header = SgIoHeader {
         interface_id: 'S' as i32,
         dxfer_direction,
         cmd_len,
-        mx_sb_len: SENSE_BUF_SIZE,
+        mx_sb_len: sense_buffer_len,
         iovec_count: 0,
         dxfer_len,
         dxferp: data.as_mut_ptr() as *mut c_void,
-        cmdp: cdb.as_mut_ptr(),
+        // The kernel only ever reads from the CDB
+        cmdp: cdb.as_ptr() as *mut c_uchar,
         sbp: sense.as_mut_ptr(),
-        timeout: 10_000,
+        timeout: u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX),
         flags: 0,
         pack_id: 0,
         usr_ptr: ptr::null_mut(),
@@ -143,39 +228,32 @@ pub fn run_sgio(
         ioctl_sg_io(fd, &mut header)?;
     }
 
-    let status = StatusCondition::try_from_primitive(header.masked_status).map_err(|_| ScsiError::UnknownStatus(header.masked_status))?;
+    let status = StatusCondition::try_from_primitive(header.masked_status)
+        .map_err(|_| ScsiError::UnknownStatus(header.masked_status))?;
 
     // Note: If status == ConditionGood, then there *is* sense data available, but idk if I really
     // care about that.
-    if matches!(status, StatusCondition::Good | StatusCondition::ConditionGood) {
-        // From the SCSI HOWTO: "In practice it only reports underruns (i.e. positive number) as data
-        // overruns should never happen"
-
-        if let Ok(residual) = usize::try_from(header.resid)
-            && allocation_len > residual
-        {
-            data.truncate(allocation_len - residual);
-            return Ok(data);
-        };
-
-        return Err(ScsiError::InvalidResidual {
-            resid: header.resid,
-            allocated: dxfer_len,
+    if matches!(
+        status,
+        StatusCondition::Good | StatusCondition::ConditionGood
+    ) {
+        let transferred = residual_policy.transferred_len(allocation_len, header.resid)?;
+        return Ok(Completion {
+            transferred,
+            residual: allocation_len - transferred,
+            duration: Duration::from_millis(header.duration.into()),
         });
     }
 
     // If there's sense data, parse it for more details
-    if header.sb_len_wr > 0 {
-        let sk = sense[2] & 0x0F; // Sense key
-        let asc = sense[12]; // Additional Sense Code
-        let ascq = sense[13]; // Additional Sense Code Qualifier
-
-        let Some(mmc_error) = MMCError::from_codes(sk, asc, ascq) else {
+    let sense = &sense[..usize::from(header.sb_len_wr).min(sense.len())];
+    if let Some(sense_data) = SenseData::parse(sense) {
+        let Some(mmc_error) = sense_data.mmc_error() else {
             return Err(ScsiError::UnknownSenseData {
                 status,
-                sk,
-                asc,
-                ascq,
+                sk: sense_data.sense_key,
+                asc: sense_data.asc,
+                ascq: sense_data.ascq,
             });
         };
 
@@ -184,3 +262,165 @@ pub fn run_sgio(
 
     Err(ScsiError::BadStatus(status))
 }
+
+/// The Linux SG_IO [`Transport`], which opens the device node for each submitted command.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SgIo {
+    devnode: String,
+    sense_buffer_len: u8,
+    residual_policy: ResidualPolicy,
+    open_attempts: u32,
+    open_retry_delay: Duration,
+}
+
+impl SgIo {
+    pub fn new(devnode: String) -> Self {
+        Self {
+            devnode,
+            sense_buffer_len: DEFAULT_SENSE_BUFFER_LEN,
+            residual_policy: ResidualPolicy::default(),
+            open_attempts: 1,
+            open_retry_delay: Duration::ZERO,
+        }
+    }
+
+    /// Sets how many bytes of sense data the Drive may return, for Drives with longer descriptor
+    /// format sense data than [`DEFAULT_SENSE_BUFFER_LEN`] covers.
+    pub fn with_sense_buffer_len(mut self, sense_buffer_len: u8) -> Self {
+        self.sense_buffer_len = sense_buffer_len;
+        self
+    }
+
+    /// Sets how a command transferring less data than its allocation length is treated, by default
+    /// [`ResidualPolicy::AllowUnderrun`].
+    pub fn with_residual_policy(mut self, residual_policy: ResidualPolicy) -> Self {
+        self.residual_policy = residual_policy;
+        self
+    }
+
+    /// Retries opening the device node while it's [`OpenError::Busy`], up to `attempts` times in
+    /// total with `delay` between attempts. By default it's only tried once.
+    pub fn with_open_retries(mut self, attempts: u32, delay: Duration) -> Self {
+        self.open_attempts = attempts.max(1);
+        self.open_retry_delay = delay;
+        self
+    }
+
+    pub fn devnode(&self) -> &str {
+        &self.devnode
+    }
+
+    fn get_fd(&self) -> nix::Result<OwnedFd> {
+        open(
+            self.devnode.as_str(),
+            OFlag::O_RDONLY | OFlag::O_NONBLOCK,
+            Mode::empty(),
+        )
+    }
+
+    /// Like [`SgIo::get_fd`], but retries while the device node is busy, see
+    /// [`SgIo::with_open_retries`]. Any other error is returned immediately.
+    pub fn open_with_retry(&self) -> Result<OwnedFd, OpenError> {
+        let mut attempt = 1;
+        loop {
+            match self.get_fd().map_err(OpenError::from) {
+                Err(OpenError::Busy) if attempt < self.open_attempts => {
+                    thread::sleep(self.open_retry_delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Transport for SgIo {
+    fn submit(
+        &self,
+        cdb: &[u8],
+        dir: DxferDirection,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, ScsiError> {
+        self.submit_detailed(cdb, dir, buf, timeout)
+            .map(|completion| completion.transferred)
+    }
+
+    /// Uses the residual and duration the kernel reported, the duration in milliseconds.
+    fn submit_detailed(
+        &self,
+        cdb: &[u8],
+        dir: DxferDirection,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> Result<Completion, ScsiError> {
+        run_sgio(
+            self.open_with_retry()?.as_raw_fd(),
+            cdb,
+            dir,
+            buf,
+            timeout,
+            self.sense_buffer_len,
+            self.residual_policy,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_open_errors() {
+        assert_eq!(OpenError::from(Errno::EBUSY), OpenError::Busy);
+        assert_eq!(OpenError::from(Errno::ENOENT), OpenError::NotFound);
+        assert_eq!(OpenError::from(Errno::EACCES), OpenError::PermissionDenied);
+        assert_eq!(OpenError::from(Errno::EIO), OpenError::Other(Errno::EIO));
+    }
+
+    #[test]
+    fn underrun_allowed() {
+        let policy = ResidualPolicy::AllowUnderrun;
+
+        assert_eq!(policy.transferred_len(4096, 0).unwrap(), 4096);
+        assert_eq!(policy.transferred_len(4096, 4000).unwrap(), 96);
+        assert_eq!(policy.transferred_len(4096, 4096).unwrap(), 0);
+        assert!(matches!(
+            policy.transferred_len(4096, 4097),
+            Err(ScsiError::InvalidResidual {
+                resid: 4097,
+                allocated: 4096
+            })
+        ));
+        assert!(matches!(
+            policy.transferred_len(4096, -1),
+            Err(ScsiError::InvalidResidual { .. })
+        ));
+    }
+
+    #[test]
+    fn strict_underrun() {
+        let policy = ResidualPolicy::Strict;
+
+        assert_eq!(policy.transferred_len(4096, 0).unwrap(), 4096);
+        assert!(matches!(
+            policy.transferred_len(4096, 4000),
+            Err(ScsiError::Underrun {
+                transferred: 96,
+                allocated: 4096
+            })
+        ));
+        assert!(matches!(
+            policy.transferred_len(4096, -1),
+            Err(ScsiError::InvalidResidual { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_device_node_is_not_retried() {
+        let sgio = SgIo::new("/dev/does-not-exist".to_owned())
+            .with_open_retries(3, Duration::from_secs(60));
+
+        assert_eq!(sgio.open_with_retry().unwrap_err(), OpenError::NotFound);
+    }
+}
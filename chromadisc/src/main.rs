@@ -60,7 +60,7 @@ fn main() -> io::Result<()> {
     }
     println!();
 
-    let drive = Drive::new(devices[0].clone());
+    let drive = Drive::open_optical(devices[0].clone()).map_err(io::Error::other)?;
 
     let cmd = ReadTocPmaAtip::<RawToc>::new(0, 4096, 0.into());
 